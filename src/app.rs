@@ -1,15 +1,30 @@
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use egui::{Frame, TextWrapMode, Theme, ThemePreference};
 
-use crate::async_runtime::{self, AsyncRuntimeHandle};
+use crate::async_runtime::AsyncRuntimeHandle;
 use crate::cookies::BasicCookieStore;
 use crate::{
     auth,
-    components::{body_reader_view::BodyReaderView, params_editor_view::ParamsEditorView},
-    core::{RequestId, RequestState},
-    header, http,
-    tiles::{Pane, PaneKind, TreeBehavior},
+    client::{ClientSettings, Clients},
+    collections::{Collections, ItemId},
+    components::{
+        client_settings_view,
+        collection_tree_view::CollectionTreeView,
+        cookies_view::CookiesView,
+        environment_editor_view::EnvironmentEditorView,
+        history_view::HistoryView,
+        import_view::ImportView,
+        runner_view::{RunnerAction, RunnerView},
+    },
+    core::{self, RequestId, RequestState},
+    environment::Environments,
+    header,
+    history::History,
+    http,
+    runner::FolderRun,
+    tasks::{GlobalContext, RequestContext},
+    tiles::{Pane, PaneKind, PaneViews, TreeBehavior},
 };
 
 #[derive(Hash, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
@@ -26,27 +41,52 @@ pub struct App {
 #[derive(serde::Deserialize, serde::Serialize)]
 #[serde(default)] // if we add new fields, give them default values when deserializing old state
 pub struct AppState {
-    state: Vec<(RequestId, RequestState)>,
+    collections: Collections,
 
     active_request_id: RequestId,
 
+    environments: Environments,
+
+    /// Used by requests that don't override them
+    client_settings: ClientSettings,
+
+    #[serde(skip)]
+    settings_open: bool,
+
+    /// Whether cookies are saved on shutdown
+    persist_cookies: bool,
+
     // navigation_tree: egui_tiles::Tree<RequestId>,
     #[serde(skip)]
     request_tree: egui_tiles::Tree<Pane>,
 
     #[serde(skip)]
-    params_view: ParamsEditorView,
+    pane_views: PaneViews,
 
     #[serde(skip)]
-    body_reader_view: BodyReaderView,
-}
+    environment_editor: EnvironmentEditorView,
+
+    #[serde(skip)]
+    collection_tree_view: CollectionTreeView,
+
+    #[serde(skip)]
+    import_view: ImportView,
+
+    #[serde(skip)]
+    history_view: HistoryView,
+
+    #[serde(skip)]
+    cookies_view: CookiesView,
 
-pub struct GlobalContext {
-    pub cookie_jar: Arc<BasicCookieStore>,
-    pub http_client: reqwest::Client,
-    pub async_runtime: async_runtime::AsyncRuntimeHandle,
+    #[serde(skip)]
+    runner_view: RunnerView,
 }
 
+/// Storage key of the request history, kept apart from the app state as it can get big
+const HISTORY_KEY: &str = "history";
+
+const COOKIES_KEY: &str = "cookies";
+
 impl Default for AppState {
     fn default() -> Self {
         let mut next_view_nr = 1;
@@ -70,8 +110,9 @@ impl Default for AppState {
             let left = tiles.insert_pane(gen_view(PaneKind::ResponseStats));
             let middle = tiles.insert_pane(gen_view(PaneKind::ResponseHeaders));
             let right = tiles.insert_pane(gen_view(PaneKind::ResponseBody));
+            let tests = tiles.insert_pane(gen_view(PaneKind::Tests));
 
-            tiles.insert_horizontal_tile(vec![left, middle, right])
+            tiles.insert_horizontal_tile(vec![left, middle, right, tests])
         });
 
         let request_container = tiles.insert_tab_tile(request_tabs);
@@ -80,15 +121,23 @@ impl Default for AppState {
 
         let request_tree = egui_tiles::Tree::new("request_tree", root, tiles);
 
-        let mut state = Vec::with_capacity(10);
-        let request_id = RequestId::next();
-        state.push((request_id, RequestState::default()));
+        let collections = Collections::default();
+        let active_request_id = collections.request_ids()[0];
         Self {
-            state: state,
-            active_request_id: request_id,
+            collections,
+            active_request_id,
+            environments: Default::default(),
+            client_settings: Default::default(),
+            settings_open: false,
+            persist_cookies: false,
             request_tree: request_tree,
-            params_view: Default::default(),
-            body_reader_view: Default::default(),
+            pane_views: Default::default(),
+            environment_editor: Default::default(),
+            collection_tree_view: Default::default(),
+            import_view: Default::default(),
+            history_view: Default::default(),
+            cookies_view: Default::default(),
+            runner_view: Default::default(),
         }
     }
 }
@@ -105,22 +154,40 @@ impl App {
 
         // Load previous app state (if any).
         // Note that you must enable the `persistence` feature for this to work.
-        // if let Some(storage) = cc.storage {
-        //     return eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
-        // }
+        let state: AppState = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, eframe::APP_KEY))
+            .unwrap_or_default();
+        if let Some(max_id) = state.collections.max_id() {
+            core::reserve_ids_up_to(max_id);
+        }
+        let history: History = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, HISTORY_KEY))
+            .unwrap_or_default();
+
         // prefer dark theme by default
         cc.egui_ctx.set_theme(ThemePreference::Dark);
-        let cookie_jar = Arc::new(BasicCookieStore::new());
+        let cookie_jar = match cc
+            .storage
+            .and_then(|storage| storage.get_string(COOKIES_KEY))
+        {
+            Some(json) if state.persist_cookies => BasicCookieStore::from_json(&json),
+            _ => BasicCookieStore::new(),
+        };
+        let cookie_jar = Arc::new(cookie_jar);
         App {
             global_context: GlobalContext {
                 cookie_jar: cookie_jar.clone(),
-                http_client: reqwest::Client::builder()
-                    .cookie_provider(cookie_jar)
-                    .build()
-                    .unwrap(),
+                clients: Clients::new(cookie_jar),
                 async_runtime: async_runtime_handle,
+                history: Arc::new(Mutex::new(history)),
+                script_variables: Default::default(),
+                oauth_tokens: Default::default(),
+                graphql_schemas: Default::default(),
+                grpc_descriptors: Default::default(),
             },
-            state: Default::default(),
+            state,
         }
     }
 }
@@ -129,6 +196,17 @@ impl eframe::App for App {
     /// Called by the frame work to save state before shutdown.
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
         eframe::set_value(storage, eframe::APP_KEY, &self.state);
+        eframe::set_value(
+            storage,
+            HISTORY_KEY,
+            &*self.global_context.history.lock().unwrap(),
+        );
+        let cookies = if self.state.persist_cookies {
+            self.global_context.cookie_jar.to_json()
+        } else {
+            String::new()
+        };
+        storage.set_string(COOKIES_KEY, cookies);
     }
     /// Called each time the UI needs repainting, which may be many times per second.
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
@@ -142,18 +220,42 @@ impl AppState {
         ui.horizontal(|ui| {
             ui.label("No requests yet. ");
             if ui.button("Create One").clicked() {
-                self.state
-                    .push((RequestId::next(), RequestState::default()));
+                if self.collections.items.is_empty() {
+                    self.collections = Collections::default();
+                } else {
+                    let folder_id = self.collections.items[0].id;
+                    self.collections.add_request(folder_id, Default::default());
+                }
             }
         });
     }
 
     fn ui(&mut self, ctx: &egui::Context, global_context: &mut GlobalContext) {
+        for (key, value) in global_context.script_variables.lock().unwrap().drain(..) {
+            if !self.environments.set_variable(&key, &value) {
+                log::warn!("no active environment to save the script variable '{key}' to");
+            }
+        }
+
         egui::TopBottomPanel::top("top_panel")
             .resizable(false)
             .exact_height(32.0)
             .show_separator_line(true)
             .show(ctx, |ui| {
+                ui.horizontal_centered(|ui| {
+                    self.environment_editor
+                        .show_switcher(ui, &mut self.environments);
+                    ui.separator();
+                    if ui.button("Import").clicked() {
+                        self.import_view.open = true;
+                    }
+                    if ui.button("Cookies").clicked() {
+                        self.cookies_view.open = true;
+                    }
+                    if ui.button("\u{e8b8}").on_hover_text("Settings").clicked() {
+                        self.settings_open = true;
+                    }
+                });
 
                 // egui::ComboBox::from_label("Theme").show_ui(ui, |ui| {
                 //     ui.selectable_label(ctx.theme() == Theme::, "Dark", text)
@@ -169,6 +271,21 @@ impl AppState {
                 //     ctx.set_theme(preference);
                 // }
             });
+        self.environment_editor.show(ctx, &mut self.environments);
+        self.collection_tree_view
+            .show_defaults_editor(ctx, &mut self.collections);
+        self.import_view
+            .show(ctx, &mut self.collections, &mut self.environments);
+        self.cookies_view
+            .show(ctx, &global_context.cookie_jar, &mut self.persist_cookies);
+        egui::Window::new("Settings")
+            .open(&mut self.settings_open)
+            .show(ctx, |ui| {
+                ui.label("Requests can override these in their Settings tab");
+                ui.add_space(8.0);
+                client_settings_view::show(ui, &mut self.client_settings);
+            });
+
         egui::TopBottomPanel::bottom("bottom_panel")
             .resizable(false)
             .exact_height(32.0)
@@ -189,42 +306,38 @@ impl AppState {
             ui.separator();
             ui.horizontal(|ui| {
                 ui.label(
-                    egui::RichText::new(format!("Open Requests ({})", self.state.len())).size(16.0),
+                    egui::RichText::new(format!(
+                        "Collections ({})",
+                        self.collections.request_count()
+                    ))
+                    .size(16.0),
                 );
 
                 if ui.button("New Request").clicked() {
-                    self.state.push((RequestId::next(), Default::default()));
+                    self.add_request(Default::default());
+                }
+                if ui.button("New Collection").clicked() {
+                    self.collections
+                        .items
+                        .push(crate::collections::Folder::new("New Collection".to_owned()));
                 }
             });
             // ui.add(egui::TextEdit::singleline(text).hint_text("Search Requests via URL"));
             ui.separator();
 
-            let row_height = ui.text_style_height(&egui::TextStyle::Body);
             ui.scope(|ui| {
                 ui.style_mut().wrap_mode = Some(TextWrapMode::Truncate);
-                egui::ScrollArea::vertical().show_rows(
-                    ui,
-                    row_height,
-                    self.state.len(),
-                    |ui, range| {
-                        for (request_id, state) in self.state.iter() {
-                            let url = if state.url.is_empty() {
-                                "<empty>".to_owned()
-                            } else {
-                                state.url.clone()
-                            };
-                            let label = format!("{}: {} {}", request_id.0, state.method, url);
-
-                            let width = ui.available_width();
-                            if ui
-                                .selectable_label(self.active_request_id == *request_id, label)
-                                .clicked()
-                            {
-                                self.active_request_id = *request_id;
-                            }
-                        }
-                    },
-                )
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    let run = self.collection_tree_view.show(
+                        ui,
+                        &mut self.collections,
+                        &mut self.active_request_id,
+                    );
+                    let folder = run.and_then(|id| self.collections.find_folder(id));
+                    if let Some(folder) = folder {
+                        self.runner_view.open_for(folder.id, folder.name.clone());
+                    }
+                });
             });
 
             ui.allocate_space(ui.available_size());
@@ -245,9 +358,35 @@ impl AppState {
         });
 
         egui::SidePanel::right("side_panel_right").show(ctx, |ui| {
-            ui.heading("Right Panel");
+            let restored = self
+                .history_view
+                .show(ui, &mut global_context.history.lock().unwrap());
+            if let Some(state) = restored {
+                self.add_request(state);
+            }
             ui.allocate_space(ui.available_size());
         });
+        self.history_view
+            .show_diff(ctx, &global_context.history.lock().unwrap());
+        match self.runner_view.show(ctx) {
+            Some(RunnerAction::Start(folder_id, options)) => {
+                let context = RequestContext {
+                    variables: self.environments.variables(),
+                    defaults: Default::default(),
+                    settings: self.client_settings.clone(),
+                };
+                let run = FolderRun::start(
+                    folder_id,
+                    &options,
+                    &self.collections,
+                    &context,
+                    global_context,
+                );
+                self.runner_view.started(run);
+            }
+            Some(RunnerAction::Open(request_id)) => self.active_request_id = request_id,
+            None => {}
+        }
 
         egui::CentralPanel::default()
             .frame(
@@ -256,21 +395,31 @@ impl AppState {
                     .fill(ctx.style().visuals.panel_fill),
             )
             .show(ctx, |ui| {
-                if self.state.is_empty() {
+                let request_ids = self.collections.request_ids();
+                if request_ids.is_empty() {
                     self.empty_ui(ui, ctx);
                 } else {
-                    if !self
-                        .state
-                        .iter()
-                        .any(|(id, _)| *id == self.active_request_id)
-                    {
-                        self.active_request_id = self.state.first().unwrap().0;
+                    if !request_ids.contains(&self.active_request_id) {
+                        self.active_request_id = request_ids[0];
                     }
                     self.request_ui(self.active_request_id, ui, ctx, global_context);
                 }
             });
     }
 
+    /// Adds a request next to the active one and selects it
+    fn add_request(&mut self, state: RequestState) {
+        let folder_id = self
+            .collections
+            .parent_of(ItemId::Request(self.active_request_id))
+            .or_else(|| self.collections.items.first().map(|c| c.id));
+        if let Some(folder_id) = folder_id {
+            if let Some(request_id) = self.collections.add_request(folder_id, state) {
+                self.active_request_id = request_id;
+            }
+        }
+    }
+
     fn request_ui(
         &mut self,
         request_id: RequestId,
@@ -278,16 +427,22 @@ impl AppState {
         ctx: &egui::Context,
         global_context: &mut GlobalContext,
     ) {
-        let mut state = self.state.iter_mut().find(|el| el.0 == request_id);
+        let context = RequestContext {
+            variables: self.environments.variables(),
+            defaults: self.collections.defaults_for(request_id),
+            settings: self.client_settings.clone(),
+        };
+        let state = self.collections.find_request_mut(request_id);
 
-        if let Some((_, state)) = state {
+        if let Some(state) = state {
             // let response = Self::get_response(state);
-            header::show(ui, state, global_context);
+            header::show(ui, state, &context, global_context);
+            self.pane_views.show_request(request_id);
             let mut tiles_behavior = TreeBehavior::default_with_state(
                 state,
+                &context,
                 global_context,
-                &mut self.params_view,
-                &mut self.body_reader_view,
+                &mut self.pane_views,
             );
             self.request_tree.ui(&mut tiles_behavior, ui);
 
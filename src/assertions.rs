@@ -0,0 +1,253 @@
+use std::mem;
+
+use serde_json::Value;
+
+use crate::{environment::Variables, http::HttpResponse};
+
+/// A check on the response, a request passes when all of its enabled assertions do
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Assertion {
+    pub enabled: bool,
+    pub check: Check,
+}
+
+impl Default for Assertion {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            check: Check::StatusEquals(200),
+        }
+    }
+}
+
+/// Text values can use `{{name}}` variables
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum Check {
+    StatusEquals(u16),
+    /// Both ends included
+    StatusInRange {
+        min: u16,
+        max: u16,
+    },
+    HeaderPresent {
+        name: String,
+    },
+    /// The header value matches a regular expression
+    HeaderMatches {
+        name: String,
+        pattern: String,
+    },
+    /// `expected` is compared as JSON, falling back to a plain string
+    JsonPathEquals {
+        path: String,
+        expected: String,
+    },
+    BodyContains(String),
+    /// The body matches a regular expression
+    BodyMatches(String),
+    /// In milliseconds
+    ResponseTimeUnder(u64),
+}
+
+impl Check {
+    /// One of each kind, as a new assertion of that kind starts out
+    pub fn kinds() -> [Check; 8] {
+        [
+            Check::StatusEquals(200),
+            Check::StatusInRange { min: 200, max: 299 },
+            Check::HeaderPresent {
+                name: String::new(),
+            },
+            Check::HeaderMatches {
+                name: String::new(),
+                pattern: String::new(),
+            },
+            Check::JsonPathEquals {
+                path: "$.".to_owned(),
+                expected: String::new(),
+            },
+            Check::BodyContains(String::new()),
+            Check::BodyMatches(String::new()),
+            Check::ResponseTimeUnder(1000),
+        ]
+    }
+
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            Check::StatusEquals(_) => "Status equals",
+            Check::StatusInRange { .. } => "Status in range",
+            Check::HeaderPresent { .. } => "Header present",
+            Check::HeaderMatches { .. } => "Header matches",
+            Check::JsonPathEquals { .. } => "JSON path equals",
+            Check::BodyContains(_) => "Body contains",
+            Check::BodyMatches(_) => "Body matches",
+            Check::ResponseTimeUnder(_) => "Response time under",
+        }
+    }
+
+    pub fn same_kind(&self, other: &Check) -> bool {
+        mem::discriminant(self) == mem::discriminant(other)
+    }
+
+    /// Short sentence saying what is checked, e.g. "status is 200"
+    pub fn describe(&self) -> String {
+        match self {
+            Check::StatusEquals(status) => format!("status is {status}"),
+            Check::StatusInRange { min, max } => format!("status is within {min}-{max}"),
+            Check::HeaderPresent { name } => format!("header {name} is present"),
+            Check::HeaderMatches { name, pattern } => format!("header {name} matches /{pattern}/"),
+            Check::JsonPathEquals { path, expected } => format!("{path} equals {expected}"),
+            Check::BodyContains(text) => format!("body contains \"{text}\""),
+            Check::BodyMatches(pattern) => format!("body matches /{pattern}/"),
+            Check::ResponseTimeUnder(millis) => format!("response time is under {millis} ms"),
+        }
+    }
+
+    /// Err says what was found instead
+    fn evaluate(&self, response: &HttpResponse, variables: &Variables) -> Result<(), String> {
+        match self {
+            Check::StatusEquals(status) => expect(
+                response.status == *status,
+                format!("got {}", response.status),
+            ),
+            Check::StatusInRange { min, max } => expect(
+                (*min..=*max).contains(&response.status),
+                format!("got {}", response.status),
+            ),
+            Check::HeaderPresent { name } => {
+                let name = variables.interpolate(name);
+                find_header(response, &name)
+                    .map(|_| ())
+                    .ok_or_else(|| "not in the response".to_owned())
+            }
+            Check::HeaderMatches { name, pattern } => {
+                let name = variables.interpolate(name);
+                let value =
+                    find_header(response, &name).ok_or_else(|| "not in the response".to_owned())?;
+                let regex = regex(&variables.interpolate(pattern))?;
+                expect(regex.is_match(value), format!("got '{value}'"))
+            }
+            Check::JsonPathEquals { path, expected } => {
+                let body = serde_json::from_str::<Value>(&response.body_raw)
+                    .map_err(|err| format!("body isn't JSON: {err}"))?;
+                let actual = json_path(&body, &variables.interpolate(path))?;
+                let expected = variables.interpolate(expected);
+                expect(json_equals(actual, &expected), format!("got {actual}"))
+            }
+            Check::BodyContains(text) => expect(
+                response.body_raw.contains(&variables.interpolate(text)),
+                "not found in the body".to_owned(),
+            ),
+            Check::BodyMatches(pattern) => {
+                let regex = regex(&variables.interpolate(pattern))?;
+                expect(
+                    regex.is_match(&response.body_raw),
+                    "no match in the body".to_owned(),
+                )
+            }
+            Check::ResponseTimeUnder(millis) => {
+                let took = response.duration.as_millis();
+                expect(took < u128::from(*millis), format!("took {took} ms"))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct AssertionResult {
+    pub description: String,
+    pub passed: bool,
+    /// Why it failed, empty when it passed
+    pub message: String,
+}
+
+/// Runs the enabled assertions against a response
+pub fn evaluate(
+    assertions: &[Assertion],
+    response: &HttpResponse,
+    variables: &Variables,
+) -> Vec<AssertionResult> {
+    assertions
+        .iter()
+        .filter(|assertion| assertion.enabled)
+        .map(|assertion| {
+            let result = assertion.check.evaluate(response, variables);
+            AssertionResult {
+                description: assertion.check.describe(),
+                passed: result.is_ok(),
+                message: result.err().unwrap_or_default(),
+            }
+        })
+        .collect()
+}
+
+fn expect(passed: bool, otherwise: String) -> Result<(), String> {
+    if passed { Ok(()) } else { Err(otherwise) }
+}
+
+fn find_header<'a>(response: &'a HttpResponse, name: &str) -> Option<&'a str> {
+    response
+        .headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name.trim()))
+        .map(|(_, value)| value.as_str())
+}
+
+fn regex(pattern: &str) -> Result<regex::Regex, String> {
+    regex::Regex::new(pattern).map_err(|err| format!("invalid pattern: {err}"))
+}
+
+/// Looks up a value by a simple JSON path like `$.items[0].name` or `$['some key']`,
+/// the leading `$` is optional
+fn json_path<'a>(value: &'a Value, path: &str) -> Result<&'a Value, String> {
+    let path = path.trim();
+    let mut rest = path.strip_prefix('$').unwrap_or(path);
+    let mut current = value;
+    while !rest.is_empty() {
+        let segment;
+        if let Some(after) = rest.strip_prefix('[') {
+            let end = after
+                .find(']')
+                .ok_or_else(|| format!("missing ']' in {path}"))?;
+            segment = after[..end].trim();
+            rest = &after[end + 1..];
+        } else {
+            let after = rest.strip_prefix('.').unwrap_or(rest);
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            segment = &after[..end];
+            rest = &after[end..];
+        }
+
+        let quoted = segment
+            .strip_prefix('\'')
+            .and_then(|s| s.strip_suffix('\''))
+            .or_else(|| segment.strip_prefix('"').and_then(|s| s.strip_suffix('"')));
+        current = match (current, quoted) {
+            (Value::Array(items), None) => {
+                let index = segment
+                    .parse::<usize>()
+                    .map_err(|_| format!("'{segment}' isn't an index of an array"))?;
+                items
+                    .get(index)
+                    .ok_or_else(|| format!("index {index} is out of bounds"))?
+            }
+            (Value::Object(entries), key) => {
+                let key = key.unwrap_or(segment);
+                entries
+                    .get(key)
+                    .ok_or_else(|| format!("'{key}' not found"))?
+            }
+            _ => return Err(format!("can't look up '{segment}' in {current}")),
+        };
+    }
+    Ok(current)
+}
+
+fn json_equals(actual: &Value, expected: &str) -> bool {
+    match serde_json::from_str::<Value>(expected.trim()) {
+        // 1 and 1.0 parse into different numbers
+        Ok(Value::Number(expected)) => actual.as_f64() == expected.as_f64(),
+        Ok(expected) => *actual == expected,
+        Err(_) => actual.as_str() == Some(expected),
+    }
+}
@@ -12,7 +12,7 @@ impl<T: Send> SendNativeOnly for T {}
 #[cfg(target_arch = "wasm32")]
 impl<T> SendNativeOnly for T {}
 
-// #[derive(Clone)]
+#[derive(Clone)]
 pub struct AsyncRuntimeHandle {
     #[cfg(not(target_arch = "wasm32"))]
     runtime: tokio::runtime::Handle,
@@ -52,3 +52,23 @@ impl AsyncRuntimeHandle {
         wasm_bindgen_futures::spawn_local(future);
     }
 }
+
+// native impl
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn sleep(duration: std::time::Duration) {
+    tokio::time::sleep(duration).await;
+}
+
+// wasm32 impl, there's no tokio timer in the browser so it waits on a js timeout
+#[cfg(target_arch = "wasm32")]
+pub async fn sleep(duration: std::time::Duration) {
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        if let Some(window) = web_sys::window() {
+            let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(
+                &resolve,
+                duration.as_millis().min(i32::MAX as u128) as i32,
+            );
+        }
+    });
+    let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+}
@@ -0,0 +1,211 @@
+use md5::Md5;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    auth::{nonce, quote, set_header},
+    http::HttpError,
+    util,
+};
+
+/// Answers the challenge of a 401 response by setting the Authorization header
+pub fn apply(
+    request: &mut http::Request<Vec<u8>>,
+    username: &str,
+    password: &str,
+    challenge: &[(String, String)],
+) -> Result<(), HttpError> {
+    let header = authorization(username, password, challenge, request).map_err(HttpError::Auth)?;
+    set_header(request, "Authorization", &header)
+}
+
+/// Params of the Digest challenge among the WWW-Authenticate headers of a response
+pub fn find_challenge(headers: &[(String, String)]) -> Option<Vec<(String, String)>> {
+    headers
+        .iter()
+        .filter(|(name, _)| name.eq_ignore_ascii_case("www-authenticate"))
+        .find_map(|(_, value)| {
+            // a header can hold several challenges, like `Basic realm="a", Digest realm="a"`
+            let lower = value.to_ascii_lowercase();
+            let start = lower.match_indices("digest").find_map(|(index, _)| {
+                let before = lower[..index].trim_end();
+                let after = &lower[index + "digest".len()..];
+                ((before.is_empty() || before.ends_with(',')) && after.starts_with(' '))
+                    .then_some(index + "digest".len())
+            })?;
+            Some(parse_params(&value[start..]))
+        })
+}
+
+fn authorization(
+    username: &str,
+    password: &str,
+    challenge: &[(String, String)],
+    request: &http::Request<Vec<u8>>,
+) -> Result<String, String> {
+    let uri = request
+        .uri()
+        .path_and_query()
+        .map_or("/", |path| path.as_str());
+    response_header(
+        username,
+        password,
+        challenge,
+        request.method().as_str(),
+        uri,
+        request.body(),
+        &nonce(16)?,
+    )
+}
+
+fn response_header(
+    username: &str,
+    password: &str,
+    challenge: &[(String, String)],
+    method: &str,
+    uri: &str,
+    body: &[u8],
+    cnonce: &str,
+) -> Result<String, String> {
+    let param = |name: &str| {
+        challenge
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    };
+    let realm = param("realm").unwrap_or_default();
+    let nonce = param("nonce").ok_or("the Digest challenge has no nonce")?;
+    let algorithm = param("algorithm").unwrap_or("MD5");
+    let (hash, session): (fn(&[u8]) -> String, bool) = match algorithm.to_ascii_uppercase().as_str()
+    {
+        "MD5" => (md5_hex, false),
+        "MD5-SESS" => (md5_hex, true),
+        "SHA-256" => (sha256_hex, false),
+        "SHA-256-SESS" => (sha256_hex, true),
+        _ => return Err(format!("Digest algorithm {algorithm} isn't supported")),
+    };
+    // auth when the server allows it, auth-int only when it's all it takes
+    let offered = param("qop")
+        .map(|qop| qop.split(',').map(str::trim).collect::<Vec<_>>())
+        .unwrap_or_default();
+    let qop = if offered.is_empty() {
+        None
+    } else if offered.contains(&"auth") {
+        Some("auth")
+    } else if offered.contains(&"auth-int") {
+        Some("auth-int")
+    } else {
+        return Err(format!("Digest qop {} isn't supported", offered.join(", ")));
+    };
+    let nc = "00000001";
+
+    let mut ha1 = hash(format!("{username}:{realm}:{password}").as_bytes());
+    if session {
+        ha1 = hash(format!("{ha1}:{nonce}:{cnonce}").as_bytes());
+    }
+    let ha2 = match qop {
+        Some("auth-int") => hash(format!("{method}:{uri}:{}", hash(body)).as_bytes()),
+        _ => hash(format!("{method}:{uri}").as_bytes()),
+    };
+    let response = match qop {
+        Some(qop) => hash(format!("{ha1}:{nonce}:{nc}:{cnonce}:{qop}:{ha2}").as_bytes()),
+        None => hash(format!("{ha1}:{nonce}:{ha2}").as_bytes()),
+    };
+
+    let mut header = format!(
+        "Digest username={}, realm={}, nonce={}, uri={}, algorithm={algorithm}, response=\"{response}\"",
+        quote(username),
+        quote(realm),
+        quote(nonce),
+        quote(uri),
+    );
+    if let Some(qop) = qop {
+        header.push_str(&format!(", qop={qop}, nc={nc}, cnonce={}", quote(cnonce)));
+    }
+    if let Some(opaque) = param("opaque") {
+        header.push_str(&format!(", opaque={}", quote(opaque)));
+    }
+    Ok(header)
+}
+
+/// `key=value` pairs separated by commas, values may be quoted and hold commas then
+fn parse_params(input: &str) -> Vec<(String, String)> {
+    let mut params = vec![];
+    let mut chars = input.chars().peekable();
+    loop {
+        let key = chars
+            .by_ref()
+            .skip_while(|c| *c == ',' || c.is_whitespace())
+            .take_while(|c| *c != '=')
+            .collect::<String>();
+        if key.is_empty() {
+            return params;
+        }
+        let mut value = String::new();
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => value.extend(chars.next()),
+                    '"' => break,
+                    c => value.push(c),
+                }
+            }
+            // up to the comma ending it
+            chars.by_ref().take_while(|c| *c != ',').for_each(drop);
+        } else {
+            value = chars.by_ref().take_while(|c| *c != ',').collect();
+        }
+        params.push((key.trim().to_owned(), value.trim().to_owned()));
+    }
+}
+
+fn md5_hex(bytes: &[u8]) -> String {
+    util::hex(&Md5::digest(bytes), "")
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    util::hex(&Sha256::digest(bytes), "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 7616 3.9.1
+    fn challenge(algorithm: &str) -> Vec<(String, String)> {
+        parse_params(&format!(
+            r#"realm="http-auth@example.org", qop="auth, auth-int", algorithm={algorithm}, nonce="7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v", opaque="FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS""#
+        ))
+    }
+
+    fn header(algorithm: &str) -> String {
+        response_header(
+            "Mufasa",
+            "Circle of Life",
+            &challenge(algorithm),
+            "GET",
+            "/dir/index.html",
+            b"",
+            "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn rfc_7616_md5() {
+        assert_eq!(
+            header("MD5"),
+            r#"Digest username="Mufasa", realm="http-auth@example.org", nonce="7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v", uri="/dir/index.html", algorithm=MD5, response="8ca523f5e9506fed4657c9700eebdbec", qop=auth, nc=00000001, cnonce="f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ", opaque="FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS""#
+        );
+    }
+
+    #[test]
+    fn rfc_7616_sha_256() {
+        let header = header("SHA-256");
+        assert!(header.contains("algorithm=SHA-256"));
+        assert!(header.contains(
+            r#"response="753927fa0e85d155564e2e272a28d1802ca10daf4496794697cf8db5856cb6c1""#
+        ));
+        assert!(header.contains("qop=auth, nc=00000001"));
+    }
+}
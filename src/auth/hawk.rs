@@ -0,0 +1,127 @@
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::auth::{HawkAlgorithm, HawkParams, nonce, quote};
+
+/// The Authorization header signing the request
+pub fn authorization(
+    request: &http::Request<Vec<u8>>,
+    params: &HawkParams,
+) -> Result<String, String> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|err| err.to_string())?
+        .as_secs();
+    let content_type = request
+        .headers()
+        .get(http::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    header(
+        params,
+        request.method().as_str(),
+        request.uri(),
+        content_type,
+        request.body(),
+        timestamp,
+        &nonce(6)?,
+    )
+}
+
+fn header(
+    params: &HawkParams,
+    method: &str,
+    uri: &http::Uri,
+    content_type: &str,
+    body: &[u8],
+    timestamp: u64,
+    nonce: &str,
+) -> Result<String, String> {
+    if params.id.is_empty() || params.key.is_empty() {
+        return Err("Hawk needs an id and a key".to_owned());
+    }
+    let host = uri.host().ok_or("the url has no host")?.to_lowercase();
+    let port = uri
+        .port_u16()
+        .unwrap_or(if uri.scheme_str() == Some("https") {
+            443
+        } else {
+            80
+        });
+    let resource = uri.path_and_query().map_or("/", |path| path.as_str());
+
+    let hash = if params.include_payload_hash {
+        // the media type only, without parameters like the charset
+        let content_type = content_type.split(';').next().unwrap_or_default();
+        let mut payload =
+            format!("hawk.1.payload\n{}\n", content_type.trim().to_lowercase()).into_bytes();
+        payload.extend_from_slice(body);
+        payload.push(b'\n');
+        base64(&match params.algorithm {
+            HawkAlgorithm::Sha256 => Sha256::digest(&payload).to_vec(),
+            HawkAlgorithm::Sha1 => Sha1::digest(&payload).to_vec(),
+        })
+    } else {
+        String::new()
+    };
+    let ext = &params.ext;
+    let normalized = format!(
+        "hawk.1.header\n{timestamp}\n{nonce}\n{}\n{resource}\n{host}\n{port}\n{hash}\n{ext}\n",
+        method.to_uppercase()
+    );
+    let mac = match params.algorithm {
+        HawkAlgorithm::Sha256 => sign::<Hmac<Sha256>>(&params.key, &normalized),
+        HawkAlgorithm::Sha1 => sign::<Hmac<Sha1>>(&params.key, &normalized),
+    };
+
+    let mut header = format!(
+        "Hawk id={}, ts=\"{timestamp}\", nonce={}",
+        quote(&params.id),
+        quote(nonce)
+    );
+    if !hash.is_empty() {
+        header.push_str(&format!(", hash=\"{hash}\""));
+    }
+    if !ext.is_empty() {
+        header.push_str(&format!(", ext={}", quote(ext)));
+    }
+    header.push_str(&format!(", mac=\"{mac}\""));
+    Ok(header)
+}
+
+fn sign<M: Mac + hmac::digest::KeyInit>(key: &str, message: &str) -> String {
+    let mut mac = <M as Mac>::new_from_slice(key.as_bytes()).expect("hmac takes keys of any size");
+    mac.update(message.as_bytes());
+    base64(&mac.finalize().into_bytes())
+}
+
+fn base64(bytes: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the example of the Hawk readme
+    #[test]
+    fn spec_example() {
+        let params = HawkParams {
+            id: "dh37fgj492je".to_owned(),
+            key: "werxhqb98rpaxn39848xrunpaw3489ruxnpa98w4rxn".to_owned(),
+            algorithm: HawkAlgorithm::Sha256,
+            ext: "some-app-ext-data".to_owned(),
+            include_payload_hash: false,
+        };
+        let uri = "http://example.com:8000/resource/1?b=1&a=2"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            header(&params, "GET", &uri, "", b"", 1353832234, "j4h3g2").unwrap(),
+            r#"Hawk id="dh37fgj492je", ts="1353832234", nonce="j4h3g2", ext="some-app-ext-data", mac="6R4rV5iE+NPoym+WwjeHzjAGXUtLNIxmo1vpMofpLAE=""#
+        );
+    }
+}
@@ -1,9 +1,15 @@
+pub(crate) mod digest;
+pub(crate) mod hawk;
+pub(crate) mod jwt;
+pub(crate) mod oauth2;
 pub(crate) mod sigv4;
 
 use std::str::FromStr;
 
 use base64::Engine;
 
+use crate::{environment::Variables, http::HttpError};
+
 #[derive(Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum AuthLocation {
     #[default]
@@ -11,40 +17,319 @@ pub enum AuthLocation {
     Query,
 }
 
-#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct ApiKeyParams {
     pub key: String,
     pub value: String,
     pub location: AuthLocation,
 }
 
-#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+/// Where the AWS keys are taken from
+#[derive(Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum AwsCredentials {
+    /// The keys typed into the auth
+    #[default]
+    Keys,
+    /// A profile of `~/.aws/credentials` and `~/.aws/config`
+    Profile,
+    /// `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and `AWS_SESSION_TOKEN`
+    Environment,
+}
+
+impl std::fmt::Display for AwsCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Keys => write!(f, "Access Keys"),
+            Self::Profile => write!(f, "AWS Profile"),
+            Self::Environment => write!(f, "Environment Variables"),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct SigV4Params {
+    pub credentials: AwsCredentials,
     pub access_key: String,
     pub secret_key: String,
     pub session_token: String,
+    /// Empty uses `AWS_PROFILE`, or the default profile
+    pub profile: String,
     pub service: String,
+    /// Empty uses the region of the profile or `AWS_REGION`. With SigV4a a comma separated
+    /// region set, `*` for all of them
     pub region: String,
+    /// Signs `UNSIGNED-PAYLOAD` instead of a hash of the body
+    pub unsigned_payload: bool,
+    /// Puts the signature in the query rather than the headers, so the url works on its own
+    pub presign: bool,
+    /// Seconds a presigned url stays valid
+    pub expires_in: u64,
+    /// Signs with SigV4a, for multi-region endpoints
+    pub sigv4a: bool,
+}
+
+impl Default for SigV4Params {
+    fn default() -> Self {
+        Self {
+            credentials: AwsCredentials::default(),
+            access_key: String::new(),
+            secret_key: String::new(),
+            session_token: String::new(),
+            profile: String::new(),
+            service: String::new(),
+            region: String::new(),
+            unsigned_payload: false,
+            presign: false,
+            expires_in: 3600,
+            sigv4a: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum GrantType {
+    #[default]
+    ClientCredentials,
+    Password,
+    /// With a browser, the redirect is caught by listening on the loopback redirect uri
+    AuthorizationCode,
+    /// Trades a refresh token obtained elsewhere
+    RefreshToken,
+}
+
+impl std::fmt::Display for GrantType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ClientCredentials => write!(f, "Client Credentials"),
+            Self::Password => write!(f, "Password"),
+            Self::AuthorizationCode => write!(f, "Authorization Code"),
+            Self::RefreshToken => write!(f, "Refresh Token"),
+        }
+    }
+}
+
+/// How the client id and secret are sent to the token endpoint
+#[derive(Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum ClientAuthentication {
+    #[default]
+    BasicHeader,
+    Body,
+}
+
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct OAuth2Params {
+    pub grant_type: GrantType,
+    pub token_url: String,
+    /// Authorization code grant only
+    pub auth_url: String,
+    /// Authorization code grant only, a loopback url like `http://127.0.0.1:7777/callback`
+    pub redirect_uri: String,
+    /// Authorization code grant only
+    pub pkce: bool,
+    pub client_id: String,
+    pub client_secret: String,
+    pub client_authentication: ClientAuthentication,
+    /// Space separated
+    pub scope: String,
+    /// Password grant only
+    pub username: String,
+    /// Password grant only
+    pub password: String,
+    /// Refresh token grant only
+    pub refresh_token: String,
+}
+
+impl Default for OAuth2Params {
+    fn default() -> Self {
+        Self {
+            grant_type: Default::default(),
+            token_url: String::new(),
+            auth_url: String::new(),
+            redirect_uri: "http://127.0.0.1:7777/callback".to_owned(),
+            pkce: true,
+            client_id: String::new(),
+            client_secret: String::new(),
+            client_authentication: Default::default(),
+            scope: String::new(),
+            username: String::new(),
+            password: String::new(),
+            refresh_token: String::new(),
+        }
+    }
+}
+
+impl OAuth2Params {
+    pub fn interpolate(&self, variables: &Variables) -> Self {
+        Self {
+            grant_type: self.grant_type,
+            token_url: variables.interpolate(&self.token_url),
+            auth_url: variables.interpolate(&self.auth_url),
+            redirect_uri: variables.interpolate(&self.redirect_uri),
+            pkce: self.pkce,
+            client_id: variables.interpolate(&self.client_id),
+            client_secret: variables.interpolate(&self.client_secret),
+            client_authentication: self.client_authentication,
+            scope: variables.interpolate(&self.scope),
+            username: variables.interpolate(&self.username),
+            password: variables.interpolate(&self.password),
+            refresh_token: variables.interpolate(&self.refresh_token),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum HawkAlgorithm {
+    #[default]
+    Sha256,
+    Sha1,
+}
+
+#[derive(Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct HawkParams {
+    pub id: String,
+    pub key: String,
+    pub algorithm: HawkAlgorithm,
+    /// App specific data sent along, covered by the signature
+    pub ext: String,
+    /// Also signs the body and its content type
+    pub include_payload_hash: bool,
+}
+
+impl HawkParams {
+    pub fn interpolate(&self, variables: &Variables) -> Self {
+        Self {
+            id: variables.interpolate(&self.id),
+            key: variables.interpolate(&self.key),
+            algorithm: self.algorithm,
+            ext: variables.interpolate(&self.ext),
+            include_payload_hash: self.include_payload_hash,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum JwtAlgorithm {
+    #[default]
+    Hs256,
+    Rs256,
+    Es256,
+}
+
+impl std::fmt::Display for JwtAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Hs256 => write!(f, "HS256"),
+            Self::Rs256 => write!(f, "RS256"),
+            Self::Es256 => write!(f, "ES256"),
+        }
+    }
+}
+
+/// A token signed when the request is sent, see `jwt::sign`
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct JwtParams {
+    pub algorithm: JwtAlgorithm,
+    /// The HMAC secret for HS256, a PEM private key otherwise
+    pub secret: String,
+    pub secret_base64: bool,
+    /// JSON object of extra header fields, `alg` is always set from the algorithm
+    pub header: String,
+    /// JSON object of the claims
+    pub claims: String,
+    /// Seconds until `exp`, 0 leaves it out
+    pub expires_in: u64,
+    /// Put before the token in the Authorization header
+    pub prefix: String,
+}
+
+impl Default for JwtParams {
+    fn default() -> Self {
+        Self {
+            algorithm: JwtAlgorithm::default(),
+            secret: String::new(),
+            secret_base64: false,
+            header: String::new(),
+            claims: "{\n  \"sub\": \"\"\n}".to_owned(),
+            expires_in: 300,
+            prefix: "Bearer".to_owned(),
+        }
+    }
+}
+
+impl JwtParams {
+    pub fn interpolate(&self, variables: &Variables) -> Self {
+        Self {
+            algorithm: self.algorithm,
+            secret: variables.interpolate(&self.secret),
+            secret_base64: self.secret_base64,
+            header: variables.interpolate(&self.header),
+            claims: variables.interpolate(&self.claims),
+            expires_in: self.expires_in,
+            prefix: variables.interpolate(&self.prefix),
+        }
+    }
+}
+
+impl ApiKeyParams {
+    pub fn interpolate(&self, variables: &Variables) -> Self {
+        Self {
+            key: variables.interpolate(&self.key),
+            value: variables.interpolate(&self.value),
+            location: self.location,
+        }
+    }
+}
+
+impl SigV4Params {
+    pub fn interpolate(&self, variables: &Variables) -> Self {
+        Self {
+            credentials: self.credentials,
+            access_key: variables.interpolate(&self.access_key),
+            secret_key: variables.interpolate(&self.secret_key),
+            session_token: variables.interpolate(&self.session_token),
+            profile: variables.interpolate(&self.profile),
+            service: variables.interpolate(&self.service),
+            region: variables.interpolate(&self.region),
+            unsigned_payload: self.unsigned_payload,
+            presign: self.presign,
+            expires_in: self.expires_in,
+            sigv4a: self.sigv4a,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Default, serde::Serialize, serde::Deserialize)]
 pub enum RequestAuthType {
     #[default]
+    Inherit,
     None,
     BasicAuth,
     Bearer,
     ApiKey,
     AwsSigV4,
+    OAuth2,
+    Digest,
+    Hawk,
+    Jwt,
 }
 
 impl std::fmt::Display for RequestAuthType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            Self::Inherit => write!(f, "Inherit from parent"),
             Self::None => write!(f, "No auth"),
             Self::BasicAuth => write!(f, "Basic Auth"),
             Self::Bearer => write!(f, "Bearer"),
             Self::ApiKey => write!(f, "API Key"),
             Self::AwsSigV4 => write!(f, "AWS SigV4"),
+            Self::OAuth2 => write!(f, "OAuth 2.0"),
+            Self::Digest => write!(f, "Digest Auth"),
+            Self::Hawk => write!(f, "Hawk"),
+            Self::Jwt => write!(f, "JWT Bearer"),
         }
     }
 }
@@ -52,18 +337,25 @@ impl std::fmt::Display for RequestAuthType {
 impl std::fmt::Display for RequestAuth {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            Self::Inherit => write!(f, "Inherit from parent"),
             Self::None => write!(f, "No auth"),
             Self::BasicAuth { .. } => write!(f, "Basic Auth"),
             Self::Bearer { .. } => write!(f, "Bearer"),
             Self::ApiKey(..) => write!(f, "API Key"),
             Self::AwsSigV4(..) => write!(f, "AWS SigV4"),
+            Self::OAuth2(..) => write!(f, "OAuth 2.0"),
+            Self::Digest { .. } => write!(f, "Digest Auth"),
+            Self::Hawk(..) => write!(f, "Hawk"),
+            Self::Jwt(..) => write!(f, "JWT Bearer"),
         }
     }
 }
 
-#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum RequestAuth {
+    /// Uses the auth of the closest parent folder that sets one
     #[default]
+    Inherit,
     None,
     BasicAuth {
         username: String,
@@ -74,41 +366,205 @@ pub enum RequestAuth {
     },
     ApiKey(ApiKeyParams),
     AwsSigV4(SigV4Params),
+    /// Sent as a bearer token, fetched and refreshed as needed, see `oauth2::access_token`
+    OAuth2(OAuth2Params),
+    /// Answers the server's challenge, so the request is first sent without auth
+    Digest {
+        username: String,
+        password: String,
+    },
+    Hawk(HawkParams),
+    /// A bearer token signed with fresh `iat` and `exp` each time the request is sent
+    Jwt(JwtParams),
 }
 
 impl RequestAuth {
-    pub fn apply(self, request: &mut http::Request<Vec<u8>>) {
+    /// Copy of the auth with `{{name}}` variables substituted in every field
+    pub fn interpolate(&self, variables: &Variables) -> Self {
+        match self {
+            RequestAuth::Inherit => RequestAuth::Inherit,
+            RequestAuth::None => RequestAuth::None,
+            RequestAuth::BasicAuth { username, password } => RequestAuth::BasicAuth {
+                username: variables.interpolate(username),
+                password: variables.interpolate(password),
+            },
+            RequestAuth::Bearer { token } => RequestAuth::Bearer {
+                token: variables.interpolate(token),
+            },
+            RequestAuth::ApiKey(params) => RequestAuth::ApiKey(params.interpolate(variables)),
+            RequestAuth::AwsSigV4(params) => RequestAuth::AwsSigV4(params.interpolate(variables)),
+            RequestAuth::OAuth2(params) => RequestAuth::OAuth2(params.interpolate(variables)),
+            RequestAuth::Digest { username, password } => RequestAuth::Digest {
+                username: variables.interpolate(username),
+                password: variables.interpolate(password),
+            },
+            RequestAuth::Hawk(params) => RequestAuth::Hawk(params.interpolate(variables)),
+            RequestAuth::Jwt(params) => RequestAuth::Jwt(params.interpolate(variables)),
+        }
+    }
+
+    /// Auth applied while sending, after fetching a token or getting a challenge
+    pub fn needs_round_trip(&self) -> bool {
+        matches!(self, RequestAuth::OAuth2(_) | RequestAuth::Digest { .. })
+    }
+
+    /// Runs last on the built request, so anything signing it covers the final url and headers
+    pub fn apply(self, request: &mut http::Request<Vec<u8>>) -> Result<(), HttpError> {
         match self {
             RequestAuth::BasicAuth { username, password } => {
                 let value = format!("{}:{}", username, password);
                 let encoded_value = base64::engine::general_purpose::STANDARD.encode(value);
-                request.headers_mut().insert(
-                    http::header::AUTHORIZATION,
-                    http::HeaderValue::from_str(&format!("Basic {encoded_value}")).unwrap(),
-                );
+                set_header(request, "Authorization", &format!("Basic {encoded_value}"))?;
             }
             RequestAuth::Bearer { token } => {
-                request.headers_mut().insert(
-                    http::header::AUTHORIZATION,
-                    http::HeaderValue::from_str(&format!("Bearer {token}")).unwrap(),
-                );
+                set_header(request, "Authorization", &format!("Bearer {token}"))?;
             }
             RequestAuth::ApiKey(params) => match params.location {
-                AuthLocation::Headers => {
-                    request.headers_mut().insert(
-                        http::HeaderName::from_str(&params.key).unwrap(),
-                        http::HeaderValue::from_str(&params.value).unwrap(),
-                    );
-                }
-                _ => {} // AuthLocation::Query => request.set_query_param(&params.key, &params.value),
+                AuthLocation::Headers => set_header(request, &params.key, &params.value)?,
+                AuthLocation::Query => set_query_param(request, &params.key, &params.value)?,
             },
-            RequestAuth::AwsSigV4(params) => match sigv4::apply(request, params) {
-                Ok(_) => {}
-                Err(err) => {
-                    log::error!("failed to sign request {}", err);
-                }
-            },
-            RequestAuth::None => {}
+            RequestAuth::AwsSigV4(params) => {
+                sigv4::apply(request, params).map_err(HttpError::Auth)?
+            }
+            RequestAuth::Hawk(params) => {
+                let header = hawk::authorization(request, &params).map_err(HttpError::Auth)?;
+                set_header(request, "Authorization", &header)?;
+            }
+            RequestAuth::Jwt(params) => {
+                let token = jwt::sign(&params).map_err(HttpError::Auth)?;
+                let value = match params.prefix.trim() {
+                    "" => token,
+                    prefix => format!("{prefix} {token}"),
+                };
+                set_header(request, "Authorization", &value)?;
+            }
+            // there's no token or challenge to answer yet, both come from a round trip
+            // made while sending, see `needs_round_trip`
+            RequestAuth::OAuth2(_) | RequestAuth::Digest { .. } => {}
+            RequestAuth::Inherit | RequestAuth::None => {}
         }
+        Ok(())
+    }
+}
+
+/// Replaces any param of the same name already in the url, the others are kept as they're encoded
+fn set_query_param(
+    request: &mut http::Request<Vec<u8>>,
+    name: &str,
+    value: &str,
+) -> Result<(), HttpError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(HttpError::Auth(
+            "the API key has no name to put in the query".to_owned(),
+        ));
+    }
+
+    let url = request.uri().to_string();
+    let (base, query) = url.split_once('?').unwrap_or((&url, ""));
+    let mut pairs = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .filter(|pair| {
+            let key = pair.split_once('=').map_or(*pair, |(key, _)| key);
+            serde_urlencoded::from_str::<Vec<(String, String)>>(key)
+                .ok()
+                .and_then(|decoded| decoded.into_iter().next())
+                .is_none_or(|(key, _)| key != name)
+        })
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    pairs.push(
+        serde_urlencoded::to_string([(name, value)])
+            .map_err(|err| HttpError::Auth(format!("failed to encode the API key: {err}")))?,
+    );
+
+    let url = format!("{base}?{}", pairs.join("&"));
+    *request.uri_mut() = http::Uri::from_str(&url).map_err(|err| HttpError::InvalidUrl {
+        url: url.clone(),
+        reason: err.to_string(),
+    })?;
+    Ok(())
+}
+
+/// Url safe random string from `bytes` random bytes
+fn nonce(bytes: usize) -> Result<String, String> {
+    let mut random = vec![0; bytes];
+    getrandom::getrandom(&mut random).map_err(|err| err.to_string())?;
+    Ok(base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(random))
+}
+
+/// Quoted string of an auth header param
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn set_header(
+    request: &mut http::Request<Vec<u8>>,
+    name: &str,
+    value: &str,
+) -> Result<(), HttpError> {
+    let invalid = |reason: String| HttpError::InvalidHeader {
+        name: name.to_owned(),
+        reason,
+    };
+    let header_name = http::HeaderName::from_str(name).map_err(|err| invalid(err.to_string()))?;
+    let header_value =
+        http::HeaderValue::from_str(value).map_err(|err| invalid(err.to_string()))?;
+    request.headers_mut().insert(header_name, header_value);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_key(url: &str, name: &str, value: &str) -> String {
+        let mut request = http::Request::get(url).body(vec![]).unwrap();
+        set_query_param(&mut request, name, value).unwrap();
+        request.uri().to_string()
+    }
+
+    #[test]
+    fn replaces_existing_key() {
+        assert_eq!(
+            with_key("https://example.com/a?api_key=old&x=1", "api_key", "new"),
+            "https://example.com/a?x=1&api_key=new"
+        );
+        assert_eq!(
+            with_key("https://example.com/a", "api_key", "new"),
+            "https://example.com/a?api_key=new"
+        );
+    }
+
+    #[test]
+    fn replaces_duplicate_keys() {
+        // also when the key is encoded in the url
+        assert_eq!(
+            with_key(
+                "https://example.com/a?key=1&x=2&key=3&k%65y=4",
+                "key",
+                "new"
+            ),
+            "https://example.com/a?x=2&key=new"
+        );
+    }
+
+    #[test]
+    fn drops_fragment() {
+        // http::Uri leaves the fragment out, it never ends up in the query
+        assert_eq!(
+            with_key("https://example.com/a?x=1#section", "key", "new"),
+            "https://example.com/a?x=1&key=new"
+        );
+    }
+
+    #[test]
+    fn keeps_encoding() {
+        // other params stay as they're encoded, the key's value is taken literally
+        assert_eq!(
+            with_key("https://example.com/a?q=a%20b%26c", "key", "a b&c%2F"),
+            "https://example.com/a?q=a%20b%26c&key=a+b%26c%252F"
+        );
     }
 }
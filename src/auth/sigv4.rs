@@ -1,26 +1,35 @@
+use std::time::Duration;
+
 use aws_credential_types::Credentials;
 use aws_sigv4::{
-    http_request::{SignableBody, SignableRequest, SigningSettings, sign},
-    sign::v4,
+    http_request::{
+        PayloadChecksumKind, SignableBody, SignableRequest, SignatureLocation, SigningParams,
+        SigningSettings, sign,
+    },
+    sign::{v4, v4a},
 };
 
-use crate::auth::SigV4Params;
+use crate::auth::{AwsCredentials, SigV4Params};
 
-pub fn apply(
-    request: &mut http::Request<Vec<u8>>,
-    params: SigV4Params,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let region = params.region;
-    let service = params.service;
+pub fn apply(request: &mut http::Request<Vec<u8>>, params: SigV4Params) -> Result<(), String> {
+    let keys = keys(&params)?;
+    let region = match params.region.trim() {
+        "" => keys.region.clone().unwrap_or_default(),
+        region => region.to_owned(),
+    };
+    if region.is_empty() {
+        return Err("AWS SigV4 needs a region to sign for".to_owned());
+    }
+    let service = params.service.trim();
 
-    let creds = Credentials::new(
-        params.access_key,
-        params.secret_key,
-        Some(params.session_token),
+    let identity = Credentials::new(
+        keys.access_key,
+        keys.secret_key,
+        keys.session_token,
         None,
-        "test_provider",
-    );
-    let identity = creds.into();
+        keys.provider,
+    )
+    .into();
 
     // aws sdk doesn't support wasm as it asks for SystemTime::now()
     // here we construct std::time::SystemTime from the web_time's timestamp
@@ -30,29 +39,208 @@ pub fn apply(
         .unwrap();
     let std_time = std::time::SystemTime::UNIX_EPOCH + timestamp_as_duration;
 
-    let signing_settings = SigningSettings::default();
-    let signing_params = v4::SigningParams::builder()
-        .identity(&identity)
-        .region(&region)
-        .name(&service)
-        .time(std_time)
-        .settings(signing_settings)
-        .build()?
-        .into();
+    let mut signing_settings = SigningSettings::default();
+    if params.presign {
+        signing_settings.signature_location = SignatureLocation::QueryParams;
+        signing_settings.expires_in = Some(Duration::from_secs(params.expires_in.max(1)));
+    } else if service == "s3" {
+        // s3 refuses requests without the payload hash in a header
+        signing_settings.payload_checksum_kind = PayloadChecksumKind::XAmzSha256;
+    }
+    let signing_params: SigningParams<'_> = if params.sigv4a {
+        v4a::SigningParams::builder()
+            .identity(&identity)
+            .region_set(&region)
+            .name(service)
+            .time(std_time)
+            .settings(signing_settings)
+            .build()
+            .map_err(|err| err.to_string())?
+            .into()
+    } else {
+        v4::SigningParams::builder()
+            .identity(&identity)
+            .region(&region)
+            .name(service)
+            .time(std_time)
+            .settings(signing_settings)
+            .build()
+            .map_err(|err| err.to_string())?
+            .into()
+    };
 
+    // presigned s3 urls are made before there's a body, so s3 expects them unsigned
+    let body = if params.unsigned_payload || (params.presign && service == "s3") {
+        SignableBody::UnsignedPayload
+    } else {
+        SignableBody::Bytes(request.body())
+    };
+    let headers = request
+        .headers()
+        .iter()
+        .map(|(k, v)| Ok((k.as_str(), v.to_str()?)))
+        .collect::<Result<Vec<_>, http::header::ToStrError>>()
+        .map_err(|err| err.to_string())?;
     let signable_request = SignableRequest::new(
         request.method().as_str(),
         request.uri().to_string(),
-        request
-            .headers()
-            .iter()
-            .map(|(k, v)| (k.as_str(), std::str::from_utf8(v.as_bytes()).unwrap())),
-        SignableBody::Bytes(&request.body()),
-    )?;
+        headers.into_iter(),
+        body,
+    )
+    .map_err(|err| err.to_string())?;
 
     // Sign the request
-    let (signing_instructions, _signature) = sign(signable_request, &signing_params)?.into_parts();
+    let (signing_instructions, _signature) = sign(signable_request, &signing_params)
+        .map_err(|err| err.to_string())?
+        .into_parts();
     signing_instructions.apply_to_request_http1x(request);
 
     Ok(())
 }
+
+struct Keys {
+    access_key: String,
+    secret_key: String,
+    session_token: Option<String>,
+    /// Region set alongside the keys, used when the auth leaves it empty
+    region: Option<String>,
+    provider: &'static str,
+}
+
+fn keys(params: &SigV4Params) -> Result<Keys, String> {
+    match params.credentials {
+        AwsCredentials::Keys => Ok(Keys {
+            access_key: params.access_key.clone(),
+            secret_key: params.secret_key.clone(),
+            session_token: Some(params.session_token.clone()).filter(|token| !token.is_empty()),
+            region: None,
+            provider: "restctl-keys",
+        }),
+        #[cfg(not(target_arch = "wasm32"))]
+        AwsCredentials::Profile => profile::keys(&params.profile),
+        #[cfg(not(target_arch = "wasm32"))]
+        AwsCredentials::Environment => profile::environment_keys(),
+        #[cfg(target_arch = "wasm32")]
+        AwsCredentials::Profile | AwsCredentials::Environment => Err(
+            "AWS profiles and environment variables can only be read by the desktop app".to_owned(),
+        ),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod profile {
+    use std::{collections::HashMap, path::PathBuf};
+
+    use super::Keys;
+
+    type Sections = HashMap<String, HashMap<String, String>>;
+
+    fn var(name: &str) -> Option<String> {
+        std::env::var(name).ok().filter(|value| !value.is_empty())
+    }
+
+    fn region_var() -> Option<String> {
+        var("AWS_REGION").or_else(|| var("AWS_DEFAULT_REGION"))
+    }
+
+    pub fn environment_keys() -> Result<Keys, String> {
+        let required = |name: &str| var(name).ok_or_else(|| format!("{name} isn't set"));
+        Ok(Keys {
+            access_key: required("AWS_ACCESS_KEY_ID")?,
+            secret_key: required("AWS_SECRET_ACCESS_KEY")?,
+            session_token: var("AWS_SESSION_TOKEN"),
+            region: region_var(),
+            provider: "restctl-environment",
+        })
+    }
+
+    /// Static keys of the profile, from the credentials file and then the config file
+    pub fn keys(profile: &str) -> Result<Keys, String> {
+        let name = match profile.trim() {
+            "" => var("AWS_PROFILE").unwrap_or_else(|| "default".to_owned()),
+            name => name.to_owned(),
+        };
+        let credentials = read("AWS_SHARED_CREDENTIALS_FILE", "credentials")?;
+        let config = read("AWS_CONFIG_FILE", "config")?;
+
+        let empty = HashMap::new();
+        let from_credentials = credentials.get(&name).unwrap_or(&empty);
+        // the config file prefixes every profile but the default one
+        let from_config = config
+            .get(&format!("profile {name}"))
+            .or_else(|| (name == "default").then(|| config.get("default")).flatten())
+            .unwrap_or(&empty);
+        if from_credentials.is_empty() && from_config.is_empty() {
+            return Err(format!(
+                "there's no '{name}' profile in ~/.aws/credentials or ~/.aws/config"
+            ));
+        }
+        let get = |key: &str| {
+            from_credentials
+                .get(key)
+                .or_else(|| from_config.get(key))
+                .filter(|value| !value.is_empty())
+                .cloned()
+        };
+
+        let (Some(access_key), Some(secret_key)) =
+            (get("aws_access_key_id"), get("aws_secret_access_key"))
+        else {
+            return Err(format!(
+                "the '{name}' profile has no aws_access_key_id and aws_secret_access_key, \
+                 SSO, role and process credentials aren't supported"
+            ));
+        };
+        Ok(Keys {
+            access_key,
+            secret_key,
+            session_token: get("aws_session_token"),
+            region: region_var().or_else(|| get("region")),
+            provider: "restctl-profile",
+        })
+    }
+
+    /// A missing file has no profiles
+    fn read(path_var: &str, file_name: &str) -> Result<Sections, String> {
+        let path = match var(path_var) {
+            Some(path) => PathBuf::from(path),
+            None => {
+                let Some(home) = var("HOME").or_else(|| var("USERPROFILE")) else {
+                    return Ok(Sections::new());
+                };
+                PathBuf::from(home).join(".aws").join(file_name)
+            }
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Ok(parse(&text)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Sections::new()),
+            Err(err) => Err(format!("failed to read '{}': {err}", path.display())),
+        }
+    }
+
+    fn parse(text: &str) -> Sections {
+        let mut sections = Sections::new();
+        let mut section = None;
+        for line in text.lines() {
+            // indented lines are settings nested under a key, like the s3 ones
+            if line.starts_with([' ', '\t']) {
+                continue;
+            }
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(['#', ';']) {
+                continue;
+            }
+            if let Some(name) = line
+                .strip_prefix('[')
+                .and_then(|line| line.strip_suffix(']'))
+            {
+                let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+                section = Some(sections.entry(name).or_default());
+            } else if let (Some(section), Some((key, value))) = (&mut section, line.split_once('='))
+            {
+                section.insert(key.trim().to_lowercase(), value.trim().to_owned());
+            }
+        }
+        sections
+    }
+}
@@ -0,0 +1,11 @@
+#![warn(clippy::all, rust_2018_idioms)]
+
+// Runs saved collections without the GUI, e.g. in CI
+#[cfg(not(target_arch = "wasm32"))]
+fn main() -> std::process::ExitCode {
+    restctl::cli::main()
+}
+
+// there's no headless runner on the web
+#[cfg(target_arch = "wasm32")]
+fn main() {}
@@ -0,0 +1,292 @@
+use std::fmt::Write;
+
+use crate::{core::Param, environment::Variables, http::HttpError};
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum BodyKind {
+    /// The text as is, with the content type picked for it
+    #[default]
+    Raw,
+    Json,
+    /// The query is the request's text, sent with the variables as JSON
+    GraphQl,
+    FormUrlEncoded,
+    Multipart,
+    /// The content of a file
+    Binary,
+}
+
+impl BodyKind {
+    pub fn values() -> [Self; 6] {
+        [
+            Self::Raw,
+            Self::Json,
+            Self::GraphQl,
+            Self::FormUrlEncoded,
+            Self::Multipart,
+            Self::Binary,
+        ]
+    }
+
+    /// Whether the body is the request's text, the others are made from `BodyOptions`
+    pub fn is_text(self) -> bool {
+        matches!(self, Self::Raw | Self::Json | Self::GraphQl)
+    }
+}
+
+impl std::fmt::Display for BodyKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Raw => write!(f, "Raw"),
+            Self::Json => write!(f, "JSON"),
+            Self::GraphQl => write!(f, "GraphQL"),
+            Self::FormUrlEncoded => write!(f, "Form URL Encoded"),
+            Self::Multipart => write!(f, "Multipart Form"),
+            Self::Binary => write!(f, "Binary"),
+        }
+    }
+}
+
+/// How the body is made, the text of raw and JSON bodies and GraphQL queries is
+/// `RequestState.body`.
+/// Every kind keeps its own fields, so switching back and forth loses nothing
+#[derive(Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct BodyOptions {
+    pub kind: BodyKind,
+    /// Content type of a raw body, none is sent when empty
+    pub content_type: String,
+    /// Fields of form and multipart bodies, multipart ones can be files
+    pub form: Vec<Param>,
+    /// Path of the file sent as a binary body
+    pub file: String,
+    /// JSON object of the variables sent with a GraphQL query
+    pub graphql_variables: String,
+}
+
+/// The bytes to send and the content type they call for
+pub struct EncodedBody {
+    pub bytes: Vec<u8>,
+    pub content_type: Option<String>,
+}
+
+/// The body as curl and generated code write it. Files are referred to by path, their
+/// bytes needn't be text
+pub enum BodySource {
+    /// The body as encoded, empty when there's none
+    Text(String),
+    /// Path of a binary body's file
+    File(String),
+    /// Fields of a multipart body, written out they make their own boundary
+    Multipart(Vec<FormPart>),
+}
+
+pub struct FormPart {
+    pub name: String,
+    /// The path of a file
+    pub value: String,
+    pub file: bool,
+}
+
+impl BodySource {
+    /// `encoded` is the body of the built request, used as is unless it's made of files
+    pub fn new(
+        encoded: &[u8],
+        options: &BodyOptions,
+        variables: &Variables,
+    ) -> Result<Self, HttpError> {
+        Ok(match options.kind {
+            BodyKind::Binary => Self::File(variables.interpolate(&options.file).trim().to_owned()),
+            BodyKind::Multipart => {
+                let parts = variables.interpolate_params(&options.form).into_iter();
+                let parts = parts.filter(|param| param.enabled && !param.key.is_empty());
+                Self::Multipart(
+                    parts
+                        .map(|param| FormPart {
+                            name: param.key,
+                            value: match param.file {
+                                true => param.value.trim().to_owned(),
+                                false => param.value,
+                            },
+                            file: param.file,
+                        })
+                        .collect(),
+                )
+            }
+            _ => Self::Text(String::from_utf8(encoded.to_vec()).map_err(|_| {
+                HttpError::RequestBody("the body isn't text, it can't be written out".to_owned())
+            })?),
+        })
+    }
+}
+
+/// The name a file is sent with in a multipart body
+pub fn file_name(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+/// The body as it's sent, with `{{name}}` variables substituted and files read
+pub fn encode(
+    text: &str,
+    options: &BodyOptions,
+    variables: &Variables,
+) -> Result<EncodedBody, HttpError> {
+    let form = || {
+        variables
+            .interpolate_params(&options.form)
+            .into_iter()
+            .filter(|param| param.enabled && !param.key.is_empty())
+    };
+    Ok(match options.kind {
+        BodyKind::Raw => EncodedBody {
+            bytes: variables.interpolate(text).into_bytes(),
+            content_type: Some(options.content_type.trim().to_owned())
+                .filter(|content_type| !content_type.is_empty()),
+        },
+        BodyKind::Json => EncodedBody {
+            bytes: variables.interpolate(text).into_bytes(),
+            content_type: Some("application/json".to_owned()),
+        },
+        BodyKind::GraphQl => {
+            let mut payload = serde_json::Map::new();
+            payload.insert("query".to_owned(), variables.interpolate(text).into());
+            let graphql_variables = variables.interpolate(&options.graphql_variables);
+            if !graphql_variables.trim().is_empty() {
+                let parsed = serde_json::from_str(&graphql_variables).map_err(|err| {
+                    HttpError::RequestBody(format!(
+                        "the GraphQL variables aren't valid JSON: {err}"
+                    ))
+                })?;
+                payload.insert("variables".to_owned(), parsed);
+            }
+            EncodedBody {
+                bytes: serde_json::Value::Object(payload).to_string().into_bytes(),
+                content_type: Some("application/json".to_owned()),
+            }
+        }
+        BodyKind::FormUrlEncoded => {
+            let pairs = form()
+                .map(|param| (param.key, param.value))
+                .collect::<Vec<_>>();
+            EncodedBody {
+                bytes: serde_urlencoded::to_string(pairs)
+                    .map_err(|err| HttpError::RequestBody(err.to_string()))?
+                    .into_bytes(),
+                content_type: Some("application/x-www-form-urlencoded".to_owned()),
+            }
+        }
+        BodyKind::Multipart => multipart(form())?,
+        BodyKind::Binary => {
+            let path = variables.interpolate(&options.file);
+            EncodedBody {
+                bytes: read_file(&path)?,
+                content_type: Some(guess_content_type(&path).to_owned()),
+            }
+        }
+    })
+}
+
+fn multipart(parts: impl Iterator<Item = Param>) -> Result<EncodedBody, HttpError> {
+    let mut random = [0; 12];
+    getrandom::getrandom(&mut random).map_err(|err| HttpError::RequestBody(err.to_string()))?;
+    let boundary = random
+        .iter()
+        .fold("----restctl".to_owned(), |mut boundary, byte| {
+            let _ = write!(boundary, "{byte:02x}");
+            boundary
+        });
+
+    let mut bytes = vec![];
+    for part in parts {
+        bytes.extend_from_slice(format!("--{boundary}\r\n").as_bytes());
+        let disposition = format!(
+            "Content-Disposition: form-data; name=\"{}\"",
+            escape(&part.key)
+        );
+        if part.file {
+            let path = part.value.trim();
+            let name = file_name(path);
+            bytes.extend_from_slice(
+                format!(
+                    "{disposition}; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+                    escape(name),
+                    guess_content_type(path)
+                )
+                .as_bytes(),
+            );
+            bytes.extend_from_slice(&read_file(path)?);
+        } else {
+            bytes.extend_from_slice(format!("{disposition}\r\n\r\n").as_bytes());
+            bytes.extend_from_slice(part.value.as_bytes());
+        }
+        bytes.extend_from_slice(b"\r\n");
+    }
+    bytes.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+
+    Ok(EncodedBody {
+        bytes,
+        content_type: Some(format!("multipart/form-data; boundary={boundary}")),
+    })
+}
+
+/// Names in a Content-Disposition, escaped the way browsers do it
+fn escape(name: &str) -> String {
+    name.replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_file(path: &str) -> Result<Vec<u8>, HttpError> {
+    let path = path.trim();
+    if path.is_empty() {
+        return Err(HttpError::RequestBody("no file is chosen".to_owned()));
+    }
+    crate::util::read_file(path).map_err(HttpError::RequestBody)
+}
+
+// the browser doesn't let us read files by path
+#[cfg(target_arch = "wasm32")]
+fn read_file(_path: &str) -> Result<Vec<u8>, HttpError> {
+    Err(HttpError::RequestBody(
+        "files can only be sent by the desktop app".to_owned(),
+    ))
+}
+
+/// Content type from the file extension, for the common ones
+fn guess_content_type(path: &str) -> &'static str {
+    let extension = path
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "txt" => "text/plain",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        "js" => "application/javascript",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "gz" => "application/gzip",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Text of a JSON body checked for syntax. Variables aren't known while editing, so they're
+/// taken for a value, which covers both `"{{name}}"` and `{{count}}`
+pub fn check_json(text: &str) -> Result<(), String> {
+    static VARIABLE: std::sync::LazyLock<regex::Regex> =
+        std::sync::LazyLock::new(|| regex::Regex::new(r"\{\{[^{}]*\}\}").unwrap());
+    if text.trim().is_empty() {
+        return Ok(());
+    }
+    serde_json::from_str::<serde::de::IgnoredAny>(&VARIABLE.replace_all(text, "0"))
+        .map(|_| ())
+        .map_err(|err| err.to_string())
+}
@@ -0,0 +1,472 @@
+use std::{
+    collections::HashMap, fmt::Write as _, path::PathBuf, process::ExitCode, sync::Arc,
+    time::Duration,
+};
+
+use crate::{
+    assertions::AssertionResult,
+    async_runtime::AsyncRuntimeHandle,
+    client::{ClientSettings, Clients},
+    collections::{CollectionItem, Collections, FolderId},
+    cookies::BasicCookieStore,
+    environment::Environments,
+    runner::{self, FolderRun, RequestOutcome, RunOptions},
+    tasks::{GlobalContext, RequestContext},
+};
+
+const USAGE: &str = "\
+Runs saved collections without the GUI and exits non-zero if any request fails
+
+Usage: restctl-cli [OPTIONS] [FOLDER]...
+
+FOLDER is a collection or folder name, or a path like 'Collection/Folder'.
+Every collection runs when none is given
+
+Options:
+      --storage <FILE>     app.ron saved by restctl, defaults to where the app keeps it
+  -e, --env <NAME>         environment to use, defaults to the one active in the app
+      --var <NAME=VALUE>   sets a variable, can be repeated
+  -n, --iterations <N>     times to run each folder [default: 1]
+      --delay <MS>         wait between requests, in milliseconds [default: 0]
+  -d, --data <FILE>        CSV or JSON data file, each row feeds one iteration
+      --bail               stop a folder at its first failure
+      --junit <FILE>       writes a JUnit XML report
+      --json <FILE>        writes a JSON report
+  -h, --help               prints this help";
+
+/// What the app saves that the CLI needs, the rest of its state is ignored
+#[derive(Default, serde::Deserialize)]
+#[serde(default)]
+struct SavedState {
+    /// None rather than the app's sample request, which nobody asked to send
+    collections: Option<Collections>,
+    environments: Environments,
+    client_settings: ClientSettings,
+}
+
+#[derive(Default)]
+struct Args {
+    storage: Option<PathBuf>,
+    environment: Option<String>,
+    variables: Vec<(String, String)>,
+    folders: Vec<String>,
+    options: RunOptions,
+    junit: Option<PathBuf>,
+    json: Option<PathBuf>,
+}
+
+pub fn main() -> ExitCode {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match run(args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// None when help was asked for
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut parsed = Args::default();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{arg} needs a value"));
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--storage" => parsed.storage = Some(value()?.into()),
+            "-e" | "--env" => parsed.environment = Some(value()?),
+            "--var" => {
+                let variable = value()?;
+                let (name, value) = variable
+                    .split_once('=')
+                    .ok_or(format!("expected NAME=VALUE, got '{variable}'"))?;
+                parsed
+                    .variables
+                    .push((name.trim().to_owned(), value.to_owned()));
+            }
+            "-n" | "--iterations" => {
+                parsed.options.iterations = parse_number(&arg, &value()?)?;
+            }
+            "--delay" => parsed.options.delay = parse_number(&arg, &value()?)?,
+            "-d" | "--data" => {
+                let path = value()?;
+                let data = std::fs::read_to_string(&path)
+                    .map_err(|err| format!("failed to read '{path}': {err}"))?;
+                runner::parse_data(&data).map_err(|err| format!("'{path}': {err}"))?;
+                parsed.options.data = data;
+            }
+            "--bail" => parsed.options.stop_on_failure = true,
+            "--junit" => parsed.junit = Some(value()?.into()),
+            "--json" => parsed.json = Some(value()?.into()),
+            option if option.starts_with('-') => return Err(format!("unknown option {option}")),
+            _ => parsed.folders.push(arg),
+        }
+    }
+    Ok(Some(parsed))
+}
+
+fn parse_number<T: std::str::FromStr>(option: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{option} expects a number, got '{value}'"))
+}
+
+/// Whether every request passed, Err if the run couldn't happen at all
+fn run(args: Args) -> Result<bool, String> {
+    let storage = match args.storage {
+        Some(storage) => storage,
+        None => default_storage().ok_or("couldn't find where restctl saves its state")?,
+    };
+    let state = load_state(&storage)?;
+    let collections = state
+        .collections
+        .ok_or(format!("'{}' has no collections saved", storage.display()))?;
+
+    let mut environments = state.environments;
+    if let Some(name) = &args.environment {
+        let index = environments
+            .items
+            .iter()
+            .position(|environment| environment.name == *name)
+            .ok_or(format!("no environment named '{name}'"))?;
+        environments.active = Some(index);
+    }
+    let mut context = RequestContext {
+        variables: environments.variables(),
+        defaults: Default::default(),
+        settings: state.client_settings,
+    };
+    for (name, value) in &args.variables {
+        context.variables.set(name, value);
+    }
+
+    let folders = args
+        .folders
+        .iter()
+        .map(|path| {
+            find_folder(&collections, path).ok_or(format!("no collection or folder named '{path}'"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let folders = if args.folders.is_empty() {
+        collections.items.iter().map(|c| c.id).collect()
+    } else {
+        folders
+    };
+
+    let runtime = tokio::runtime::Runtime::new().map_err(|err| err.to_string())?;
+    let cookie_jar = Arc::new(BasicCookieStore::new());
+    let global_context = GlobalContext {
+        cookie_jar: cookie_jar.clone(),
+        clients: Clients::new(cookie_jar),
+        async_runtime: AsyncRuntimeHandle::new_native(runtime.handle().clone()),
+        history: Default::default(),
+        script_variables: Default::default(),
+        oauth_tokens: Default::default(),
+        graphql_schemas: Default::default(),
+        grpc_descriptors: Default::default(),
+    };
+
+    let mut report = Report::default();
+    for folder_id in folders {
+        let run = FolderRun::start(
+            folder_id,
+            &args.options,
+            &collections,
+            &context,
+            &global_context,
+        )?;
+        println!("{}", run.name);
+        if run.websockets > 0 {
+            println!("  SKIP  {} WebSocket requests", run.websockets);
+        }
+        let outcomes = wait_for(&run);
+        report.suites.push(SuiteReport::new(&run, &outcomes));
+
+        // like the app saving them into the environment, later folders see what scripts set
+        for (name, value) in global_context.script_variables.lock().unwrap().drain(..) {
+            context.variables.set(&name, &value);
+        }
+    }
+    report.passed = report.suites.iter().all(SuiteReport::passed);
+    print_summary(&report);
+
+    if let Some(path) = &args.junit {
+        std::fs::write(path, junit_xml(&report))
+            .map_err(|err| format!("failed to write '{}': {err}", path.display()))?;
+    }
+    if let Some(path) = &args.json {
+        let json = serde_json::to_string_pretty(&report).map_err(|err| err.to_string())?;
+        std::fs::write(path, json)
+            .map_err(|err| format!("failed to write '{}': {err}", path.display()))?;
+    }
+    Ok(report.passed)
+}
+
+/// Same place eframe keeps the app's state
+fn default_storage() -> Option<PathBuf> {
+    use std::env::var_os;
+
+    let home = || var_os("HOME").map(PathBuf::from);
+    let dir = if cfg!(target_os = "windows") {
+        var_os("APPDATA").map(|appdata| PathBuf::from(appdata).join("restctl").join("data"))
+    } else if cfg!(target_os = "macos") {
+        home().map(|home| home.join("Library/Application Support/restctl"))
+    } else {
+        var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .or_else(|| home().map(|home| home.join(".local/share")))
+            .map(|data| data.join("restctl"))
+    };
+    dir.map(|dir| dir.join("app.ron"))
+}
+
+fn load_state(path: &PathBuf) -> Result<SavedState, String> {
+    let file = std::fs::read_to_string(path)
+        .map_err(|err| format!("failed to read '{}': {err}", path.display()))?;
+    // a map of keys to RON strings, the app's state is under "app"
+    let entries = ron::from_str::<HashMap<String, String>>(&file)
+        .map_err(|err| format!("'{}' isn't saved app state: {err}", path.display()))?;
+    let app = entries
+        .get("app")
+        .ok_or(format!("'{}' has no app state", path.display()))?;
+    ron::from_str(app).map_err(|err| format!("failed to load the app state: {err}"))
+}
+
+/// Collection by name, or a folder by its path like `Collection/Folder`.
+/// A single name that isn't a collection matches the first folder named so
+fn find_folder(collections: &Collections, path: &str) -> Option<FolderId> {
+    let mut segments = path.split('/').map(str::trim).filter(|s| !s.is_empty());
+    let first = segments.next()?;
+    let mut folder = collections.items.iter().find(|c| c.name == first);
+    for segment in segments {
+        folder = folder?.items.iter().find_map(|item| match item {
+            CollectionItem::Folder(folder) if folder.name == segment => Some(&**folder),
+            _ => None,
+        });
+    }
+    folder.map(|folder| folder.id).or_else(|| {
+        collections
+            .folders()
+            .into_iter()
+            .find(|(_, folder)| !path.contains('/') && folder.name == path.trim())
+            .map(|(_, folder)| folder.id)
+    })
+}
+
+/// Prints outcomes as they come in, returns them all once the run is over
+fn wait_for(run: &FolderRun) -> Vec<RequestOutcome> {
+    let mut printed = 0;
+    loop {
+        let (outcomes, finished) = {
+            let progress = run.progress.lock().unwrap();
+            (progress.outcomes[printed..].to_vec(), progress.finished)
+        };
+        for outcome in &outcomes {
+            print_outcome(run, outcome);
+        }
+        printed += outcomes.len();
+        if finished {
+            return run.progress.lock().unwrap().outcomes.clone();
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+fn print_outcome(run: &FolderRun, outcome: &RequestOutcome) {
+    let status = if outcome.passed() { "PASS" } else { "FAIL" };
+    println!(
+        "  {status}  {} ({} ms)",
+        case_name(run.iterations, outcome),
+        outcome.duration.as_millis()
+    );
+    match &outcome.result {
+        Ok(results) => {
+            for result in results.iter().filter(|result| !result.passed) {
+                println!("        {}: {}", result.description, result.message);
+            }
+        }
+        Err(err) => println!("        {}: {err}", err.title()),
+    }
+}
+
+fn case_name(iterations: usize, outcome: &RequestOutcome) -> String {
+    if iterations > 1 {
+        format!("#{} {}", outcome.iteration + 1, outcome.name)
+    } else {
+        outcome.name.clone()
+    }
+}
+
+fn print_summary(report: &Report) {
+    let cases = report.suites.iter().flat_map(|suite| &suite.cases);
+    let (mut requests, mut failed, mut assertions, mut passed_assertions) = (0, 0, 0, 0);
+    for case in cases {
+        requests += 1;
+        failed += usize::from(!case.passed);
+        assertions += case.assertions.len();
+        passed_assertions += case.assertions.iter().filter(|a| a.passed).count();
+    }
+    println!();
+    println!(
+        "{requests} request(s), {failed} failed, {passed_assertions} of {assertions} assertion(s) passed"
+    );
+}
+
+#[derive(Default, serde::Serialize)]
+struct Report {
+    passed: bool,
+    suites: Vec<SuiteReport>,
+}
+
+/// A folder run
+#[derive(serde::Serialize)]
+struct SuiteReport {
+    name: String,
+    iterations: usize,
+    /// Requests that didn't get to run, because of a failure with `--bail`
+    skipped: usize,
+    cases: Vec<CaseReport>,
+}
+
+impl SuiteReport {
+    fn new(run: &FolderRun, outcomes: &[RequestOutcome]) -> Self {
+        Self {
+            name: run.name.clone(),
+            iterations: run.iterations,
+            skipped: run.total().saturating_sub(outcomes.len()),
+            cases: outcomes
+                .iter()
+                .map(|outcome| CaseReport {
+                    name: case_name(run.iterations, outcome),
+                    passed: outcome.passed(),
+                    duration_ms: outcome.duration.as_millis() as u64,
+                    error: outcome
+                        .result
+                        .as_ref()
+                        .err()
+                        .map(|err| format!("{}: {err}", err.title())),
+                    assertions: outcome.result.clone().unwrap_or_default(),
+                })
+                .collect(),
+        }
+    }
+
+    fn passed(&self) -> bool {
+        self.cases.iter().all(|case| case.passed)
+    }
+}
+
+/// A request of a folder run
+#[derive(serde::Serialize)]
+struct CaseReport {
+    name: String,
+    passed: bool,
+    duration_ms: u64,
+    /// Why no response came back
+    error: Option<String>,
+    assertions: Vec<AssertionResult>,
+}
+
+fn junit_xml(report: &Report) -> String {
+    let seconds = |millis: u64| millis as f64 / 1000.0;
+    let count = |suite: &SuiteReport, failed: bool| {
+        suite
+            .cases
+            .iter()
+            .filter(|case| case.error.is_none() && case.passed != failed)
+            .count()
+    };
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let tests = report.suites.iter().map(|s| s.cases.len()).sum::<usize>();
+    let failures = report.suites.iter().map(|s| count(s, true)).sum::<usize>();
+    let errors = report.suites.iter().map(error_count).sum::<usize>();
+    let _ = writeln!(
+        xml,
+        "<testsuites name=\"restctl\" tests=\"{tests}\" failures=\"{failures}\" errors=\"{errors}\">"
+    );
+    for suite in &report.suites {
+        let time = seconds(suite.cases.iter().map(|case| case.duration_ms).sum());
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{time:.3}\">",
+            escape_xml(&suite.name),
+            suite.cases.len() + suite.skipped,
+            count(suite, true),
+            error_count(suite),
+            suite.skipped,
+        );
+        for case in &suite.cases {
+            let _ = write!(
+                xml,
+                "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+                escape_xml(&case.name),
+                escape_xml(&suite.name),
+                seconds(case.duration_ms)
+            );
+            let failed = case.assertions.iter().filter(|a| !a.passed);
+            if let Some(error) = &case.error {
+                let _ = writeln!(
+                    xml,
+                    ">\n      <error message=\"{}\"/>\n    </testcase>",
+                    escape_xml(error)
+                );
+            } else if !case.passed {
+                let _ = writeln!(xml, ">");
+                for result in failed {
+                    let _ = writeln!(
+                        xml,
+                        "      <failure message=\"{}\">{}</failure>",
+                        escape_xml(&result.description),
+                        escape_xml(&result.message)
+                    );
+                }
+                let _ = writeln!(xml, "    </testcase>");
+            } else {
+                let _ = writeln!(xml, "/>");
+            }
+        }
+        let _ = writeln!(xml, "  </testsuite>");
+    }
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+fn error_count(suite: &SuiteReport) -> usize {
+    suite
+        .cases
+        .iter()
+        .filter(|case| case.error.is_some())
+        .count()
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // not allowed in XML 1.0 at all
+            c if c.is_control() && !matches!(c, '\n' | '\r' | '\t') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,180 @@
+use std::sync::{Arc, Mutex};
+
+use crate::{cookies::BasicCookieStore, http::HttpError};
+
+/// How requests get sent. Set globally, requests can override them as a whole
+#[derive(Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ClientSettings {
+    /// In milliseconds, 0 waits forever
+    pub connect_timeout: u64,
+    /// Longest wait between two reads of the response, in milliseconds, 0 waits forever
+    pub read_timeout: u64,
+    pub follow_redirects: bool,
+    pub max_redirects: usize,
+    /// `http://`, `https://`, `socks5://` or `socks5h://` url, empty for a direct connection
+    pub proxy: String,
+    /// PEM file with extra root certificates to trust
+    pub ca_bundle: String,
+    /// PEM files of the client certificate and its PKCS #8 key, for mutual TLS
+    pub client_cert: String,
+    pub client_key: String,
+    /// Skips verifying the server's certificate and host name
+    pub insecure: bool,
+}
+
+impl Default for ClientSettings {
+    fn default() -> Self {
+        Self {
+            connect_timeout: 30_000,
+            read_timeout: 0,
+            follow_redirects: true,
+            max_redirects: 10,
+            proxy: String::new(),
+            ca_bundle: String::new(),
+            client_cert: String::new(),
+            client_key: String::new(),
+            insecure: false,
+        }
+    }
+}
+
+/// Clients built so far, one per distinct settings. Reusing them keeps connections
+/// pooled, and they all share the cookie jar
+#[derive(Clone)]
+pub struct Clients {
+    cookie_jar: Arc<BasicCookieStore>,
+    /// With whether they only speak HTTP/2
+    built: Arc<Mutex<Vec<(ClientSettings, bool, reqwest::Client)>>>,
+}
+
+impl Clients {
+    pub fn new(cookie_jar: Arc<BasicCookieStore>) -> Self {
+        Self {
+            cookie_jar,
+            built: Default::default(),
+        }
+    }
+
+    pub fn get(&self, settings: &ClientSettings) -> Result<reqwest::Client, HttpError> {
+        self.get_with(settings, false)
+    }
+
+    /// A client going straight to HTTP/2, even without TLS, as gRPC needs
+    pub fn get_http2(&self, settings: &ClientSettings) -> Result<reqwest::Client, HttpError> {
+        self.get_with(settings, true)
+    }
+
+    fn get_with(
+        &self,
+        settings: &ClientSettings,
+        http2: bool,
+    ) -> Result<reqwest::Client, HttpError> {
+        let mut built = self.built.lock().unwrap();
+        if let Some((_, _, client)) = built.iter().find(|(s, h, _)| s == settings && *h == http2) {
+            return Ok(client.clone());
+        }
+        let client = self.build(settings, http2).map_err(HttpError::Settings)?;
+        built.push((settings.clone(), http2, client.clone()));
+        Ok(client)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn build(&self, settings: &ClientSettings, http2: bool) -> Result<reqwest::Client, String> {
+        use std::time::Duration;
+
+        use crate::timing;
+
+        let mut builder = reqwest::Client::builder()
+            .cookie_provider(self.cookie_jar.clone())
+            .dns_resolver(Arc::new(timing::TimingResolver))
+            .connector_layer(timing::TimingLayer)
+            .redirect(redirect_policy(settings))
+            .use_preconfigured_tls(tls_connector(settings, &["h2", "http/1.1"])?);
+
+        if http2 {
+            builder = builder.http2_prior_knowledge();
+        }
+        if settings.connect_timeout > 0 {
+            builder = builder.connect_timeout(Duration::from_millis(settings.connect_timeout));
+        }
+        if settings.read_timeout > 0 {
+            builder = builder.read_timeout(Duration::from_millis(settings.read_timeout));
+        }
+        let proxy = settings.proxy.trim();
+        if !proxy.is_empty() {
+            let proxy =
+                reqwest::Proxy::all(proxy).map_err(|err| format!("proxy '{proxy}': {err}"))?;
+            builder = builder.proxy(proxy);
+        }
+        builder.build().map_err(|err| err.to_string())
+    }
+
+    // the browser owns redirects, certificates and proxies on the web
+    #[cfg(target_arch = "wasm32")]
+    fn build(&self, _settings: &ClientSettings, _http2: bool) -> Result<reqwest::Client, String> {
+        reqwest::Client::builder()
+            .build()
+            .map_err(|err| err.to_string())
+    }
+}
+
+/// TLS as the settings ask for it, shared by the HTTP client and WebSocket connections.
+/// `alpn` are the protocols offered to the server
+#[cfg(not(target_arch = "wasm32"))]
+pub fn tls_connector(
+    settings: &ClientSettings,
+    alpn: &[&str],
+) -> Result<native_tls::TlsConnector, String> {
+    let mut builder = native_tls::TlsConnector::builder();
+    builder
+        .danger_accept_invalid_certs(settings.insecure)
+        .request_alpns(alpn);
+    let ca_bundle = settings.ca_bundle.trim();
+    if !ca_bundle.is_empty() {
+        let pem = String::from_utf8_lossy(&crate::util::read_file(ca_bundle)?).into_owned();
+        // native-tls takes the certificates of a bundle one by one
+        for certificate in pem.split_inclusive("-----END CERTIFICATE-----") {
+            if !certificate.contains("-----BEGIN CERTIFICATE-----") {
+                continue;
+            }
+            let certificate = native_tls::Certificate::from_pem(certificate.as_bytes())
+                .map_err(|err| format!("CA bundle '{ca_bundle}': {err}"))?;
+            builder.add_root_certificate(certificate);
+        }
+    }
+    match (settings.client_cert.trim(), settings.client_key.trim()) {
+        ("", "") => {}
+        ("", _) | (_, "") => {
+            return Err("client certificate and key both need to be set".to_owned());
+        }
+        (cert, key) => {
+            let identity = native_tls::Identity::from_pkcs8(
+                &crate::util::read_file(cert)?,
+                &crate::util::read_file(key)?,
+            )
+            .map_err(|err| format!("client certificate: {err}"))?;
+            builder.identity(identity);
+        }
+    }
+    builder.build().map_err(|err| err.to_string())
+}
+
+/// Follows redirects as configured, noting each hop for the request being sent
+#[cfg(not(target_arch = "wasm32"))]
+fn redirect_policy(settings: &ClientSettings) -> reqwest::redirect::Policy {
+    if !settings.follow_redirects {
+        return reqwest::redirect::Policy::none();
+    }
+    let max_redirects = settings.max_redirects;
+    reqwest::redirect::Policy::custom(move |attempt| {
+        if let Some(from) = attempt.previous().last() {
+            crate::http::record_redirect(attempt.status().as_u16(), from.as_str());
+        }
+        if attempt.previous().len() > max_redirects {
+            attempt.error(format!("stopped after {max_redirects} redirects"))
+        } else {
+            attempt.follow()
+        }
+    })
+}
@@ -0,0 +1,516 @@
+use std::fmt::Write;
+
+use crate::body::{self, BodySource};
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum Language {
+    #[default]
+    RustReqwest,
+    PythonRequests,
+    JavaScriptFetch,
+    GoNetHttp,
+}
+
+impl Language {
+    pub fn values() -> [Language; 4] {
+        [
+            Language::RustReqwest,
+            Language::PythonRequests,
+            Language::JavaScriptFetch,
+            Language::GoNetHttp,
+        ]
+    }
+
+    /// File extension syntect uses to pick the syntax
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Language::RustReqwest => "rs",
+            Language::PythonRequests => "py",
+            Language::JavaScriptFetch => "js",
+            Language::GoNetHttp => "go",
+        }
+    }
+}
+
+impl std::fmt::Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Language::RustReqwest => write!(f, "Rust (reqwest)"),
+            Language::PythonRequests => write!(f, "Python (requests)"),
+            Language::JavaScriptFetch => write!(f, "JavaScript (fetch)"),
+            Language::GoNetHttp => write!(f, "Go (net/http)"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct CodegenOptions {
+    /// Swap credentials in headers and the query for `<NAME>` placeholders
+    pub redact_secrets: bool,
+}
+
+/// The parts of a request every generator needs, already redacted if asked to
+struct Snippet<'a> {
+    method: http::Method,
+    url: String,
+    headers: Vec<(String, String)>,
+    body: &'a BodySource,
+}
+
+impl<'a> Snippet<'a> {
+    fn new(
+        request: &http::Request<Vec<u8>>,
+        body: &'a BodySource,
+        options: &CodegenOptions,
+    ) -> Self {
+        let mut url = request.uri().to_string();
+        let mut headers = request
+            .headers()
+            .iter()
+            // the libraries pick the multipart boundary, ours wouldn't match it
+            .filter(|(name, _)| {
+                *name != http::header::CONTENT_TYPE || !matches!(body, BodySource::Multipart(_))
+            })
+            .map(|(name, value)| {
+                let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+                (name.to_string(), value)
+            })
+            .collect::<Vec<_>>();
+
+        if options.redact_secrets {
+            url = redact_url(&url);
+            for (name, value) in &mut headers {
+                if is_secret(name) {
+                    *value = redact_header(name, value);
+                }
+            }
+        }
+
+        Snippet {
+            method: request.method().clone(),
+            url,
+            headers,
+            body,
+        }
+    }
+
+    fn reads_files(&self) -> bool {
+        match self.body {
+            BodySource::Text(_) => false,
+            BodySource::File(_) => true,
+            BodySource::Multipart(form) => form.iter().any(|part| part.file),
+        }
+    }
+
+    /// Repeated headers folded into one, for languages that take headers as a map
+    fn merged_headers(&self) -> Vec<(String, String)> {
+        let mut merged: Vec<(String, String)> = vec![];
+        for (name, value) in &self.headers {
+            match merged.iter_mut().find(|(n, _)| n == name) {
+                Some((_, existing)) => {
+                    existing.push_str(", ");
+                    existing.push_str(value);
+                }
+                None => merged.push((name.clone(), value.clone())),
+            }
+        }
+        merged
+    }
+}
+
+/// Renders a snippet that sends `request`, which is expected to have auth applied already.
+/// Files of the body are read by the snippet
+pub fn generate(
+    request: &http::Request<Vec<u8>>,
+    body: &BodySource,
+    language: Language,
+    options: &CodegenOptions,
+) -> String {
+    let snippet = Snippet::new(request, body, options);
+    match language {
+        Language::RustReqwest => rust_reqwest(&snippet),
+        Language::PythonRequests => python_requests(&snippet),
+        Language::JavaScriptFetch => javascript_fetch(&snippet),
+        Language::GoNetHttp => go_net_http(&snippet),
+    }
+}
+
+fn rust_reqwest(snippet: &Snippet<'_>) -> String {
+    let mut code = String::new();
+    if let BodySource::Multipart(form) = snippet.body {
+        code.push_str("let form = reqwest::multipart::Form::new()");
+        for part in form {
+            let name = rust_str(&part.name);
+            let _ = match part.file {
+                true => write!(
+                    code,
+                    "\n    .part(\n        {name},\n        reqwest::multipart::Part::bytes(std::fs::read({})?)\n            .file_name({}),\n    )",
+                    rust_str(&part.value),
+                    rust_str(body::file_name(&part.value))
+                ),
+                false => write!(code, "\n    .text({name}, {})", rust_str(&part.value)),
+            };
+        }
+        code.push_str(";\n\n");
+    }
+    code.push_str("let client = reqwest::Client::new();\nlet response = client\n");
+
+    let url = rust_str(&snippet.url);
+    let _ = match snippet.method {
+        http::Method::GET => writeln!(code, "    .get({url})"),
+        http::Method::POST => writeln!(code, "    .post({url})"),
+        http::Method::PUT => writeln!(code, "    .put({url})"),
+        http::Method::PATCH => writeln!(code, "    .patch({url})"),
+        http::Method::DELETE => writeln!(code, "    .delete({url})"),
+        http::Method::HEAD => writeln!(code, "    .head({url})"),
+        ref method => writeln!(code, "    .request(reqwest::Method::{method}, {url})"),
+    };
+    for (name, value) in &snippet.headers {
+        let _ = writeln!(code, "    .header({}, {})", rust_str(name), rust_str(value));
+    }
+    let _ = match snippet.body {
+        BodySource::Text(text) if text.is_empty() => Ok(()),
+        BodySource::Text(text) => writeln!(code, "    .body({})", rust_str(text)),
+        BodySource::File(path) => writeln!(code, "    .body(std::fs::read({})?)", rust_str(path)),
+        BodySource::Multipart(_) => writeln!(code, "    .multipart(form)"),
+    };
+    code.push_str("    .send()\n    .await?;\n\n");
+    code.push_str("println!(\"{}\", response.status());\n");
+    code.push_str("println!(\"{}\", response.text().await?);\n");
+    code
+}
+
+fn python_requests(snippet: &Snippet<'_>) -> String {
+    let mut code = String::from("import requests\n\n");
+    let _ = writeln!(code, "url = {}", quoted(&snippet.url));
+
+    let headers = snippet.merged_headers();
+    if !headers.is_empty() {
+        code.push_str("headers = {\n");
+        for (name, value) in &headers {
+            let _ = writeln!(code, "    {}: {},", quoted(name), quoted(value));
+        }
+        code.push_str("}\n");
+    }
+    let body_arg = match snippet.body {
+        BodySource::Text(text) if text.is_empty() => None,
+        BodySource::Text(text) => {
+            let _ = writeln!(code, "data = {}", quoted_body(text));
+            Some("data=data")
+        }
+        BodySource::File(path) => {
+            let _ = writeln!(code, "data = open({}, \"rb\")", quoted(path));
+            Some("data=data")
+        }
+        BodySource::Multipart(form) => {
+            // fields go in files too, with no file name, so it's multipart even without files
+            code.push_str("files = [\n");
+            for part in form {
+                let value = match part.file {
+                    true => format!("open({}, \"rb\")", quoted(&part.value)),
+                    false => format!("(None, {})", quoted(&part.value)),
+                };
+                let _ = writeln!(code, "    ({}, {value}),", quoted(&part.name));
+            }
+            code.push_str("]\n");
+            Some("files=files")
+        }
+    };
+
+    let method = snippet.method.as_str().to_lowercase();
+    let mut args = vec!["url".to_owned()];
+    if !headers.is_empty() {
+        args.push("headers=headers".to_owned());
+    }
+    args.extend(body_arg.map(str::to_owned));
+    let call = match snippet.method {
+        http::Method::GET
+        | http::Method::POST
+        | http::Method::PUT
+        | http::Method::PATCH
+        | http::Method::DELETE
+        | http::Method::HEAD
+        | http::Method::OPTIONS => format!("requests.{method}({})", args.join(", ")),
+        _ => format!(
+            "requests.request({}, {})",
+            quoted(snippet.method.as_str()),
+            args.join(", ")
+        ),
+    };
+
+    let _ = writeln!(code, "\nresponse = {call}");
+    code.push_str("print(response.status_code)\nprint(response.text)\n");
+    code
+}
+
+fn javascript_fetch(snippet: &Snippet<'_>) -> String {
+    let mut code = String::new();
+    if snippet.reads_files() {
+        code.push_str("import { readFile } from \"node:fs/promises\";\n\n");
+    }
+    if let BodySource::Multipart(form) = snippet.body {
+        code.push_str("const form = new FormData();\n");
+        for part in form {
+            let _ = match part.file {
+                true => writeln!(
+                    code,
+                    "form.append({}, new Blob([await readFile({})]), {});",
+                    quoted(&part.name),
+                    quoted(&part.value),
+                    quoted(body::file_name(&part.value))
+                ),
+                false => writeln!(
+                    code,
+                    "form.append({}, {});",
+                    quoted(&part.name),
+                    quoted(&part.value)
+                ),
+            };
+        }
+        code.push('\n');
+    }
+    let _ = writeln!(
+        code,
+        "const response = await fetch({}, {{",
+        quoted(&snippet.url)
+    );
+    let _ = writeln!(code, "  method: {},", quoted(snippet.method.as_str()));
+
+    let headers = snippet.merged_headers();
+    if !headers.is_empty() {
+        code.push_str("  headers: {\n");
+        for (name, value) in &headers {
+            let _ = writeln!(code, "    {}: {},", quoted(name), quoted(value));
+        }
+        code.push_str("  },\n");
+    }
+
+    let _ = match snippet.body {
+        BodySource::Text(text) if text.is_empty() => Ok(()),
+        BodySource::Text(text) => writeln!(code, "  body: {},", quoted_body(text)),
+        BodySource::File(path) => writeln!(code, "  body: await readFile({}),", quoted(path)),
+        BodySource::Multipart(_) => writeln!(code, "  body: form,"),
+    };
+
+    code.push_str("});\n\n");
+    code.push_str("console.log(response.status);\nconsole.log(await response.text());\n");
+    code
+}
+
+fn go_net_http(snippet: &Snippet<'_>) -> String {
+    let mut imports = vec!["fmt", "io", "net/http"];
+    match snippet.body {
+        BodySource::Text(text) if text.is_empty() => {}
+        BodySource::Text(_) => imports.push("strings"),
+        BodySource::File(_) => imports.push("os"),
+        BodySource::Multipart(_) => {
+            imports.extend(["bytes", "mime/multipart"]);
+            if snippet.reads_files() {
+                imports.push("os");
+            }
+        }
+    }
+    imports.sort();
+    let mut code = String::from("package main\n\nimport (\n");
+    for import in imports {
+        let _ = writeln!(code, "\t{}", quoted(import));
+    }
+    code.push_str(")\n\nfunc main() {\n");
+
+    let check = "\tif err != nil {\n\t\tpanic(err)\n\t}\n";
+    let body = match snippet.body {
+        BodySource::Text(text) if text.is_empty() => "nil",
+        BodySource::Text(text) => {
+            let _ = writeln!(code, "\tbody := strings.NewReader({})", go_str(text));
+            "body"
+        }
+        BodySource::File(path) => {
+            let _ = writeln!(code, "\tbody, err := os.Open({})", quoted(path));
+            code.push_str(check);
+            code.push_str("\tdefer body.Close()\n\n");
+            "body"
+        }
+        BodySource::Multipart(form) => {
+            code.push_str("\tbody := &bytes.Buffer{}\n\tform := multipart.NewWriter(body)\n");
+            for part in form {
+                let name = quoted(&part.name);
+                if !part.file {
+                    let _ = writeln!(code, "\tform.WriteField({name}, {})", quoted(&part.value));
+                    continue;
+                }
+                // a block each, so every file gets its own variables
+                let _ = writeln!(
+                    code,
+                    "\t{{\n\t\tfile, err := os.ReadFile({})",
+                    quoted(&part.value)
+                );
+                code.push_str("\t\tif err != nil {\n\t\t\tpanic(err)\n\t\t}\n");
+                let _ = writeln!(
+                    code,
+                    "\t\tpart, err := form.CreateFormFile({name}, {})",
+                    quoted(body::file_name(&part.value))
+                );
+                code.push_str(
+                    "\t\tif err != nil {\n\t\t\tpanic(err)\n\t\t}\n\t\tpart.Write(file)\n\t}\n",
+                );
+            }
+            code.push_str("\tform.Close()\n\n");
+            "body"
+        }
+    };
+    let method = match snippet.method {
+        http::Method::GET => "http.MethodGet".to_owned(),
+        http::Method::POST => "http.MethodPost".to_owned(),
+        http::Method::PUT => "http.MethodPut".to_owned(),
+        http::Method::PATCH => "http.MethodPatch".to_owned(),
+        http::Method::DELETE => "http.MethodDelete".to_owned(),
+        http::Method::HEAD => "http.MethodHead".to_owned(),
+        http::Method::OPTIONS => "http.MethodOptions".to_owned(),
+        http::Method::TRACE => "http.MethodTrace".to_owned(),
+        http::Method::CONNECT => "http.MethodConnect".to_owned(),
+        ref method => quoted(method.as_str()),
+    };
+    let _ = writeln!(
+        code,
+        "\treq, err := http.NewRequest({method}, {}, {body})",
+        quoted(&snippet.url)
+    );
+    code.push_str("\tif err != nil {\n\t\tpanic(err)\n\t}\n");
+
+    let mut seen: Vec<&str> = vec![];
+    for (name, value) in &snippet.headers {
+        // Set replaces, so repeated headers have to be added
+        let setter = if seen.contains(&name.as_str()) {
+            "Add"
+        } else {
+            "Set"
+        };
+        seen.push(name);
+        let _ = writeln!(
+            code,
+            "\treq.Header.{setter}({}, {})",
+            quoted(name),
+            quoted(value)
+        );
+    }
+
+    if let BodySource::Multipart(_) = snippet.body {
+        code.push_str("\treq.Header.Set(\"Content-Type\", form.FormDataContentType())\n");
+    }
+
+    code.push_str(
+        "\n\tres, err := http.DefaultClient.Do(req)\n\tif err != nil {\n\t\tpanic(err)\n\t}\n\tdefer res.Body.Close()\n\n",
+    );
+    code.push_str(
+        "\tresBody, err := io.ReadAll(res.Body)\n\tif err != nil {\n\t\tpanic(err)\n\t}\n",
+    );
+    code.push_str("\tfmt.Println(res.Status)\n\tfmt.Println(string(resBody))\n}\n");
+    code
+}
+
+/// A double quoted string literal. JSON escaping is valid in Python, JavaScript and Go
+fn quoted(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+/// Single quotes for bodies full of double quotes like JSON, in Python and JavaScript
+fn quoted_body(value: &str) -> String {
+    if value.contains('"') && !value.contains(['\'', '\\', '\n', '\r']) {
+        format!("'{value}'")
+    } else {
+        quoted(value)
+    }
+}
+
+/// Raw string literal when the value has quotes or backslashes, so bodies stay readable
+fn rust_str(value: &str) -> String {
+    let needs_raw = value.contains(['"', '\\']);
+    let has_control = value.chars().any(|c| c.is_control() && c != '\n');
+    if !needs_raw || has_control {
+        return format!("{value:?}");
+    }
+    let mut hashes = String::from("#");
+    while value.contains(&format!("\"{hashes}")) {
+        hashes.push('#');
+    }
+    format!("r{hashes}\"{value}\"{hashes}")
+}
+
+/// Backtick raw strings unless the value itself has a backtick
+fn go_str(value: &str) -> String {
+    if value.contains('`') || value.contains('\r') {
+        quoted(value)
+    } else {
+        format!("`{value}`")
+    }
+}
+
+fn is_secret(name: &str) -> bool {
+    let name = name.to_lowercase();
+    matches!(
+        name.as_str(),
+        "authorization" | "proxy-authorization" | "cookie" | "key"
+    ) || [
+        "token",
+        "secret",
+        "password",
+        "api-key",
+        "apikey",
+        "api_key",
+        "signature",
+        "credential",
+    ]
+    .iter()
+    .any(|part| name.contains(part))
+}
+
+/// Keeps the auth scheme so the snippet still shows how to authenticate
+fn redact_header(name: &str, value: &str) -> String {
+    let placeholder = placeholder(name);
+    match value.split_once(' ') {
+        Some((scheme, _))
+            if name.eq_ignore_ascii_case("authorization")
+                || name.eq_ignore_ascii_case("proxy-authorization") =>
+        {
+            format!("{scheme} {placeholder}")
+        }
+        _ => placeholder,
+    }
+}
+
+fn redact_url(url: &str) -> String {
+    let Ok(mut parsed) = url::Url::parse(url) else {
+        return url.to_owned();
+    };
+    let pairs = parsed
+        .query_pairs()
+        .map(|(key, value)| {
+            let value = if is_secret(&key) {
+                placeholder(&key)
+            } else {
+                value.into_owned()
+            };
+            (key.into_owned(), value)
+        })
+        .collect::<Vec<_>>();
+    if pairs.is_empty() {
+        return url.to_owned();
+    }
+    parsed.query_pairs_mut().clear().extend_pairs(pairs);
+    // keep the placeholders readable rather than percent encoded
+    parsed.to_string().replace("%3C", "<").replace("%3E", ">")
+}
+
+fn placeholder(name: &str) -> String {
+    let name = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    format!("<{name}>")
+}
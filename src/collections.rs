@@ -0,0 +1,415 @@
+use crate::{
+    auth::RequestAuth,
+    core::{Param, RequestId, RequestState, get_new_id},
+};
+
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct FolderId(pub usize);
+
+impl FolderId {
+    pub fn next() -> Self {
+        Self(get_new_id())
+    }
+}
+
+/// Identifies any node of the collections tree
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ItemId {
+    Folder(FolderId),
+    Request(RequestId),
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub enum CollectionItem {
+    Folder(Box<Folder>),
+    Request(RequestId, Box<RequestState>),
+}
+
+impl CollectionItem {
+    pub fn id(&self) -> ItemId {
+        match self {
+            CollectionItem::Folder(folder) => ItemId::Folder(folder.id),
+            CollectionItem::Request(id, _) => ItemId::Request(*id),
+        }
+    }
+}
+
+/// Values a folder hands down to every request below it
+#[derive(Clone, Default, serde::Deserialize, serde::Serialize)]
+pub struct FolderDefaults {
+    pub base_url: String,
+    pub headers: Vec<Param>,
+    pub auth: RequestAuth,
+}
+
+/// A folder, top level folders are shown as collections
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct Folder {
+    pub id: FolderId,
+    pub name: String,
+    #[serde(default)]
+    pub defaults: FolderDefaults,
+    pub items: Vec<CollectionItem>,
+}
+
+impl Folder {
+    pub fn new(name: String) -> Self {
+        Self {
+            id: FolderId::next(),
+            name,
+            defaults: Default::default(),
+            items: vec![],
+        }
+    }
+
+    fn find_request(&self, request_id: RequestId) -> Option<&RequestState> {
+        self.items.iter().find_map(|item| match item {
+            CollectionItem::Request(id, state) if *id == request_id => Some(&**state),
+            CollectionItem::Folder(folder) => folder.find_request(request_id),
+            _ => None,
+        })
+    }
+
+    fn find_request_mut(&mut self, request_id: RequestId) -> Option<&mut RequestState> {
+        self.items.iter_mut().find_map(|item| match item {
+            CollectionItem::Request(id, state) if *id == request_id => Some(&mut **state),
+            CollectionItem::Folder(folder) => folder.find_request_mut(request_id),
+            _ => None,
+        })
+    }
+
+    fn find_imported_request_mut(&mut self, key: &str) -> Option<&mut RequestState> {
+        self.items.iter_mut().find_map(|item| match item {
+            CollectionItem::Request(_, state)
+                if state
+                    .origin
+                    .as_ref()
+                    .is_some_and(|origin| origin.key == key) =>
+            {
+                Some(&mut **state)
+            }
+            CollectionItem::Folder(folder) => folder.find_imported_request_mut(key),
+            _ => None,
+        })
+    }
+
+    pub fn has_imported_requests(&self) -> bool {
+        self.items.iter().any(|item| match item {
+            CollectionItem::Request(_, state) => state.origin.is_some(),
+            CollectionItem::Folder(folder) => folder.has_imported_requests(),
+        })
+    }
+
+    /// Moves the items of `other` in, folders with the same name are merged rather than repeated
+    pub fn merge(&mut self, other: Folder) {
+        for item in other.items {
+            match item {
+                CollectionItem::Folder(folder) => {
+                    let existing = self.items.iter_mut().find_map(|item| match item {
+                        CollectionItem::Folder(existing) if existing.name == folder.name => {
+                            Some(existing)
+                        }
+                        _ => None,
+                    });
+                    match existing {
+                        Some(existing) => existing.merge(*folder),
+                        None => self.items.push(CollectionItem::Folder(folder)),
+                    }
+                }
+                request => self.items.push(request),
+            }
+        }
+    }
+
+    fn find_folder(&self, folder_id: FolderId) -> Option<&Folder> {
+        if self.id == folder_id {
+            return Some(self);
+        }
+        self.items.iter().find_map(|item| match item {
+            CollectionItem::Folder(folder) => folder.find_folder(folder_id),
+            _ => None,
+        })
+    }
+
+    fn find_folder_mut(&mut self, folder_id: FolderId) -> Option<&mut Folder> {
+        if self.id == folder_id {
+            return Some(self);
+        }
+        self.items.iter_mut().find_map(|item| match item {
+            CollectionItem::Folder(folder) => folder.find_folder_mut(folder_id),
+            _ => None,
+        })
+    }
+
+    /// Chain of folders from this one down to the parent of `item`, if `item` is inside
+    fn path_to<'a>(&'a self, item: ItemId, path: &mut Vec<&'a Folder>) -> bool {
+        path.push(self);
+        for child in &self.items {
+            if child.id() == item {
+                return true;
+            }
+            if let CollectionItem::Folder(folder) = child {
+                if folder.path_to(item, path) {
+                    return true;
+                }
+            }
+        }
+        path.pop();
+        false
+    }
+
+    fn remove(&mut self, item: ItemId) -> Option<CollectionItem> {
+        if let Some(index) = self.items.iter().position(|child| child.id() == item) {
+            return Some(self.items.remove(index));
+        }
+        self.items.iter_mut().find_map(|child| match child {
+            CollectionItem::Folder(folder) => folder.remove(item),
+            _ => None,
+        })
+    }
+
+    fn position_of(&self, item: ItemId) -> Option<usize> {
+        self.items.iter().position(|child| child.id() == item)
+    }
+
+    pub fn request_ids(&self, ids: &mut Vec<RequestId>) {
+        for item in &self.items {
+            match item {
+                CollectionItem::Folder(folder) => folder.request_ids(ids),
+                CollectionItem::Request(id, _) => ids.push(*id),
+            }
+        }
+    }
+
+    pub fn folders<'a>(&'a self, depth: usize, folders: &mut Vec<(usize, &'a Folder)>) {
+        folders.push((depth, self));
+        for item in &self.items {
+            if let CollectionItem::Folder(folder) = item {
+                folder.folders(depth + 1, folders);
+            }
+        }
+    }
+
+    fn max_id(&self) -> usize {
+        self.items
+            .iter()
+            .map(|item| match item {
+                CollectionItem::Folder(folder) => folder.max_id(),
+                CollectionItem::Request(id, _) => id.0,
+            })
+            .fold(self.id.0, usize::max)
+    }
+}
+
+/// Defaults resolved along the folder chain of a request, applied when it gets built
+#[derive(Clone, Default)]
+pub struct RequestDefaults {
+    pub base_url: String,
+    pub headers: Vec<Param>,
+    pub auth: RequestAuth,
+}
+
+impl RequestDefaults {
+    /// Folders are expected from the outermost to the innermost one
+    pub fn from_folders<'a>(folders: impl IntoIterator<Item = &'a Folder>) -> Self {
+        let mut defaults = RequestDefaults::default();
+        for folder in folders {
+            let folder_defaults = &folder.defaults;
+            if !folder_defaults.base_url.trim().is_empty() {
+                defaults.base_url = folder_defaults.base_url.clone();
+            }
+            for header in folder_defaults.headers.iter().filter(|h| h.enabled) {
+                defaults
+                    .headers
+                    .retain(|existing| !existing.key.eq_ignore_ascii_case(&header.key));
+                defaults.headers.push(header.clone());
+            }
+            if !matches!(folder_defaults.auth, RequestAuth::Inherit) {
+                defaults.auth = folder_defaults.auth.clone();
+            }
+        }
+        defaults
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct Collections {
+    pub items: Vec<Folder>,
+}
+
+impl Default for Collections {
+    fn default() -> Self {
+        let mut collection = Folder::new("My Collection".to_owned());
+        collection.items.push(CollectionItem::Request(
+            RequestId::next(),
+            Default::default(),
+        ));
+        Self {
+            items: vec![collection],
+        }
+    }
+}
+
+impl Collections {
+    pub fn find_request(&self, request_id: RequestId) -> Option<&RequestState> {
+        self.items
+            .iter()
+            .find_map(|folder| folder.find_request(request_id))
+    }
+
+    pub fn find_request_mut(&mut self, request_id: RequestId) -> Option<&mut RequestState> {
+        self.items
+            .iter_mut()
+            .find_map(|folder| folder.find_request_mut(request_id))
+    }
+
+    /// Request imported from the source identified by `key`, wherever it has been moved to
+    pub fn find_imported_request_mut(&mut self, key: &str) -> Option<&mut RequestState> {
+        self.items
+            .iter_mut()
+            .find_map(|folder| folder.find_imported_request_mut(key))
+    }
+
+    pub fn find_folder(&self, folder_id: FolderId) -> Option<&Folder> {
+        self.items
+            .iter()
+            .find_map(|folder| folder.find_folder(folder_id))
+    }
+
+    pub fn find_folder_mut(&mut self, folder_id: FolderId) -> Option<&mut Folder> {
+        self.items
+            .iter_mut()
+            .find_map(|folder| folder.find_folder_mut(folder_id))
+    }
+
+    /// Folders containing `item`, from the collection down to its direct parent
+    pub fn path_to(&self, item: ItemId) -> Vec<&Folder> {
+        let mut path = vec![];
+        for collection in &self.items {
+            if collection.path_to(item, &mut path) {
+                break;
+            }
+        }
+        path
+    }
+
+    pub fn parent_of(&self, item: ItemId) -> Option<FolderId> {
+        self.path_to(item).last().map(|folder| folder.id)
+    }
+
+    pub fn defaults_for(&self, request_id: RequestId) -> RequestDefaults {
+        RequestDefaults::from_folders(self.path_to(ItemId::Request(request_id)))
+    }
+
+    pub fn request_ids(&self) -> Vec<RequestId> {
+        let mut ids = vec![];
+        for collection in &self.items {
+            collection.request_ids(&mut ids);
+        }
+        ids
+    }
+
+    pub fn request_count(&self) -> usize {
+        self.request_ids().len()
+    }
+
+    /// All folders flattened with their depth, collections have depth 0
+    pub fn folders(&self) -> Vec<(usize, &Folder)> {
+        let mut folders = vec![];
+        for collection in &self.items {
+            collection.folders(0, &mut folders);
+        }
+        folders
+    }
+
+    pub fn max_id(&self) -> Option<usize> {
+        self.items.iter().map(Folder::max_id).max()
+    }
+
+    pub fn add_request(&mut self, folder_id: FolderId, state: RequestState) -> Option<RequestId> {
+        let folder = self.find_folder_mut(folder_id)?;
+        let request_id = RequestId::next();
+        folder
+            .items
+            .push(CollectionItem::Request(request_id, Box::new(state)));
+        Some(request_id)
+    }
+
+    pub fn add_folder(&mut self, parent_id: FolderId, name: String) -> Option<FolderId> {
+        let parent = self.find_folder_mut(parent_id)?;
+        let folder = Folder::new(name);
+        let folder_id = folder.id;
+        parent.items.push(CollectionItem::Folder(Box::new(folder)));
+        Some(folder_id)
+    }
+
+    pub fn remove(&mut self, item: ItemId) -> Option<CollectionItem> {
+        if let ItemId::Folder(folder_id) = item {
+            if let Some(index) = self.items.iter().position(|c| c.id == folder_id) {
+                return Some(CollectionItem::Folder(Box::new(self.items.remove(index))));
+            }
+        }
+        self.items.iter_mut().find_map(|folder| folder.remove(item))
+    }
+
+    /// Whether `item` is `folder_id` itself or lives anywhere below it
+    pub fn is_within(&self, item: ItemId, folder_id: FolderId) -> bool {
+        item == ItemId::Folder(folder_id)
+            || self
+                .path_to(item)
+                .iter()
+                .any(|folder| folder.id == folder_id)
+    }
+
+    /// Moves `item` into `folder_id` before the child at `index`, appends when `index` is None.
+    /// Moving a folder into itself or one of its descendants is ignored
+    pub fn move_item(&mut self, item: ItemId, folder_id: FolderId, index: Option<usize>) {
+        if let ItemId::Folder(moved_folder) = item {
+            if self.is_within(ItemId::Folder(folder_id), moved_folder) {
+                return;
+            }
+        }
+        if self.find_folder(folder_id).is_none() {
+            return;
+        }
+
+        // the target index shifts by one if the item is removed from before it in the same folder
+        let mut index = index;
+        if let (Some(target), Some(parent)) = (index, self.parent_of(item)) {
+            if parent == folder_id {
+                let current = self
+                    .find_folder(folder_id)
+                    .and_then(|folder| folder.position_of(item));
+                if let Some(current) = current {
+                    if current < target {
+                        index = Some(target - 1);
+                    }
+                }
+            }
+        }
+
+        let Some(removed) = self.remove(item) else {
+            return;
+        };
+
+        if let Some(folder) = self.find_folder_mut(folder_id) {
+            let index = index.unwrap_or(folder.items.len()).min(folder.items.len());
+            folder.items.insert(index, removed);
+        }
+    }
+
+    pub fn rename(&mut self, item: ItemId, name: String) {
+        match item {
+            ItemId::Folder(folder_id) => {
+                if let Some(folder) = self.find_folder_mut(folder_id) {
+                    folder.name = name;
+                }
+            }
+            ItemId::Request(request_id) => {
+                if let Some(state) = self.find_request_mut(request_id) {
+                    state.name = name;
+                }
+            }
+        }
+    }
+}
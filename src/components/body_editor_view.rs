@@ -1,7 +1,138 @@
-pub fn show(ui: &mut egui::Ui, body: &mut String) {
+use egui::TextStyle;
+
+use crate::{
+    body::{self, BodyKind, BodyOptions},
+    components::{graphql_editor_view, params_editor_view::ParamsEditorView},
+    core::Param,
+    graphql::CachedSchema,
+};
+
+const RAW_CONTENT_TYPES: [&str; 6] = [
+    "text/plain",
+    "application/xml",
+    "text/html",
+    "application/javascript",
+    "text/csv",
+    "application/yaml",
+];
+
+/// Returns true when the GraphQL schema is asked for
+pub fn show(
+    ui: &mut egui::Ui,
+    body: &mut String,
+    options: &mut BodyOptions,
+    form_view: &mut ParamsEditorView,
+    schema: &CachedSchema,
+) -> bool {
+    ui.horizontal(|ui| {
+        for kind in BodyKind::values() {
+            ui.selectable_value(&mut options.kind, kind, kind.to_string());
+        }
+    });
+    ui.add_space(8.0);
+
+    let mut introspect = false;
+    match options.kind {
+        BodyKind::Raw => {
+            ui.horizontal(|ui| {
+                ui.label("Content Type");
+                ui.add(
+                    egui::TextEdit::singleline(&mut options.content_type)
+                        .font(TextStyle::Monospace)
+                        .desired_width(220.0)
+                        .hint_text("none"),
+                );
+                egui::ComboBox::from_id_salt("body.content_type")
+                    .selected_text("")
+                    .width(0.0)
+                    .show_ui(ui, |ui| {
+                        for content_type in RAW_CONTENT_TYPES {
+                            ui.selectable_value(
+                                &mut options.content_type,
+                                content_type.to_owned(),
+                                content_type,
+                            );
+                        }
+                    });
+            });
+            ui.add_space(4.0);
+            show_text_editor(ui, body);
+        }
+        BodyKind::Json => {
+            let check = body::check_json(body);
+            ui.horizontal(|ui| {
+                // variables can't be kept through a round trip, only plain JSON is formatted
+                let formatted = serde_json::from_str::<serde_json::Value>(body)
+                    .ok()
+                    .and_then(|value| serde_json::to_string_pretty(&value).ok());
+                if ui
+                    .add_enabled(formatted.is_some(), egui::Button::new("Format"))
+                    .on_disabled_hover_text("Only valid JSON without variables outside strings")
+                    .clicked()
+                {
+                    if let Some(formatted) = formatted {
+                        *body = formatted;
+                    }
+                }
+                match &check {
+                    Ok(()) => ui.weak("Sent as application/json"),
+                    Err(err) => ui.colored_label(ui.visuals().error_fg_color, err),
+                };
+            });
+            ui.add_space(4.0);
+            show_text_editor(ui, body);
+        }
+        BodyKind::GraphQl => {
+            introspect =
+                graphql_editor_view::show(ui, body, &mut options.graphql_variables, schema);
+        }
+        BodyKind::FormUrlEncoded => {
+            form_view.show(ui, &mut options.form);
+        }
+        BodyKind::Multipart => {
+            add_dropped_files(ui, |path| {
+                options
+                    .form
+                    .retain(|param| !param.key.is_empty() || !param.value.is_empty());
+                let name = path.rsplit(['/', '\\']).next().unwrap_or(&path).to_owned();
+                options.form.push(Param {
+                    file: true,
+                    ..Param::enabled(name, path)
+                });
+            });
+            ui.weak("Drop files here to add them as parts");
+            form_view.show_with_files(ui, &mut options.form);
+        }
+        BodyKind::Binary => {
+            add_dropped_files(ui, |path| options.file = path);
+            ui.horizontal(|ui| {
+                ui.label("File");
+                ui.add(
+                    egui::TextEdit::singleline(&mut options.file)
+                        .font(TextStyle::Monospace)
+                        .desired_width(f32::INFINITY)
+                        .hint_text("Path of the file, or drop one here"),
+                );
+            });
+        }
+    }
+    introspect
+}
+
+fn show_text_editor(ui: &mut egui::Ui, body: &mut String) {
     let editor = egui::TextEdit::multiline(body)
         .code_editor()
         .desired_width(ui.available_width())
         .desired_rows(20);
     ui.add(editor);
 }
+
+/// Paths of files dropped on the window, only the desktop app gets them
+fn add_dropped_files(ui: &egui::Ui, mut add: impl FnMut(String)) {
+    let dropped_files = ui.ctx().input(|i| i.raw.dropped_files.clone());
+    for file in dropped_files {
+        if let Some(path) = file.path {
+            add(path.display().to_string());
+        }
+    }
+}
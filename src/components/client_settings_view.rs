@@ -0,0 +1,78 @@
+use egui::RichText;
+
+use crate::client::ClientSettings;
+
+pub fn show(ui: &mut egui::Ui, settings: &mut ClientSettings) {
+    egui::Grid::new(ui.id().with("client_settings"))
+        .num_columns(2)
+        .spacing(egui::Vec2::splat(6.0))
+        .min_col_width(120.0)
+        .show(ui, |ui| {
+            ui.label("Connect timeout");
+            ui.add(timeout_value(&mut settings.connect_timeout));
+            ui.end_row();
+
+            ui.label("Read timeout");
+            ui.add(timeout_value(&mut settings.read_timeout));
+            ui.end_row();
+
+            ui.label("Redirects");
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut settings.follow_redirects, "Follow");
+                ui.add_enabled(
+                    settings.follow_redirects,
+                    egui::DragValue::new(&mut settings.max_redirects)
+                        .range(0..=100)
+                        .prefix("at most "),
+                );
+            });
+            ui.end_row();
+
+            ui.label("Proxy");
+            ui.add(
+                egui::TextEdit::singleline(&mut settings.proxy)
+                    .hint_text("http://, https:// or socks5://host:port"),
+            );
+            ui.end_row();
+
+            ui.label("CA bundle");
+            ui.add(
+                egui::TextEdit::singleline(&mut settings.ca_bundle).hint_text("path to a PEM file"),
+            );
+            ui.end_row();
+
+            ui.label("Client certificate");
+            ui.add(
+                egui::TextEdit::singleline(&mut settings.client_cert)
+                    .hint_text("path to a PEM certificate"),
+            );
+            ui.end_row();
+
+            ui.label("Client key");
+            ui.add(
+                egui::TextEdit::singleline(&mut settings.client_key)
+                    .hint_text("path to a PEM PKCS #8 key"),
+            );
+            ui.end_row();
+
+            ui.label("Insecure");
+            ui.checkbox(
+                &mut settings.insecure,
+                RichText::new("Skip TLS certificate verification")
+                    .color(ui.visuals().warn_fg_color),
+            );
+            ui.end_row();
+        });
+}
+
+fn timeout_value(millis: &mut u64) -> egui::DragValue<'_> {
+    egui::DragValue::new(millis)
+        .speed(100)
+        .custom_formatter(|value, _| {
+            if value == 0.0 {
+                "none".to_owned()
+            } else {
+                format!("{value} ms")
+            }
+        })
+}
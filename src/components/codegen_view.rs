@@ -0,0 +1,104 @@
+use egui_extras::syntax_highlighting::CodeTheme;
+
+use crate::{
+    body::BodySource,
+    codegen::{self, CodegenOptions, Language},
+    components::http_error_view,
+    http::HttpError,
+};
+
+/// The request as it'd be sent, with its body as code writes it
+type Prepared = (http::Request<Vec<u8>>, BodySource);
+
+pub struct CodegenView {
+    language: Language,
+    options: CodegenOptions,
+    /// Built when asked for, building reads the body's files
+    request: Option<Result<Prepared, HttpError>>,
+}
+
+impl Default for CodegenView {
+    fn default() -> Self {
+        Self {
+            language: Language::default(),
+            // snippets tend to end up in chats and PRs, so err on the safe side
+            options: CodegenOptions {
+                redact_secrets: true,
+            },
+            request: None,
+        }
+    }
+}
+
+impl CodegenView {
+    /// Drops the request built for another one
+    pub fn forget(&mut self) {
+        self.request = None;
+    }
+
+    /// `prepare` builds the request as it would be sent, it runs when Generate is pressed
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        prepare: impl FnOnce() -> Result<Prepared, HttpError>,
+    ) {
+        let code = match &self.request {
+            Some(Ok((request, body))) => {
+                codegen::generate(request, body, self.language, &self.options)
+            }
+            _ => String::new(),
+        };
+
+        let mut generate = false;
+        ui.horizontal(|ui| {
+            generate = ui
+                .button("Generate")
+                .on_hover_text("Builds the request as it is now, the code doesn't follow edits")
+                .clicked();
+            egui::ComboBox::from_id_salt("codegen.language")
+                .selected_text(self.language.to_string())
+                .show_ui(ui, |ui| {
+                    for language in Language::values() {
+                        ui.selectable_value(&mut self.language, language, language.to_string());
+                    }
+                });
+            ui.checkbox(&mut self.options.redact_secrets, "Hide secrets");
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui
+                    .add_enabled(!code.is_empty(), egui::Button::new("\u{e14d}"))
+                    .on_hover_text("Copy")
+                    .clicked()
+                {
+                    ui.ctx().copy_text(code.clone());
+                }
+            });
+        });
+        ui.add_space(4.0);
+        ui.separator();
+        ui.add_space(8.0);
+        if generate {
+            self.request = Some(prepare());
+        }
+
+        match &self.request {
+            None => {
+                ui.weak("Generate turns the request into code");
+                return;
+            }
+            Some(Err(err)) => {
+                http_error_view::show(ui, err);
+                return;
+            }
+            Some(Ok(_)) => {}
+        }
+        egui::ScrollArea::both().show(ui, |ui| {
+            egui_extras::syntax_highlighting::code_view_ui(
+                ui,
+                &CodeTheme::from_style(ui.style()),
+                &code,
+                self.language.extension(),
+            );
+        });
+    }
+}
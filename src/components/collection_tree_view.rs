@@ -0,0 +1,313 @@
+use egui::{Sense, TextStyle};
+
+use crate::{
+    collections::{CollectionItem, Collections, Folder, FolderId, ItemId},
+    components::{auth_editor_view, params_editor_view::ParamsEditorView},
+    core::{RequestId, RequestState},
+};
+
+enum TreeAction {
+    Select(RequestId),
+    NewRequest(FolderId),
+    NewFolder(FolderId),
+    Run(FolderId),
+    Rename(ItemId, String),
+    Delete(ItemId),
+    Move {
+        item: ItemId,
+        folder: FolderId,
+        index: Option<usize>,
+    },
+}
+
+/// Per frame state shared while walking the tree
+struct TreeFrame {
+    active_request_id: RequestId,
+    /// Every folder with its depth, used for the "Move to" menu
+    folders: Vec<(usize, FolderId, String)>,
+    actions: Vec<TreeAction>,
+}
+
+#[derive(Default)]
+pub struct CollectionTreeView {
+    renaming: Option<(ItemId, String)>,
+    editing_defaults: Option<FolderId>,
+    params_view: ParamsEditorView,
+}
+
+impl CollectionTreeView {
+    /// Returns the folder the user asked to run
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        collections: &mut Collections,
+        active_request_id: &mut RequestId,
+    ) -> Option<FolderId> {
+        let mut frame = TreeFrame {
+            active_request_id: *active_request_id,
+            folders: collections
+                .folders()
+                .into_iter()
+                .map(|(depth, folder)| (depth, folder.id, folder.name.clone()))
+                .collect(),
+            actions: vec![],
+        };
+
+        for collection in &collections.items {
+            self.show_folder(ui, collection, &mut frame);
+        }
+
+        let mut run = None;
+        for action in frame.actions {
+            match action {
+                TreeAction::Select(request_id) => *active_request_id = request_id,
+                TreeAction::NewRequest(folder_id) => {
+                    if let Some(request_id) = collections.add_request(folder_id, Default::default())
+                    {
+                        *active_request_id = request_id;
+                    }
+                }
+                TreeAction::NewFolder(parent_id) => {
+                    if let Some(folder_id) =
+                        collections.add_folder(parent_id, "New Folder".to_owned())
+                    {
+                        self.renaming = Some((ItemId::Folder(folder_id), "New Folder".to_owned()));
+                    }
+                }
+                TreeAction::Run(folder_id) => run = Some(folder_id),
+                TreeAction::Rename(item, name) => collections.rename(item, name),
+                TreeAction::Delete(item) => {
+                    collections.remove(item);
+                }
+                TreeAction::Move {
+                    item,
+                    folder,
+                    index,
+                } => collections.move_item(item, folder, index),
+            }
+        }
+        run
+    }
+
+    /// Window for editing the base url, headers and auth a folder passes on to its requests
+    pub fn show_defaults_editor(&mut self, ctx: &egui::Context, collections: &mut Collections) {
+        let Some(folder_id) = self.editing_defaults else {
+            return;
+        };
+        let Some(folder) = collections.find_folder_mut(folder_id) else {
+            self.editing_defaults = None;
+            return;
+        };
+
+        let mut open = true;
+        egui::Window::new(format!("{} Settings", folder.name))
+            .id(egui::Id::new("folder_defaults_editor"))
+            .open(&mut open)
+            .default_width(480.0)
+            .show(ctx, |ui| {
+                ui.label("Requests in this folder inherit these unless they set their own");
+                ui.add_space(8.0);
+
+                egui::Grid::new("folder_defaults")
+                    .num_columns(2)
+                    .spacing(ui.style().spacing.item_spacing + egui::Vec2::new(0.0, 6.0))
+                    .show(ui, |ui| {
+                        ui.label("Base URL");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut folder.defaults.base_url)
+                                .font(TextStyle::Monospace)
+                                .hint_text("https://{{host}}/api"),
+                        );
+                        ui.end_row();
+                    });
+
+                ui.add_space(8.0);
+                egui::CollapsingHeader::new("Headers")
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        self.params_view.show(ui, &mut folder.defaults.headers);
+                    });
+                egui::CollapsingHeader::new("Auth")
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        auth_editor_view::show(ui, &mut folder.defaults.auth);
+                    });
+            });
+
+        if !open {
+            self.editing_defaults = None;
+        }
+    }
+
+    fn show_folder(&mut self, ui: &mut egui::Ui, folder: &Folder, frame: &mut TreeFrame) {
+        let item_id = ItemId::Folder(folder.id);
+        if self.show_rename_editor(ui, item_id, &mut frame.actions) {
+            return;
+        }
+
+        let response = egui::CollapsingHeader::new(&folder.name)
+            .id_salt(folder.id)
+            .default_open(true)
+            .show(ui, |ui| {
+                if folder.items.is_empty() {
+                    ui.weak("Empty");
+                }
+                for (index, item) in folder.items.iter().enumerate() {
+                    match item {
+                        CollectionItem::Folder(child) => self.show_folder(ui, child, frame),
+                        CollectionItem::Request(request_id, state) => {
+                            self.show_request(ui, folder.id, index, *request_id, state, frame)
+                        }
+                    }
+                }
+            });
+
+        let header = response.header_response.interact(Sense::drag());
+        if header.drag_started() {
+            header.dnd_set_drag_payload(item_id);
+        }
+        // dropping onto a folder header moves the item to the end of that folder
+        if header.dnd_hover_payload::<ItemId>().is_some() {
+            ui.painter().rect_stroke(
+                header.rect,
+                2.0,
+                (1.0, egui::Color32::WHITE),
+                egui::StrokeKind::Inside,
+            );
+        }
+        if let Some(payload) = header.dnd_release_payload::<ItemId>() {
+            frame.actions.push(TreeAction::Move {
+                item: *payload,
+                folder: folder.id,
+                index: None,
+            });
+        }
+
+        header.context_menu(|ui| {
+            if ui.button("New Request").clicked() {
+                frame.actions.push(TreeAction::NewRequest(folder.id));
+            }
+            if ui.button("New Folder").clicked() {
+                frame.actions.push(TreeAction::NewFolder(folder.id));
+            }
+            if ui.button("Settings").clicked() {
+                self.editing_defaults = Some(folder.id);
+            }
+            if ui.button("Run").clicked() {
+                frame.actions.push(TreeAction::Run(folder.id));
+            }
+            ui.separator();
+            if ui.button("Rename").clicked() {
+                self.renaming = Some((item_id, folder.name.clone()));
+            }
+            if ui.button("Delete").clicked() {
+                frame.actions.push(TreeAction::Delete(item_id));
+            }
+        });
+    }
+
+    fn show_request(
+        &mut self,
+        ui: &mut egui::Ui,
+        parent_id: FolderId,
+        index: usize,
+        request_id: RequestId,
+        state: &RequestState,
+        frame: &mut TreeFrame,
+    ) {
+        let item_id = ItemId::Request(request_id);
+        if self.show_rename_editor(ui, item_id, &mut frame.actions) {
+            return;
+        }
+
+        let response = ui
+            .selectable_label(frame.active_request_id == request_id, state.label())
+            .interact(Sense::drag());
+        if response.clicked() {
+            frame.actions.push(TreeAction::Select(request_id));
+        }
+        if response.drag_started() {
+            response.dnd_set_drag_payload(item_id);
+        }
+
+        // dropping onto a request inserts before or after it, depending on the pointer position
+        if response.dnd_hover_payload::<ItemId>().is_some() {
+            let (top, _) = response.rect.split_top_bottom_at_fraction(0.5);
+            let insert_before = ui.rect_contains_pointer(top);
+            let y = if insert_before {
+                response.rect.top()
+            } else {
+                response.rect.bottom()
+            };
+            ui.painter()
+                .hline(response.rect.x_range(), y, (2.0, egui::Color32::WHITE));
+
+            if let Some(payload) = response.dnd_release_payload::<ItemId>() {
+                frame.actions.push(TreeAction::Move {
+                    item: *payload,
+                    folder: parent_id,
+                    index: Some(if insert_before { index } else { index + 1 }),
+                });
+            }
+        }
+
+        response.context_menu(|ui| {
+            if ui.button("Rename").clicked() {
+                self.renaming = Some((item_id, state.label()));
+            }
+            ui.menu_button("Move to", |ui| {
+                for (depth, folder_id, name) in &frame.folders {
+                    ui.horizontal(|ui| {
+                        ui.add_space(*depth as f32 * 12.0);
+                        let is_parent = *folder_id == parent_id;
+                        if ui
+                            .add_enabled(!is_parent, egui::Button::new(name))
+                            .clicked()
+                        {
+                            frame.actions.push(TreeAction::Move {
+                                item: item_id,
+                                folder: *folder_id,
+                                index: None,
+                            });
+                        }
+                    });
+                }
+            });
+            ui.separator();
+            if ui.button("Delete").clicked() {
+                frame.actions.push(TreeAction::Delete(item_id));
+            }
+        });
+    }
+
+    /// Shows an inline text field while `item_id` is being renamed, returns whether it did
+    fn show_rename_editor(
+        &mut self,
+        ui: &mut egui::Ui,
+        item_id: ItemId,
+        actions: &mut Vec<TreeAction>,
+    ) -> bool {
+        let Some((renaming_id, name)) = &mut self.renaming else {
+            return false;
+        };
+        if *renaming_id != item_id {
+            return false;
+        }
+
+        let response = ui.add(egui::TextEdit::singleline(name).desired_width(f32::INFINITY));
+        if !response.has_focus() && !response.lost_focus() {
+            response.request_focus();
+        }
+
+        if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+            self.renaming = None;
+        } else if response.lost_focus() {
+            let name = name.trim().to_owned();
+            if !name.is_empty() {
+                actions.push(TreeAction::Rename(item_id, name));
+            }
+            self.renaming = None;
+        }
+        true
+    }
+}
@@ -0,0 +1,204 @@
+use egui::{RichText, TextWrapMode};
+
+use crate::cookies::{BasicCookieStore, CookieEntry};
+
+#[derive(Default)]
+pub struct CookiesView {
+    pub open: bool,
+    /// Cookie being added or edited, with the one it replaces
+    editing: Option<(Option<CookieEntry>, CookieEntry)>,
+    error: Option<String>,
+}
+
+impl CookiesView {
+    pub fn show(&mut self, ctx: &egui::Context, jar: &BasicCookieStore, persist: &mut bool) {
+        let mut open = self.open;
+        egui::Window::new("Cookies")
+            .open(&mut open)
+            .default_size([640.0, 400.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("Add").clicked() {
+                        self.editing = Some((None, CookieEntry::default()));
+                        self.error = None;
+                    }
+                    if ui.button("Clear All").clicked() {
+                        jar.clear();
+                        self.editing = None;
+                    }
+                    ui.checkbox(persist, "Keep cookies after restarting");
+                });
+                ui.separator();
+
+                if self.editing.is_some() {
+                    self.show_editor(ui, jar);
+                    ui.separator();
+                }
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    self.show_list(ui, jar);
+                });
+            });
+        self.open = open;
+    }
+
+    fn show_list(&mut self, ui: &mut egui::Ui, jar: &BasicCookieStore) {
+        let entries = jar.entries();
+        if entries.is_empty() {
+            ui.label("No cookies yet, they're added as responses set them");
+            return;
+        }
+
+        ui.style_mut().wrap_mode = Some(TextWrapMode::Truncate);
+        let mut domains = entries.chunk_by(|a, b| a.domain == b.domain).peekable();
+        while let Some(cookies) = domains.next() {
+            egui::CollapsingHeader::new(format!("{} ({})", cookies[0].domain, cookies.len()))
+                .default_open(true)
+                .show(ui, |ui| {
+                    egui::Grid::new(("cookies", &cookies[0].domain))
+                        .num_columns(6)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for cookie in cookies {
+                                self.show_row(ui, jar, cookie);
+                            }
+                        });
+                });
+            if domains.peek().is_some() {
+                ui.separator();
+            }
+        }
+    }
+
+    fn show_row(&mut self, ui: &mut egui::Ui, jar: &BasicCookieStore, cookie: &CookieEntry) {
+        ui.monospace(&cookie.name);
+        ui.add(egui::Label::new(RichText::new(&cookie.value).monospace()).truncate())
+            .on_hover_text(&cookie.value);
+        ui.monospace(&cookie.path);
+        if cookie.expires.is_empty() {
+            ui.weak("Session");
+        } else {
+            ui.label(&cookie.expires);
+        }
+        ui.weak(flags(cookie));
+        ui.horizontal(|ui| {
+            if ui.small_button("\u{e3c9}").on_hover_text("Edit").clicked() {
+                self.editing = Some((Some(cookie.clone()), cookie.clone()));
+                self.error = None;
+            }
+            if ui
+                .small_button("\u{e92e}")
+                .on_hover_text("Delete")
+                .clicked()
+            {
+                jar.remove(cookie);
+            }
+        });
+        ui.end_row();
+    }
+
+    fn show_editor(&mut self, ui: &mut egui::Ui, jar: &BasicCookieStore) {
+        let Some((original, draft)) = &mut self.editing else {
+            return;
+        };
+
+        egui::Grid::new("cookies.editor")
+            .num_columns(2)
+            .spacing(egui::Vec2::splat(6.0))
+            .show(ui, |ui| {
+                ui.label("Name");
+                ui.text_edit_singleline(&mut draft.name);
+                ui.end_row();
+
+                ui.label("Value");
+                ui.text_edit_singleline(&mut draft.value);
+                ui.end_row();
+
+                ui.label("Domain");
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut draft.domain);
+                    ui.checkbox(&mut draft.include_subdomains, "Include subdomains");
+                });
+                ui.end_row();
+
+                ui.label("Path");
+                ui.add(egui::TextEdit::singleline(&mut draft.path).hint_text("/"));
+                ui.end_row();
+
+                ui.label("Expires");
+                ui.add(
+                    egui::TextEdit::singleline(&mut draft.expires)
+                        .hint_text("empty for a session cookie"),
+                );
+                ui.end_row();
+
+                ui.label("Flags");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut draft.secure, "Secure");
+                    ui.checkbox(&mut draft.http_only, "HttpOnly");
+                    egui::ComboBox::from_id_salt("cookies.editor.same_site")
+                        .selected_text(match draft.same_site {
+                            Some(same_site) => format!("SameSite={same_site}"),
+                            None => "SameSite unset".to_owned(),
+                        })
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut draft.same_site, None, "Unset");
+                            for same_site in [
+                                cookie::SameSite::Strict,
+                                cookie::SameSite::Lax,
+                                cookie::SameSite::None,
+                            ] {
+                                ui.selectable_value(
+                                    &mut draft.same_site,
+                                    Some(same_site),
+                                    same_site.to_string(),
+                                );
+                            }
+                        });
+                });
+                ui.end_row();
+            });
+
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+        let (save, cancel) = ui
+            .horizontal(|ui| (ui.button("Save").clicked(), ui.button("Cancel").clicked()))
+            .inner;
+
+        if save {
+            if let Err(err) = jar.insert(draft) {
+                self.error = Some(err);
+                return;
+            }
+            // a renamed or moved cookie would otherwise stay under its old key
+            if let Some(original) = original.as_ref().filter(|o| !same_key(o, draft)) {
+                jar.remove(original);
+            }
+        }
+        if save || cancel {
+            self.editing = None;
+            self.error = None;
+        }
+    }
+}
+
+fn same_key(a: &CookieEntry, b: &CookieEntry) -> bool {
+    a.name.trim() == b.name.trim()
+        && a.domain.trim() == b.domain.trim()
+        && a.path.trim() == b.path.trim()
+}
+
+fn flags(cookie: &CookieEntry) -> String {
+    let mut flags = vec![];
+    if cookie.secure {
+        flags.push("Secure".to_owned());
+    }
+    if cookie.http_only {
+        flags.push("HttpOnly".to_owned());
+    }
+    if let Some(same_site) = cookie.same_site {
+        flags.push(format!("SameSite={same_site}"));
+    }
+    flags.join(" ")
+}
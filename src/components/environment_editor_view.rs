@@ -0,0 +1,120 @@
+use crate::{
+    components::params_editor_view::ParamsEditorView,
+    environment::{Environment, Environments},
+};
+
+#[derive(Default)]
+pub struct EnvironmentEditorView {
+    pub open: bool,
+    selected: usize,
+    params_view: ParamsEditorView,
+}
+
+impl EnvironmentEditorView {
+    /// Switcher shown in the top panel, lets you pick the active environment or open the editor
+    pub fn show_switcher(&mut self, ui: &mut egui::Ui, environments: &mut Environments) {
+        ui.label("Environment");
+
+        let selected_text = environments
+            .active()
+            .map(|env| env.name.clone())
+            .unwrap_or_else(|| "No Environment".to_owned());
+
+        egui::ComboBox::from_id_salt("environment.switcher")
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut environments.active, None, "No Environment");
+                for (index, env) in environments.items.iter().enumerate() {
+                    ui.selectable_value(&mut environments.active, Some(index), &env.name);
+                }
+            });
+
+        if ui.button("Manage \u{e8b8}").clicked() {
+            self.open = true;
+            if let Some(active) = environments.active {
+                self.selected = active;
+            }
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, environments: &mut Environments) {
+        let mut open = self.open;
+        egui::Window::new("Environments")
+            .open(&mut open)
+            .default_size([560.0, 360.0])
+            .show(ctx, |ui| {
+                egui::SidePanel::left("environment.list")
+                    .resizable(false)
+                    .default_width(140.0)
+                    .show_inside(ui, |ui| {
+                        self.show_list(ui, environments);
+                    });
+
+                egui::CentralPanel::default().show_inside(ui, |ui| {
+                    self.show_selected(ui, environments);
+                });
+            });
+        self.open = open;
+    }
+
+    fn show_list(&mut self, ui: &mut egui::Ui, environments: &mut Environments) {
+        if ui.button("Add").clicked() {
+            let name = format!("Environment {}", environments.items.len() + 1);
+            environments.items.push(Environment::new(name));
+            self.selected = environments.items.len() - 1;
+        }
+        ui.separator();
+
+        for (index, env) in environments.items.iter().enumerate() {
+            let label = if environments.active == Some(index) {
+                format!("{} \u{e5ca}", env.name)
+            } else {
+                env.name.clone()
+            };
+            if ui.selectable_label(self.selected == index, label).clicked() {
+                self.selected = index;
+            }
+        }
+    }
+
+    fn show_selected(&mut self, ui: &mut egui::Ui, environments: &mut Environments) {
+        let index = self.selected;
+        let is_active = environments.active == Some(index);
+
+        let Some(env) = environments.items.get_mut(index) else {
+            ui.label("No environment selected");
+            return;
+        };
+
+        let mut delete = false;
+        let mut activate = false;
+        ui.horizontal(|ui| {
+            ui.label("Name");
+            ui.text_edit_singleline(&mut env.name);
+            if ui
+                .add_enabled(!is_active, egui::Button::new("Set Active"))
+                .clicked()
+            {
+                activate = true;
+            }
+            if ui.button("Delete \u{e872}").clicked() {
+                delete = true;
+            }
+        });
+        ui.add_space(8.0);
+        ui.label("Use variables as {{name}} in the URL, params, headers, body and auth fields");
+        ui.add_space(8.0);
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            self.params_view.show(ui, &mut env.variables);
+        });
+
+        if activate {
+            environments.active = Some(index);
+        }
+        if delete {
+            environments.remove(index);
+            self.selected = index.saturating_sub(1);
+        }
+    }
+}
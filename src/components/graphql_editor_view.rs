@@ -0,0 +1,149 @@
+use egui::{
+    RichText,
+    text::{CCursor, CCursorRange},
+};
+
+use crate::{
+    body,
+    graphql::{self, CachedSchema, SchemaStatus},
+};
+
+/// Most fields suggested at once, the rest show up as more of the name is typed
+const MAX_COMPLETIONS: usize = 30;
+
+/// Editors of the query and its variables, returns true when the schema is asked for
+pub fn show(
+    ui: &mut egui::Ui,
+    query: &mut String,
+    variables: &mut String,
+    cached: &CachedSchema,
+) -> bool {
+    let introspect = show_schema_status(ui, cached);
+    ui.add_space(4.0);
+
+    ui.strong("Query");
+    let mut output = egui::TextEdit::multiline(query)
+        .id_salt("graphql.query")
+        .code_editor()
+        .desired_width(ui.available_width())
+        .desired_rows(14)
+        .show(ui);
+    let cursor = output
+        .state
+        .cursor
+        .char_range()
+        .map(|range| byte_offset(query, range.primary.index));
+    let analysis = graphql::analyze(query, cached.schema.as_deref(), cursor);
+
+    // the suggestions stay up while they're pointed at, clicking one takes the focus away
+    let hovered_id = output.response.id.with("completions_hovered");
+    let hovered = ui.data(|data| data.get_temp::<bool>(hovered_id).unwrap_or(false));
+    let completions = analysis
+        .completions
+        .filter(|completions| !completions.fields.is_empty());
+    let mut picked = None;
+    match completions {
+        Some(completions) if output.response.has_focus() || hovered => {
+            let strip = ui.horizontal_wrapped(|ui| {
+                ui.weak("Fields");
+                for (name, field_type) in completions.fields.iter().take(MAX_COMPLETIONS) {
+                    if ui
+                        .small_button(RichText::new(name).monospace())
+                        .on_hover_text(field_type)
+                        .clicked()
+                    {
+                        picked = Some((completions.replace.clone(), name.clone()));
+                    }
+                }
+                if completions.fields.len() > MAX_COMPLETIONS {
+                    ui.weak(format!(
+                        "{} more",
+                        completions.fields.len() - MAX_COMPLETIONS
+                    ));
+                }
+            });
+            let hovered = strip.response.contains_pointer();
+            ui.data_mut(|data| data.insert_temp(hovered_id, hovered));
+        }
+        _ => ui.data_mut(|data| data.insert_temp(hovered_id, false)),
+    }
+    if let Some((replace, name)) = picked {
+        query.replace_range(replace.clone(), &name);
+        let end = query[..replace.start + name.len()].chars().count();
+        output
+            .state
+            .cursor
+            .set_char_range(Some(CCursorRange::one(CCursor::new(end))));
+        output.state.store(ui.ctx(), output.response.id);
+        output.response.request_focus();
+    }
+
+    for problem in &analysis.problems {
+        let (line, column) = problem.position(query);
+        ui.colored_label(
+            ui.visuals().error_fg_color,
+            format!("{line}:{column} {}", problem.message),
+        );
+    }
+    if analysis.problems.is_empty() && cached.schema.is_some() && !query.trim().is_empty() {
+        ui.weak("The query fits the schema");
+    }
+    ui.add_space(8.0);
+
+    ui.horizontal(|ui| {
+        ui.strong("Variables");
+        if let Err(err) = body::check_json(variables) {
+            ui.colored_label(ui.visuals().error_fg_color, err);
+        }
+    });
+    ui.add(
+        egui::TextEdit::multiline(variables)
+            .id_salt("graphql.variables")
+            .code_editor()
+            .desired_width(ui.available_width())
+            .desired_rows(6)
+            .hint_text("{ \"id\": 1 }"),
+    );
+    introspect
+}
+
+/// The introspection button with how the last one went, returns true when it's clicked
+pub fn show_schema_status(ui: &mut egui::Ui, cached: &CachedSchema) -> bool {
+    let mut introspect = false;
+    ui.horizontal(|ui| {
+        let fetching = matches!(cached.status, SchemaStatus::Fetching);
+        if ui
+            .add_enabled(!fetching, egui::Button::new("Introspect Schema"))
+            .on_hover_text("Runs the introspection query against the request's url")
+            .clicked()
+        {
+            introspect = true;
+        }
+        match (&cached.status, &cached.schema) {
+            (SchemaStatus::Fetching, _) => {
+                ui.spinner();
+                ui.label("Fetching the schema");
+                // the schema arrives without any input to trigger a repaint
+                ui.ctx()
+                    .request_repaint_after(std::time::Duration::from_millis(200));
+            }
+            (SchemaStatus::Failed(err), _) => {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            }
+            (SchemaStatus::Idle, Some(schema)) => {
+                ui.weak(format!("Schema with {} types", schema.types.len()));
+            }
+            (SchemaStatus::Idle, None) => {
+                ui.weak("No schema yet, fields are completed and checked once there's one");
+            }
+        }
+    });
+    introspect
+}
+
+fn byte_offset(text: &str, char_index: usize) -> usize {
+    text.char_indices()
+        .nth(char_index)
+        .map(|(offset, _)| offset)
+        .unwrap_or(text.len())
+}
@@ -0,0 +1,36 @@
+use egui::RichText;
+
+use crate::graphql::ResponseError;
+
+/// The `errors` of a GraphQL response, set apart from its `data`
+pub fn show(ui: &mut egui::Ui, errors: &[ResponseError]) {
+    let color = ui.visuals().error_fg_color;
+    let title = match errors.len() {
+        1 => "1 GraphQL error".to_owned(),
+        count => format!("{count} GraphQL errors"),
+    };
+    egui::CollapsingHeader::new(RichText::new(title).color(color).strong())
+        .id_salt("graphql.errors")
+        .default_open(true)
+        .show(ui, |ui| {
+            egui::Grid::new("graphql.errors.grid")
+                .num_columns(3)
+                .spacing(egui::Vec2::splat(6.0))
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.weak("Message");
+                    ui.weak("Path");
+                    ui.weak("Location");
+                    ui.end_row();
+
+                    for error in errors {
+                        ui.colored_label(color, &error.message);
+                        ui.monospace(&error.path);
+                        ui.monospace(error.locations.join(", "));
+                        ui.end_row();
+                    }
+                });
+        });
+    ui.separator();
+    ui.add_space(4.0);
+}
@@ -0,0 +1,195 @@
+use egui::{Color32, RichText};
+
+use crate::{
+    body,
+    grpc::{self, CachedDescriptors, DescriptorSource, DescriptorStatus, GrpcOptions, GrpcStatus},
+};
+
+/// Where the descriptors come from, the method to call and its message, returns true when
+/// the descriptors are asked for
+pub fn show(
+    ui: &mut egui::Ui,
+    options: &mut GrpcOptions,
+    message: &mut String,
+    cached: &CachedDescriptors,
+) -> bool {
+    ui.horizontal(|ui| {
+        for source in DescriptorSource::values() {
+            ui.selectable_value(&mut options.source, source, source.to_string());
+        }
+    });
+    ui.add_space(4.0);
+    if options.source == DescriptorSource::ProtoFiles {
+        ui.strong("Proto Files");
+        show_paths(
+            ui,
+            "grpc.proto_files",
+            &mut options.proto_files,
+            "protos/service.proto",
+        );
+        ui.strong("Import Paths");
+        show_paths(ui, "grpc.import_paths", &mut options.import_paths, "protos");
+        ui.add_space(4.0);
+    }
+    let load = show_descriptor_status(ui, options.source, cached);
+    ui.add_space(8.0);
+
+    let services = cached.pool.as_ref().map(grpc::services).unwrap_or_default();
+    let service = services
+        .iter()
+        .find(|service| service.full_name() == options.service);
+    ui.horizontal(|ui| {
+        ui.label("Service");
+        egui::ComboBox::from_id_salt("grpc.service")
+            .selected_text(&options.service)
+            .width(240.0)
+            .show_ui(ui, |ui| {
+                for service in &services {
+                    let name = service.full_name().to_owned();
+                    if ui
+                        .selectable_label(options.service == name, &name)
+                        .clicked()
+                    {
+                        options.service = name;
+                        options.method.clear();
+                    }
+                }
+            });
+        ui.label("Method");
+        egui::ComboBox::from_id_salt("grpc.method")
+            .selected_text(&options.method)
+            .width(180.0)
+            .show_ui(ui, |ui| {
+                for method in service.into_iter().flat_map(|service| service.methods()) {
+                    let label = format!("{} ({})", method.name(), grpc::call_kind(&method));
+                    ui.selectable_value(&mut options.method, method.name().to_owned(), label);
+                }
+            });
+    });
+    let method = service.and_then(|service| {
+        service
+            .methods()
+            .find(|method| method.name() == options.method)
+    });
+    if let Some(method) = &method {
+        ui.weak(format!(
+            "{}, takes {} and returns {}",
+            grpc::call_kind(method),
+            method.input().full_name(),
+            method.output().full_name()
+        ));
+    }
+    ui.add_space(8.0);
+
+    ui.horizontal(|ui| {
+        ui.strong("Message");
+        if let Some(method) = &method {
+            if ui
+                .small_button("Template")
+                .on_hover_text("Replaces the message with every field of it at its default")
+                .clicked()
+            {
+                *message = grpc::message_template(&method.input());
+            }
+        }
+        if let Err(err) = body::check_json(message) {
+            ui.colored_label(ui.visuals().error_fg_color, err);
+        }
+    });
+    ui.add(
+        egui::TextEdit::multiline(message)
+            .id_salt("grpc.message")
+            .code_editor()
+            .desired_width(ui.available_width())
+            .desired_rows(12)
+            .hint_text("The message as JSON, {{variables}} are substituted"),
+    );
+    ui.weak("Metadata is sent from the Headers tab");
+    load
+}
+
+/// The load or reflect button with how the last one went, returns true when it's clicked
+fn show_descriptor_status(
+    ui: &mut egui::Ui,
+    source: DescriptorSource,
+    cached: &CachedDescriptors,
+) -> bool {
+    let mut load = false;
+    ui.horizontal(|ui| {
+        let loading = matches!(cached.status, DescriptorStatus::Loading);
+        let (label, hover) = match source {
+            DescriptorSource::ProtoFiles => ("Load Protos", "Parses the files above"),
+            DescriptorSource::Reflection => (
+                "Reflect Services",
+                "Asks the server at the request's url, with its headers and auth",
+            ),
+        };
+        if ui
+            .add_enabled(!loading, egui::Button::new(label))
+            .on_hover_text(hover)
+            .clicked()
+        {
+            load = true;
+        }
+        match (&cached.status, &cached.pool) {
+            (DescriptorStatus::Loading, _) => {
+                ui.spinner();
+                ui.label("Loading the descriptors");
+                // the descriptors arrive without any input to trigger a repaint
+                ui.ctx()
+                    .request_repaint_after(std::time::Duration::from_millis(200));
+            }
+            (DescriptorStatus::Failed(err), _) => {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            }
+            (DescriptorStatus::Idle, Some(pool)) => {
+                ui.weak(format!("{} services", grpc::services(pool).len()));
+            }
+            (DescriptorStatus::Idle, None) => {
+                ui.weak("Nothing loaded yet");
+            }
+        }
+    });
+    load
+}
+
+fn show_paths(ui: &mut egui::Ui, id: &str, paths: &mut Vec<String>, hint: &str) {
+    let mut removed = None;
+    for (index, path) in paths.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(path)
+                    .id_salt((id, index))
+                    .desired_width(360.0)
+                    .hint_text(hint),
+            );
+            if ui
+                .small_button("\u{e5cd}")
+                .on_hover_text("Remove")
+                .clicked()
+            {
+                removed = Some(index);
+            }
+        });
+    }
+    if let Some(index) = removed {
+        paths.remove(index);
+    }
+    if ui.small_button("Add").clicked() {
+        paths.push(String::new());
+    }
+}
+
+/// How a gRPC call ended, shown above its messages
+pub fn show_status(ui: &mut egui::Ui, status: &GrpcStatus) {
+    let color = match status.is_ok() {
+        true => Color32::from_rgb(90, 180, 90),
+        false => ui.visuals().error_fg_color,
+    };
+    ui.horizontal(|ui| {
+        ui.weak("gRPC Status");
+        ui.label(RichText::new(status.to_string()).color(color).strong());
+    });
+    ui.separator();
+    ui.add_space(4.0);
+}
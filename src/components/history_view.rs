@@ -0,0 +1,313 @@
+use egui::{Color32, RichText, TextWrapMode};
+use similar::{DiffTag, TextDiff};
+
+use crate::{
+    components::response_stats_view::format_size,
+    core::RequestState,
+    history::{self, History, HistoryEntry},
+};
+
+#[derive(Default)]
+pub struct HistoryView {
+    search: String,
+    /// Entry picked as the left side of a comparison
+    compare_with: Option<usize>,
+    /// Entries shown in the compare window
+    diff: Option<(usize, usize)>,
+}
+
+impl HistoryView {
+    /// Lists the history, returns a request to open when an entry gets restored
+    pub fn show(&mut self, ui: &mut egui::Ui, history: &mut History) -> Option<RequestState> {
+        let mut restored = None;
+        let mut removed = None;
+
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(format!("History ({})", history.entries().len())).size(16.0));
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui
+                    .button("\u{e92e}")
+                    .on_hover_text("Clear history")
+                    .clicked()
+                {
+                    history.clear();
+                    self.compare_with = None;
+                }
+            });
+        });
+        ui.add(
+            egui::TextEdit::singleline(&mut self.search)
+                .hint_text("Search by url, method or status")
+                .desired_width(f32::INFINITY),
+        );
+        if let Some(id) = self.compare_with {
+            ui.horizontal(|ui| {
+                ui.weak(format!("Comparing #{id}, pick another entry"));
+                if ui.small_button("\u{e5cd}").clicked() {
+                    self.compare_with = None;
+                }
+            });
+        }
+        ui.separator();
+
+        ui.style_mut().wrap_mode = Some(TextWrapMode::Truncate);
+        egui::ScrollArea::vertical()
+            .id_salt("history.entries")
+            .show(ui, |ui| {
+                let search = self.search.clone();
+                let entries = history
+                    .entries()
+                    .iter()
+                    .rev()
+                    .filter(|entry| entry.matches(&search));
+                for entry in entries {
+                    let selected = self.compare_with == Some(entry.id);
+                    let frame = egui::Frame::new().inner_margin(4).fill(if selected {
+                        ui.visuals().selection.bg_fill.gamma_multiply(0.4)
+                    } else {
+                        Color32::TRANSPARENT
+                    });
+                    frame.show(ui, |ui| {
+                        self.show_entry(ui, entry, &mut restored, &mut removed);
+                    });
+                    ui.separator();
+                }
+            });
+
+        if let Some(id) = removed {
+            history.remove(id);
+            if self.compare_with == Some(id) {
+                self.compare_with = None;
+            }
+        }
+        restored
+    }
+
+    fn show_entry(
+        &mut self,
+        ui: &mut egui::Ui,
+        entry: &HistoryEntry,
+        restored: &mut Option<RequestState>,
+        removed: &mut Option<usize>,
+    ) {
+        ui.horizontal(|ui| {
+            let status = ui.label(status_text(ui, entry.response.status));
+            if let Some(err) = &entry.error {
+                status.on_hover_text(err.to_string());
+            }
+            ui.monospace(entry.method_label());
+            if !entry.name.is_empty() {
+                ui.label(&entry.name);
+            }
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui
+                    .small_button("\u{e92e}")
+                    .on_hover_text("Delete")
+                    .clicked()
+                {
+                    *removed = Some(entry.id);
+                }
+                let compare_hint = match self.compare_with {
+                    Some(id) if id != entry.id => "Compare with the picked entry",
+                    _ => "Compare",
+                };
+                if ui
+                    .small_button("\u{e915}")
+                    .on_hover_text(compare_hint)
+                    .clicked()
+                {
+                    self.compare_with = match self.compare_with {
+                        Some(id) if id == entry.id => None,
+                        Some(id) => {
+                            self.diff = Some((id, entry.id));
+                            None
+                        }
+                        None => Some(entry.id),
+                    };
+                }
+                if ui
+                    .small_button("\u{e89e}")
+                    .on_hover_text("Restore into a new request")
+                    .clicked()
+                {
+                    *restored = Some(entry.restore());
+                }
+            });
+        });
+        ui.add(egui::Label::new(RichText::new(&entry.sent.url).monospace()).truncate())
+            .on_hover_text(&entry.sent.url);
+        ui.weak(format!(
+            "{} · {} ms · {}",
+            time_ago(entry.timestamp),
+            entry.response.duration.as_millis(),
+            format_size(entry.response.sizes.response_body),
+        ));
+    }
+
+    /// Side by side diff of the headers and bodies of two entries
+    pub fn show_diff(&mut self, ctx: &egui::Context, history: &History) {
+        let Some((left_id, right_id)) = self.diff else {
+            return;
+        };
+        let (Some(left), Some(right)) = (history.get(left_id), history.get(right_id)) else {
+            self.diff = None;
+            return;
+        };
+
+        let mut open = true;
+        egui::Window::new("Compare")
+            .open(&mut open)
+            .default_size([900.0, 600.0])
+            .show(ctx, |ui| {
+                egui::Grid::new("history.compare.titles")
+                    .num_columns(2)
+                    .min_col_width(ui.available_width() / 2.0 - 8.0)
+                    .show(ui, |ui| {
+                        ui.strong(entry_title(left));
+                        ui.strong(entry_title(right));
+                        ui.end_row();
+                    });
+                ui.separator();
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    let sections = [
+                        (
+                            "Request Headers",
+                            headers_text(&left.sent.headers),
+                            headers_text(&right.sent.headers),
+                        ),
+                        (
+                            "Request Body",
+                            left.sent.body.clone(),
+                            right.sent.body.clone(),
+                        ),
+                        (
+                            "Response Headers",
+                            headers_text(&left.response.headers),
+                            headers_text(&right.response.headers),
+                        ),
+                        ("Response Body", response_body(left), response_body(right)),
+                    ];
+                    for (title, old, new) in sections {
+                        let changed = old != new;
+                        egui::CollapsingHeader::new(if changed {
+                            format!("{title} (changed)")
+                        } else {
+                            title.to_owned()
+                        })
+                        .id_salt(title)
+                        .default_open(changed)
+                        .show(ui, |ui| show_text_diff(ui, title, &old, &new));
+                    }
+                });
+            });
+
+        if !open {
+            self.diff = None;
+        }
+    }
+}
+
+fn show_text_diff(ui: &mut egui::Ui, id: &str, old: &str, new: &str) {
+    let diff = TextDiff::from_lines(old, new);
+    let (old_lines, new_lines) = (diff.old_slices(), diff.new_slices());
+    let removed = ui.visuals().error_fg_color;
+    let added = Color32::from_rgb(80, 200, 120);
+
+    let line = |text: Option<&&str>, color: Option<Color32>| {
+        let text = text
+            .map(|text| text.trim_end_matches(['\n', '\r']))
+            .unwrap_or("");
+        let text = RichText::new(text).monospace();
+        match color {
+            Some(color) => text
+                .color(color)
+                .background_color(color.gamma_multiply(0.12)),
+            None => text,
+        }
+    };
+
+    egui::Grid::new(id)
+        .num_columns(2)
+        .min_col_width(ui.available_width() / 2.0 - 8.0)
+        .max_col_width(ui.available_width() / 2.0 - 8.0)
+        .show(ui, |ui| {
+            for op in diff.ops() {
+                let (tag, old_range, new_range) = op.as_tag_tuple();
+                let rows = old_range.len().max(new_range.len());
+                for row in 0..rows {
+                    let old_line = old_lines
+                        .get(old_range.start + row)
+                        .filter(|_| row < old_range.len());
+                    let new_line = new_lines
+                        .get(new_range.start + row)
+                        .filter(|_| row < new_range.len());
+                    let (old_color, new_color) = match tag {
+                        DiffTag::Equal => (None, None),
+                        _ => (Some(removed), Some(added)),
+                    };
+                    ui.label(line(old_line, old_color.filter(|_| old_line.is_some())));
+                    ui.label(line(new_line, new_color.filter(|_| new_line.is_some())));
+                    ui.end_row();
+                }
+            }
+        });
+}
+
+fn status_text(ui: &egui::Ui, status: u16) -> RichText {
+    let color = match status {
+        0 => ui.visuals().error_fg_color,
+        200..=299 => Color32::from_rgb(80, 200, 120),
+        300..=399 => ui.visuals().warn_fg_color,
+        _ => ui.visuals().error_fg_color,
+    };
+    let text = if status == 0 {
+        "ERR".to_owned()
+    } else {
+        status.to_string()
+    };
+    RichText::new(text).monospace().color(color)
+}
+
+fn entry_title(entry: &HistoryEntry) -> String {
+    format!(
+        "#{} {} {} ({})",
+        entry.id,
+        entry.method_label(),
+        entry.sent.url,
+        time_ago(entry.timestamp)
+    )
+}
+
+fn headers_text(headers: &[(String, String)]) -> String {
+    let mut lines = headers
+        .iter()
+        .map(|(name, value)| format!("{name}: {value}\n"))
+        .collect::<Vec<_>>();
+    // order doesn't matter for headers, sorting keeps the diff about values
+    lines.sort();
+    lines.concat()
+}
+
+fn response_body(entry: &HistoryEntry) -> String {
+    if let Some(err) = &entry.error {
+        return err.to_string();
+    }
+    entry
+        .response
+        .body_pretty
+        .clone()
+        .unwrap_or_else(|| entry.response.body_raw.clone())
+}
+
+fn time_ago(timestamp: u64) -> String {
+    let seconds = history::now_millis().saturating_sub(timestamp) / 1000;
+    match seconds {
+        0..=9 => "just now".to_owned(),
+        10..=59 => format!("{seconds}s ago"),
+        60..=3599 => format!("{}m ago", seconds / 60),
+        3600..=86399 => format!("{}h ago", seconds / 3600),
+        _ => format!("{}d ago", seconds / 86400),
+    }
+}
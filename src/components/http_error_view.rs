@@ -0,0 +1,14 @@
+use egui::RichText;
+
+use crate::http::HttpError;
+
+pub fn show(ui: &mut egui::Ui, error: &HttpError) {
+    ui.label(
+        RichText::new(error.title())
+            .size(16.0)
+            .color(ui.visuals().error_fg_color),
+    );
+    ui.label(error.hint());
+    ui.add_space(6.0);
+    ui.add(egui::Label::new(RichText::new(error.to_string()).monospace().weak()).wrap());
+}
@@ -0,0 +1,127 @@
+use crate::{
+    collections::Collections,
+    environment::Environments,
+    import::{self, ImportSummary},
+};
+
+#[derive(Default)]
+pub struct ImportView {
+    pub open: bool,
+    input: String,
+    outcome: Option<Result<ImportSummary, String>>,
+}
+
+impl ImportView {
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        collections: &mut Collections,
+        environments: &mut Environments,
+    ) {
+        if !self.open {
+            return;
+        }
+
+        // files dropped anywhere on the window while it's open get imported right away
+        let dropped_files = ctx.input(|i| i.raw.dropped_files.clone());
+        for file in dropped_files {
+            match read_dropped_file(&file) {
+                Ok(content) => self.run_import(&content, collections, environments),
+                Err(err) => self.outcome = Some(Err(format!("{}: {err}", file.name))),
+            }
+        }
+
+        let mut open = self.open;
+        egui::Window::new("Import")
+            .open(&mut open)
+            .default_size([520.0, 420.0])
+            .show(ctx, |ui| {
+                ui.label(
+                    "Paste a Postman v2.1 collection or environment export, or an OpenAPI 3 / Swagger 2 spec in JSON or YAML. Files can be dropped here too",
+                );
+                ui.add_space(8.0);
+
+                egui::ScrollArea::vertical()
+                    .max_height(220.0)
+                    .show(ui, |ui| {
+                        ui.add(
+                            egui::TextEdit::multiline(&mut self.input)
+                                .code_editor()
+                                .desired_rows(10)
+                                .desired_width(f32::INFINITY),
+                        );
+                    });
+
+                ui.add_space(8.0);
+                if ui
+                    .add_enabled(!self.input.trim().is_empty(), egui::Button::new("Import"))
+                    .clicked()
+                {
+                    let input = std::mem::take(&mut self.input);
+                    self.run_import(&input, collections, environments);
+                }
+
+                ui.separator();
+                self.show_outcome(ui);
+            });
+        self.open = open;
+    }
+
+    fn run_import(
+        &mut self,
+        input: &str,
+        collections: &mut Collections,
+        environments: &mut Environments,
+    ) {
+        self.outcome = Some(match import::import(input) {
+            Ok(result) => Ok(import::merge_into(result, collections, environments)),
+            Err(err) => Err(err.to_string()),
+        });
+    }
+
+    fn show_outcome(&mut self, ui: &mut egui::Ui) {
+        match &self.outcome {
+            None => {}
+            Some(Err(err)) => {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            }
+            Some(Ok(summary)) => {
+                ui.label(format!(
+                    "Imported {} request(s) in {} folder(s) and {} environment(s)",
+                    summary.requests, summary.folders, summary.environments
+                ));
+                if summary.updated > 0 {
+                    ui.label(format!(
+                        "{} previously imported request(s) updated, local edits were kept",
+                        summary.updated
+                    ));
+                }
+                if !summary.warnings.is_empty() {
+                    ui.add_space(4.0);
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        format!("{} item(s) need attention:", summary.warnings.len()),
+                    );
+                    egui::ScrollArea::vertical()
+                        .id_salt("import.warnings")
+                        .show(ui, |ui| {
+                            for warning in &summary.warnings {
+                                ui.label(warning);
+                            }
+                        });
+                }
+            }
+        }
+    }
+}
+
+pub fn read_dropped_file(file: &egui::DroppedFile) -> Result<String, String> {
+    // the web gives us the bytes directly, native only the path
+    if let Some(bytes) = &file.bytes {
+        return String::from_utf8(bytes.to_vec()).map_err(|err| err.to_string());
+    }
+    match &file.path {
+        Some(path) => std::fs::read_to_string(path).map_err(|err| err.to_string()),
+        None => Err("file has no content".to_owned()),
+    }
+}
@@ -0,0 +1,223 @@
+use cookie::time::{OffsetDateTime, format_description::well_known::Rfc2822};
+use egui::{RichText, TextStyle};
+use egui_extras::syntax_highlighting::CodeTheme;
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::{
+    auth::jwt,
+    http::{HttpError, HttpResponse},
+};
+
+/// Decodes the JWTs found in the request and its last response, or a pasted one
+#[derive(Default)]
+pub struct JwtView {
+    pasted: String,
+    /// Where the shown token was found, kept while the tokens change on every send
+    selected: Option<String>,
+    /// Tokens of the request as it would be sent, signed ones change every time so
+    /// they're only read when asked for
+    request_tokens: Option<Result<Vec<(String, String)>, HttpError>>,
+}
+
+impl JwtView {
+    /// Drops the tokens read from another request
+    pub fn forget(&mut self) {
+        self.request_tokens = None;
+    }
+
+    /// `prepare` builds the request with its auth applied, it runs when asked for
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        prepare: impl FnOnce() -> Result<http::Request<Vec<u8>>, HttpError>,
+        response: Option<&HttpResponse>,
+    ) {
+        let mut found = match &self.request_tokens {
+            Some(Ok(tokens)) => tokens.clone(),
+            _ => vec![],
+        };
+        if let Some(response) = response {
+            for (name, value) in &response.headers {
+                collect(&mut found, &format!("Response header {name}"), value);
+            }
+            collect(&mut found, "Response body", &response.body_raw);
+        }
+        if !self.pasted.trim().is_empty() {
+            found.push(("Pasted".to_owned(), self.pasted.trim().to_owned()));
+        }
+
+        let mut read_request = false;
+        ui.horizontal(|ui| {
+            read_request = ui
+                .button("Read Request")
+                .on_hover_text("Signs or looks up the request's tokens as they'd be sent now")
+                .clicked();
+            ui.label("Token");
+            egui::ComboBox::from_id_salt("jwt.source")
+                .selected_text(
+                    self.selected
+                        .clone()
+                        .filter(|selected| found.iter().any(|(source, _)| source == selected))
+                        .or_else(|| found.first().map(|(source, _)| source.clone()))
+                        .unwrap_or_else(|| "None found".to_owned()),
+                )
+                .show_ui(ui, |ui| {
+                    for (source, _) in &found {
+                        ui.selectable_value(&mut self.selected, Some(source.clone()), source);
+                    }
+                });
+        });
+        ui.add(
+            egui::TextEdit::singleline(&mut self.pasted)
+                .font(TextStyle::Monospace)
+                .desired_width(f32::INFINITY)
+                .hint_text("Paste a token to decode it"),
+        );
+        if let Some(Err(err)) = &self.request_tokens {
+            ui.colored_label(ui.visuals().error_fg_color, err.to_string());
+        }
+        if read_request {
+            self.request_tokens = Some(prepare().map(|request| request_tokens(&request)));
+        }
+        ui.add_space(4.0);
+        ui.separator();
+        ui.add_space(8.0);
+
+        let token = found
+            .iter()
+            .find(|(source, _)| self.selected.as_ref() == Some(source))
+            .or(found.first())
+            .map(|(_, token)| token);
+        let Some(token) = token else {
+            match self.request_tokens {
+                Some(_) => ui.weak("No JWT in the request or its response"),
+                None => ui.weak("No JWT in the response, Read Request looks in the request"),
+            };
+            return;
+        };
+
+        match jwt::decode(token) {
+            Ok(decoded) => show_decoded(ui, token, &decoded),
+            Err(err) => {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            }
+        }
+    }
+}
+
+fn request_tokens(request: &http::Request<Vec<u8>>) -> Vec<(String, String)> {
+    let mut found = vec![];
+    for (name, value) in request.headers() {
+        let value = String::from_utf8_lossy(value.as_bytes());
+        collect(&mut found, &format!("Request header {name}"), &value);
+    }
+    if let Some(query) = request.uri().query() {
+        collect(&mut found, "Request query", query);
+    }
+    found
+}
+
+/// Adds the tokens in `text`, numbered when the same place has several
+fn collect(found: &mut Vec<(String, String)>, source: &str, text: &str) {
+    let tokens = jwt::find_tokens(text);
+    let several = tokens.len() > 1;
+    for (index, token) in tokens.into_iter().enumerate() {
+        let source = match several {
+            true => format!("{source} #{}", index + 1),
+            false => source.to_owned(),
+        };
+        found.push((source, token));
+    }
+}
+
+fn show_decoded(ui: &mut egui::Ui, token: &str, decoded: &jwt::Decoded) {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs() as i64)
+        .unwrap_or_default();
+
+    egui::Grid::new("jwt.times")
+        .num_columns(2)
+        .spacing(egui::Vec2::splat(6.0))
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("Token");
+            ui.horizontal(|ui| {
+                if ui.small_button("\u{e14d}").on_hover_text("Copy").clicked() {
+                    ui.ctx().copy_text(token.to_owned());
+                }
+                ui.add(egui::Label::new(RichText::new(token).monospace()).truncate());
+            });
+            ui.end_row();
+
+            ui.label("Algorithm");
+            ui.label(
+                decoded
+                    .header
+                    .get("alg")
+                    .and_then(|alg| alg.as_str())
+                    .unwrap_or("-"),
+            );
+            ui.end_row();
+
+            ui.label("Signature");
+            ui.add(
+                egui::Label::new(RichText::new(&decoded.signature).monospace().weak()).truncate(),
+            );
+            ui.end_row();
+
+            for (claim, label) in [("iat", "Issued"), ("nbf", "Not Before"), ("exp", "Expires")] {
+                let Some(time) = decoded.time_claim(claim) else {
+                    continue;
+                };
+                ui.label(label);
+                ui.horizontal(|ui| {
+                    let expired = claim == "exp" && time <= now;
+                    let relative = RichText::new(relative_time(time - now));
+                    if expired {
+                        ui.colored_label(ui.visuals().warn_fg_color, relative.strong());
+                    } else {
+                        ui.label(relative);
+                    }
+                    ui.weak(absolute_time(time));
+                });
+                ui.end_row();
+            }
+        });
+    ui.weak("The signature isn't verified");
+    ui.add_space(8.0);
+
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        for (title, part) in [("Header", &decoded.header), ("Claims", &decoded.claims)] {
+            ui.strong(title);
+            let json = serde_json::to_string_pretty(part).unwrap_or_default();
+            egui_extras::syntax_highlighting::code_view_ui(
+                ui,
+                &CodeTheme::from_style(ui.style()),
+                &json,
+                "json",
+            );
+            ui.add_space(8.0);
+        }
+    });
+}
+
+fn relative_time(seconds: i64) -> String {
+    let amount = match seconds.unsigned_abs() {
+        left @ 0..60 => format!("{left} s"),
+        left @ 60..3600 => format!("{} min", left / 60),
+        left @ 3600..86400 => format!("{} h {} min", left / 3600, left % 3600 / 60),
+        left => format!("{} days", left / 86400),
+    };
+    match seconds < 0 {
+        true => format!("{amount} ago"),
+        false => format!("In {amount}"),
+    }
+}
+
+fn absolute_time(seconds: i64) -> String {
+    OffsetDateTime::from_unix_timestamp(seconds)
+        .ok()
+        .and_then(|time| time.format(&Rfc2822).ok())
+        .unwrap_or_else(|| seconds.to_string())
+}
@@ -1,7 +1,26 @@
 pub mod auth_editor_view;
 pub mod body_editor_view;
 pub mod body_reader_view;
+pub mod client_settings_view;
+pub mod codegen_view;
+pub mod collection_tree_view;
+pub mod cookies_view;
+pub mod environment_editor_view;
+pub mod graphql_editor_view;
+pub mod graphql_errors_view;
+pub mod grpc_editor_view;
+pub mod history_view;
+pub mod http_error_view;
+pub mod import_view;
+pub mod jwt_view;
 pub mod navigation_bar_view;
+pub mod oauth2_token_view;
 pub mod params_editor_view;
 pub mod params_reader_view;
 pub mod response_stats_view;
+pub mod runner_view;
+pub mod schema_view;
+pub mod script_view;
+pub mod stream_view;
+pub mod tests_view;
+pub mod websocket_view;
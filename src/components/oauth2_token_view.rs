@@ -0,0 +1,129 @@
+use egui::{RichText, TextStyle};
+
+use crate::auth::{
+    OAuth2Params,
+    oauth2::{Token, TokenCache, TokenStatus},
+};
+
+/// Inspector of the token cached for the auth, returns true when a new token is asked for
+pub fn show(ui: &mut egui::Ui, params: &OAuth2Params, tokens: &TokenCache) -> bool {
+    let cached = tokens.get(params);
+    let mut fetch = false;
+
+    ui.horizontal(|ui| {
+        ui.strong("Token");
+        let busy = matches!(
+            cached.status,
+            TokenStatus::Fetching | TokenStatus::Authorizing { .. }
+        );
+        if ui
+            .add_enabled(!busy, egui::Button::new("Get New Token"))
+            .clicked()
+        {
+            fetch = true;
+        }
+        if cached.token.is_some() && ui.button("Clear").clicked() {
+            tokens.clear(params);
+        }
+    });
+
+    match &cached.status {
+        TokenStatus::Idle => {}
+        TokenStatus::Fetching => {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Getting a token");
+            });
+        }
+        TokenStatus::Authorizing { url } => {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Waiting for the authorization in the browser");
+            });
+            ui.hyperlink_to("Open the authorization page", url);
+        }
+        TokenStatus::Failed(err) => {
+            ui.colored_label(ui.visuals().error_fg_color, err);
+        }
+    }
+    if !matches!(cached.status, TokenStatus::Idle | TokenStatus::Failed(_)) {
+        // the token arrives without any input to trigger a repaint
+        ui.ctx()
+            .request_repaint_after(std::time::Duration::from_millis(200));
+    }
+
+    match &cached.token {
+        Some(token) => show_token(ui, token),
+        None => {
+            ui.weak("No token yet, one is fetched when the request is sent");
+        }
+    }
+    fetch
+}
+
+fn show_token(ui: &mut egui::Ui, token: &Token) {
+    egui::Grid::new("oauth2.token")
+        .num_columns(2)
+        .spacing(egui::Vec2::splat(6.0))
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("Access Token");
+            ui.horizontal(|ui| {
+                if ui.small_button("\u{e14d}").on_hover_text("Copy").clicked() {
+                    ui.ctx().copy_text(token.access_token.clone());
+                }
+                ui.add(egui::Label::new(RichText::new(&token.access_token).monospace()).truncate());
+            });
+            ui.end_row();
+
+            ui.label("Type");
+            ui.label(&token.token_type);
+            ui.end_row();
+
+            ui.label("Expires");
+            match token.seconds_left() {
+                None => ui.weak("Not said by the server"),
+                Some(left) if left <= 0 => ui.colored_label(ui.visuals().warn_fg_color, "Expired"),
+                Some(left) => ui.label(format!("In {}", format_seconds(left))),
+            };
+            ui.end_row();
+
+            ui.label("Scope");
+            ui.label(token.scope.as_deref().unwrap_or("-"));
+            ui.end_row();
+
+            ui.label("Refresh Token");
+            match &token.refresh_token {
+                Some(refresh_token) => {
+                    ui.add(egui::Label::new(RichText::new(refresh_token).monospace()).truncate())
+                        .on_hover_text("Used to get a new token once this one expires");
+                }
+                None => {
+                    ui.weak("None");
+                }
+            }
+            ui.end_row();
+        });
+
+    egui::CollapsingHeader::new("Token Response")
+        .id_salt("oauth2.token.raw")
+        .show(ui, |ui| {
+            let pretty = serde_json::from_str::<serde_json::Value>(&token.raw)
+                .ok()
+                .and_then(|value| serde_json::to_string_pretty(&value).ok());
+            let mut raw = pretty.as_deref().unwrap_or(&token.raw);
+            ui.add(
+                egui::TextEdit::multiline(&mut raw)
+                    .font(TextStyle::Monospace)
+                    .desired_width(f32::INFINITY),
+            );
+        });
+}
+
+fn format_seconds(seconds: i64) -> String {
+    match seconds {
+        0..60 => format!("{seconds} s"),
+        60..3600 => format!("{} min", seconds / 60),
+        _ => format!("{} h {} min", seconds / 3600, seconds % 3600 / 60),
+    }
+}
@@ -4,10 +4,24 @@ use crate::core::Param;
 pub struct ParamsEditorView {
     bulk_edit_enabled: bool,
     bulk_edit_value: String,
+    /// Rows can be switched to a file, for multipart bodies
+    #[serde(skip)]
+    files: bool,
 }
 
 impl ParamsEditorView {
     pub fn show(&mut self, ui: &mut egui::Ui, values: &mut Vec<Param>) {
+        self.files = false;
+        self.show_params(ui, values);
+    }
+
+    /// Like `show`, with a toggle on each row to send a file instead of the value
+    pub fn show_with_files(&mut self, ui: &mut egui::Ui, values: &mut Vec<Param>) {
+        self.files = true;
+        self.show_params(ui, values);
+    }
+
+    fn show_params(&mut self, ui: &mut egui::Ui, values: &mut Vec<Param>) {
         ui.horizontal(|ui| {
             if ui
                 .checkbox(&mut self.bulk_edit_enabled, "Bulk Edit")
@@ -125,10 +139,20 @@ impl ParamsEditorView {
                         .hint_text("Key")
                         .desired_width(150.0),
                 );
+                if self.files {
+                    ui.add_enabled_ui(param.enabled, |ui| {
+                        ui.toggle_value(&mut param.file, "File")
+                            .on_hover_text("Send the file at the path in the value");
+                    });
+                }
                 ui.add_enabled(
                     param.enabled,
                     egui::TextEdit::singleline(&mut param.value)
-                        .hint_text("Value")
+                        .hint_text(if self.files && param.file {
+                            "Path"
+                        } else {
+                            "Value"
+                        })
                         .desired_width(200.0),
                 );
                 if values.len() > 1 {
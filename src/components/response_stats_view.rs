@@ -1,6 +1,13 @@
+use std::time::Duration;
+
+use egui::{Color32, RichText};
+
 use crate::http::HttpResponse;
 
 pub fn show(id: egui::Id, ui: &mut egui::Ui, response: &HttpResponse) {
+    let timing = &response.timing;
+    let sizes = &response.sizes;
+
     egui::Grid::new(id)
         .num_columns(2)
         .spacing(egui::Vec2::splat(6.0))
@@ -19,5 +26,110 @@ pub fn show(id: egui::Id, ui: &mut egui::Ui, response: &HttpResponse) {
             ui.label("Status Text");
             ui.label(response.status_text.to_string());
             ui.end_row();
+
+            ui.label("Total");
+            ui.label(format_duration(response.duration));
+            ui.end_row();
+
+            ui.label("Time To First Byte");
+            ui.label(format_duration(timing.time_to_first_byte()));
+            ui.end_row();
+
+            ui.label("DNS Lookup");
+            ui.label(timing.dns.map_or("-".to_owned(), format_duration))
+                .on_hover_text("Empty when the connection was reused");
+            ui.end_row();
+
+            ui.label(timing.connect_label());
+            ui.label(timing.connect.map_or("-".to_owned(), format_duration))
+                .on_hover_text("Empty when the connection was reused");
+            ui.end_row();
+
+            ui.label("Request Size");
+            ui.label(format!(
+                "{} headers, {} body",
+                format_size(sizes.request_headers),
+                format_size(sizes.request_body)
+            ));
+            ui.end_row();
+
+            ui.label("Response Size");
+            ui.label(format!(
+                "{} headers, {} body",
+                format_size(sizes.response_headers),
+                format_size(sizes.response_body)
+            ));
+            ui.end_row();
+        });
+
+    if !response.redirects.is_empty() {
+        ui.add_space(8.0);
+        ui.label(format!("Redirects ({})", response.redirects.len()));
+        for redirect in &response.redirects {
+            ui.horizontal(|ui| {
+                ui.monospace(redirect.status.to_string());
+                ui.add(egui::Label::new(RichText::new(&redirect.url).monospace()).truncate())
+                    .on_hover_text(&redirect.url);
+            });
+        }
+    }
+
+    ui.add_space(8.0);
+    waterfall(ui, response);
+}
+
+/// One bar per phase, placed at its offset within the total
+fn waterfall(ui: &mut egui::Ui, response: &HttpResponse) {
+    let total = response.timing.total.as_secs_f32();
+    if total <= 0.0 {
+        return;
+    }
+
+    let colors = [
+        Color32::from_rgb(0, 150, 136),
+        Color32::from_rgb(255, 152, 0),
+        Color32::from_rgb(156, 39, 176),
+        Color32::from_rgb(33, 150, 243),
+    ];
+    let row_height = ui.text_style_height(&egui::TextStyle::Body);
+
+    for (i, (label, offset, duration)) in response.timing.phases().into_iter().enumerate() {
+        ui.horizontal(|ui| {
+            ui.add_sized(
+                [100.0, row_height],
+                egui::Label::new(RichText::new(label).small()),
+            );
+            let width = (ui.available_width() - 80.0).max(40.0);
+            let (rect, _) =
+                ui.allocate_exact_size([width, row_height].into(), egui::Sense::hover());
+            ui.painter()
+                .rect_filled(rect, 2.0, ui.visuals().faint_bg_color);
+
+            let start = rect.left() + rect.width() * offset.as_secs_f32() / total;
+            // keep very short phases visible
+            let end = (rect.left() + rect.width() * (offset + duration).as_secs_f32() / total)
+                .max(start + 1.0);
+            let bar = egui::Rect::from_x_y_ranges(start..=end, rect.y_range());
+            ui.painter().rect_filled(bar, 2.0, colors[i % colors.len()]);
+
+            ui.label(RichText::new(format_duration(duration)).small());
         });
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let millis = duration.as_secs_f64() * 1000.0;
+    if millis < 10.0 {
+        format!("{millis:.2} ms")
+    } else {
+        format!("{millis:.0} ms")
+    }
+}
+
+pub fn format_size(bytes: usize) -> String {
+    match bytes {
+        0..1024 => format!("{bytes} B"),
+        1024..1_048_576 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
 }
@@ -0,0 +1,236 @@
+use egui::RichText;
+
+use crate::{
+    collections::FolderId,
+    components::{
+        import_view,
+        tests_view::{self, PASSED_COLOR},
+    },
+    core::RequestId,
+    runner::{self, FolderRun, RunOptions},
+};
+
+pub enum RunnerAction {
+    Start(FolderId, RunOptions),
+    /// Select a request of the run in the editor
+    Open(RequestId),
+}
+
+#[derive(Default)]
+pub struct RunnerView {
+    pub open: bool,
+    folder: Option<(FolderId, String)>,
+    options: RunOptions,
+    run: Option<FolderRun>,
+    error: Option<String>,
+}
+
+impl RunnerView {
+    /// Opens the runner for a folder, the options and results of the last run are kept
+    pub fn open_for(&mut self, folder_id: FolderId, name: String) {
+        self.folder = Some((folder_id, name));
+        self.error = None;
+        self.open = true;
+    }
+
+    pub fn started(&mut self, run: Result<FolderRun, String>) {
+        match run {
+            Ok(run) => {
+                self.run = Some(run);
+                self.error = None;
+            }
+            Err(err) => self.error = Some(err),
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<RunnerAction> {
+        if !self.open {
+            return None;
+        }
+
+        // files dropped while the runner is open become its data
+        for file in ctx.input(|i| i.raw.dropped_files.clone()) {
+            match import_view::read_dropped_file(&file) {
+                Ok(content) => self.options.data = content,
+                Err(err) => self.error = Some(format!("{}: {err}", file.name)),
+            }
+        }
+
+        let mut action = None;
+        let mut open = self.open;
+        egui::Window::new("Runner")
+            .id(egui::Id::new("runner"))
+            .open(&mut open)
+            .default_size([520.0, 480.0])
+            .show(ctx, |ui| {
+                if let Some((_, name)) = &self.folder {
+                    ui.heading(name);
+                }
+                self.show_options(ui);
+
+                let running = self.run.as_ref().is_some_and(|run| !run.is_finished());
+                ui.horizontal(|ui| {
+                    if running {
+                        if ui.button("Stop").clicked() {
+                            if let Some(run) = &self.run {
+                                run.stop();
+                            }
+                        }
+                    } else if let Some((folder_id, _)) = &self.folder {
+                        if ui.button("Start").clicked() {
+                            action = Some(RunnerAction::Start(*folder_id, self.options.clone()));
+                        }
+                    }
+                });
+                if let Some(error) = &self.error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+
+                if let Some(run) = &self.run {
+                    ui.separator();
+                    if let Some(request_id) = show_run(ui, run) {
+                        action = Some(RunnerAction::Open(request_id));
+                    }
+                }
+            });
+        self.open = open;
+        action
+    }
+
+    fn show_options(&mut self, ui: &mut egui::Ui) {
+        let options = &mut self.options;
+        egui::Grid::new("runner.options")
+            .num_columns(2)
+            .spacing(egui::Vec2::splat(6.0))
+            .show(ui, |ui| {
+                ui.label("Iterations");
+                ui.add(egui::DragValue::new(&mut options.iterations).range(1..=10_000));
+                ui.end_row();
+
+                ui.label("Delay");
+                ui.add(
+                    egui::DragValue::new(&mut options.delay)
+                        .speed(10)
+                        .suffix(" ms"),
+                );
+                ui.end_row();
+
+                ui.label("On failure");
+                ui.checkbox(&mut options.stop_on_failure, "Stop the run");
+                ui.end_row();
+            });
+
+        egui::CollapsingHeader::new("Data")
+            .default_open(!options.data.is_empty())
+            .show(ui, |ui| {
+                ui.weak("CSV with a header row or a JSON array of objects, each row sets the variables of one iteration. Files can be dropped here");
+                egui::ScrollArea::vertical()
+                    .id_salt("runner.data")
+                    .max_height(120.0)
+                    .show(ui, |ui| {
+                        ui.add(
+                            egui::TextEdit::multiline(&mut options.data)
+                                .code_editor()
+                                .hint_text("id,name\n1,first\n2,second")
+                                .desired_rows(4)
+                                .desired_width(f32::INFINITY),
+                        );
+                    });
+                match runner::parse_data(&options.data) {
+                    Ok(rows) if rows.is_empty() => {}
+                    Ok(rows) => {
+                        ui.weak(format!("{} row(s)", rows.len()));
+                    }
+                    Err(err) => {
+                        ui.colored_label(ui.visuals().error_fg_color, err);
+                    }
+                }
+            });
+    }
+}
+
+/// Progress and results of a run, returns the request the user wants to open
+fn show_run(ui: &mut egui::Ui, run: &FolderRun) -> Option<RequestId> {
+    let progress = run.progress.lock().unwrap();
+    let outcomes = &progress.outcomes;
+    ui.strong(format!("Run of {}", run.name));
+    if !progress.finished {
+        let text = match &progress.current {
+            Some((iteration, name)) if run.iterations > 1 => {
+                format!("Iteration {} of {}: {name}", iteration + 1, run.iterations)
+            }
+            Some((_, name)) => name.clone(),
+            None => "Starting".to_owned(),
+        };
+        ui.add(
+            egui::ProgressBar::new(outcomes.len() as f32 / run.total().max(1) as f32).text(text),
+        );
+        // responses arrive without any input to trigger a repaint
+        ui.ctx()
+            .request_repaint_after(std::time::Duration::from_millis(100));
+    } else if outcomes.len() < run.total() {
+        ui.colored_label(
+            ui.visuals().warn_fg_color,
+            format!(
+                "Stopped after {} of {} requests",
+                outcomes.len(),
+                run.total()
+            ),
+        );
+    }
+
+    let passed_requests = outcomes.iter().filter(|o| o.passed()).count();
+    let assertions = outcomes
+        .iter()
+        .filter_map(|o| o.result.as_ref().ok())
+        .flatten();
+    let (passed, total) = assertions.fold((0, 0), |(passed, total), result| {
+        (passed + usize::from(result.passed), total + 1)
+    });
+    ui.label(format!(
+        "Requests: {passed_requests} passed, {} failed",
+        outcomes.len() - passed_requests
+    ));
+    ui.label(format!("Assertions: {passed} of {total} passed"));
+    if run.websockets > 0 {
+        ui.weak(format!(
+            "{} WebSocket requests skipped, runs can't send them",
+            run.websockets
+        ));
+    }
+    ui.separator();
+
+    let mut selected = None;
+    egui::ScrollArea::vertical()
+        .id_salt("runner.outcomes")
+        .show(ui, |ui| {
+            for (index, outcome) in outcomes.iter().enumerate() {
+                let name = if run.iterations > 1 {
+                    format!("#{} {}", outcome.iteration + 1, outcome.name)
+                } else {
+                    outcome.name.clone()
+                };
+                let title = if outcome.passed() {
+                    RichText::new(format!("\u{e5ca} {name}")).color(PASSED_COLOR)
+                } else {
+                    RichText::new(format!("\u{e5cd} {name}")).color(ui.visuals().error_fg_color)
+                };
+                let header = egui::CollapsingHeader::new(title)
+                    .id_salt(("runner.outcome", index))
+                    .default_open(!outcome.passed())
+                    .show(ui, |ui| match &outcome.result {
+                        Ok(results) => tests_view::show_results(ui, results),
+                        Err(err) => {
+                            ui.colored_label(ui.visuals().error_fg_color, err.title());
+                            ui.weak(err.to_string());
+                        }
+                    });
+                header.header_response.context_menu(|ui| {
+                    if ui.button("Open Request").clicked() {
+                        selected = Some(outcome.request_id);
+                    }
+                });
+            }
+        });
+    selected
+}
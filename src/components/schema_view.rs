@@ -0,0 +1,182 @@
+use egui::RichText;
+
+use crate::{
+    components::graphql_editor_view,
+    graphql::{CachedSchema, Schema, SchemaType, TypeRef},
+};
+
+/// Explorer of the schema introspected at the request's url
+#[derive(Default)]
+pub struct SchemaView {
+    filter: String,
+    /// Name of the type shown, the query type until one is picked
+    selected: Option<String>,
+}
+
+impl SchemaView {
+    /// Returns true when the schema is asked for
+    pub fn show(&mut self, ui: &mut egui::Ui, cached: &CachedSchema) -> bool {
+        let introspect = graphql_editor_view::show_schema_status(ui, cached);
+        ui.add_space(4.0);
+        ui.separator();
+
+        let Some(schema) = &cached.schema else {
+            return introspect;
+        };
+        let roots = schema.roots();
+        let selected = self
+            .selected
+            .clone()
+            .filter(|name| schema.get(name).is_some())
+            .or_else(|| roots.first().map(|(_, name)| name.to_string()));
+
+        ui.columns(2, |columns| {
+            self.show_types(&mut columns[0], schema, selected.as_deref());
+            if let Some(schema_type) = selected.as_deref().and_then(|name| schema.get(name)) {
+                self.show_type(&mut columns[1], schema_type);
+            }
+        });
+        introspect
+    }
+
+    fn show_types(&mut self, ui: &mut egui::Ui, schema: &Schema, selected: Option<&str>) {
+        ui.add(
+            egui::TextEdit::singleline(&mut self.filter)
+                .desired_width(f32::INFINITY)
+                .hint_text("Filter types"),
+        );
+        let filter = self.filter.to_lowercase();
+        let roots = schema.roots();
+        // roots first, the introspection types only get in the way
+        let mut types = schema
+            .types
+            .iter()
+            .filter(|schema_type| !schema_type.name.starts_with("__"))
+            .filter(|schema_type| schema_type.name.to_lowercase().contains(&filter))
+            .collect::<Vec<_>>();
+        types.sort_by_key(|schema_type| {
+            let root = roots.iter().position(|(_, name)| *name == schema_type.name);
+            (root.is_none(), root, schema_type.name.clone())
+        });
+
+        egui::ScrollArea::vertical()
+            .id_salt("schema.types")
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                for schema_type in types {
+                    let root = roots
+                        .iter()
+                        .find(|(_, name)| *name == schema_type.name)
+                        .map(|(operation, _)| *operation);
+                    let label = match root {
+                        Some(operation) => format!("{} ({operation})", schema_type.name),
+                        None => schema_type.name.clone(),
+                    };
+                    let current = selected == Some(schema_type.name.as_str());
+                    if ui
+                        .selectable_label(current, label)
+                        .on_hover_text(schema_type.kind.to_string())
+                        .clicked()
+                    {
+                        self.selected = Some(schema_type.name.clone());
+                    }
+                }
+            });
+    }
+
+    fn show_type(&mut self, ui: &mut egui::Ui, schema_type: &SchemaType) {
+        egui::ScrollArea::vertical()
+            .id_salt("schema.type")
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.weak(schema_type.kind.to_string());
+                    ui.heading(&schema_type.name);
+                });
+                if let Some(description) = &schema_type.description {
+                    ui.label(description);
+                }
+                if !schema_type.interfaces.is_empty() {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.weak("Implements");
+                        for interface in &schema_type.interfaces {
+                            self.type_link(ui, interface);
+                        }
+                    });
+                }
+                ui.add_space(8.0);
+
+                for field in &schema_type.fields {
+                    ui.horizontal_wrapped(|ui| {
+                        let name = RichText::new(&field.name).monospace().strong();
+                        match field.is_deprecated {
+                            true => ui.label(name.strikethrough()),
+                            false => ui.label(name),
+                        };
+                        if !field.args.is_empty() {
+                            ui.monospace("(");
+                            for (index, arg) in field.args.iter().enumerate() {
+                                if index > 0 {
+                                    ui.monospace(",");
+                                }
+                                ui.monospace(format!("{}:", arg.name));
+                                self.type_link(ui, &arg.type_ref);
+                            }
+                            ui.monospace(")");
+                        }
+                        ui.monospace(":");
+                        self.type_link(ui, &field.type_ref);
+                    });
+                    if let Some(description) = &field.description {
+                        ui.weak(description);
+                    }
+                    ui.add_space(4.0);
+                }
+
+                for input in &schema_type.input_fields {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.monospace(RichText::new(&input.name).strong());
+                        ui.monospace(":");
+                        self.type_link(ui, &input.type_ref);
+                        if let Some(default_value) = &input.default_value {
+                            ui.monospace(format!("= {default_value}"));
+                        }
+                    });
+                    if let Some(description) = &input.description {
+                        ui.weak(description);
+                    }
+                    ui.add_space(4.0);
+                }
+
+                for value in &schema_type.enum_values {
+                    let name = RichText::new(&value.name).monospace().strong();
+                    match value.is_deprecated {
+                        true => ui.label(name.strikethrough()),
+                        false => ui.label(name),
+                    };
+                    if let Some(description) = &value.description {
+                        ui.weak(description);
+                    }
+                }
+
+                if !schema_type.possible_types.is_empty() {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.weak("One of");
+                        for possible in &schema_type.possible_types {
+                            self.type_link(ui, possible);
+                        }
+                    });
+                }
+            });
+    }
+
+    /// The type in its SDL notation, clicking it shows the named type
+    fn type_link(&mut self, ui: &mut egui::Ui, type_ref: &TypeRef) {
+        if ui
+            .link(RichText::new(type_ref.to_string()).monospace())
+            .clicked()
+        {
+            self.selected = Some(type_ref.named().to_owned());
+        }
+    }
+}
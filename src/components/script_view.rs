@@ -0,0 +1,111 @@
+use std::sync::Mutex;
+
+use egui::RichText;
+
+use crate::script::{ScriptOutput, Scripts};
+
+#[derive(Default, PartialEq)]
+enum ScriptTab {
+    #[default]
+    PreRequest,
+    PostResponse,
+}
+
+#[derive(Default)]
+pub struct ScriptView {
+    tab: ScriptTab,
+}
+
+impl ScriptView {
+    pub fn show(&mut self, ui: &mut egui::Ui, scripts: &mut Scripts, output: &Mutex<ScriptOutput>) {
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.tab, ScriptTab::PreRequest, "Pre-request");
+            ui.selectable_value(&mut self.tab, ScriptTab::PostResponse, "Post-response");
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.hyperlink_to("Rhai", "https://rhai.rs/book/");
+                help_button(ui);
+            });
+        });
+        ui.add_space(4.0);
+
+        let script = match self.tab {
+            ScriptTab::PreRequest => &mut scripts.pre_request,
+            ScriptTab::PostResponse => &mut scripts.post_response,
+        };
+        let hint = match self.tab {
+            ScriptTab::PreRequest => "request.set_header(\"X-Request-Id\", uuid());",
+            ScriptTab::PostResponse => "vars.set(\"token\", response.json().access_token);",
+        };
+        egui::ScrollArea::vertical()
+            .id_salt("script.editor")
+            .max_height(ui.available_height() * 0.6)
+            .show(ui, |ui| {
+                ui.add(
+                    egui::TextEdit::multiline(script)
+                        .code_editor()
+                        .hint_text(hint)
+                        .desired_width(f32::INFINITY)
+                        .desired_rows(12),
+                );
+            });
+
+        ui.separator();
+        let mut output = output.lock().unwrap();
+        ui.horizontal(|ui| {
+            ui.label("Console");
+            if ui.small_button("\u{e92e}").on_hover_text("Clear").clicked() {
+                output.console.clear();
+            }
+        });
+        egui::ScrollArea::vertical()
+            .id_salt("script.console")
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                for line in &output.console {
+                    let text = RichText::new(line).monospace();
+                    if line.starts_with("error:") {
+                        ui.label(text.color(ui.visuals().error_fg_color));
+                    } else {
+                        ui.label(text);
+                    }
+                }
+            });
+    }
+}
+
+fn help_button(ui: &mut egui::Ui) {
+    ui.menu_button("API", |ui| {
+        ui.set_max_width(420.0);
+        for (signature, description) in [
+            ("request.url / method / body", "read and change the request"),
+            ("request.header(name)", "header value, () if unset"),
+            ("request.set_header(name, value)", ""),
+            ("request.remove_header(name)", ""),
+            ("request.query(name) / set_query / remove_query", ""),
+            (
+                "response.status / status_text / body / time",
+                "post-response only",
+            ),
+            ("response.header(name)", ""),
+            ("response.json()", "body parsed as JSON"),
+            (
+                "vars.get(name) / vars.set(name, value)",
+                "environment variables",
+            ),
+            ("vars.interpolate(text)", "substitutes {{name}} variables"),
+            ("print(value)", "writes to the console"),
+            ("unix_time() / unix_time_ms()", ""),
+            ("uuid() / random_hex(bytes)", ""),
+            ("sha256(text) / hmac_sha256(key, text)", "hex encoded"),
+            ("hmac_sha256_base64(key, text)", ""),
+            ("base64_encode(text) / base64_decode(text)", ""),
+        ] {
+            ui.horizontal_wrapped(|ui| {
+                ui.monospace(signature);
+                if !description.is_empty() {
+                    ui.weak(description);
+                }
+            });
+        }
+    });
+}
@@ -0,0 +1,139 @@
+use egui::{RichText, TextStyle};
+
+use crate::{
+    components::response_stats_view::format_size,
+    stream::{ResponseStream, ServerEvent, StreamStatus},
+};
+
+/// Most of a body shown while it streams in, the whole of it shows once it's done
+const MAX_LIVE_BYTES: usize = 256 * 1024;
+
+/// Events of a `text/event-stream` response, or its body as it arrives
+#[derive(Default)]
+pub struct StreamView {
+    /// Whether the body shows instead of the events
+    body: bool,
+}
+
+impl StreamView {
+    /// While the request is sent or its body read
+    pub fn show_live(&mut self, ui: &mut egui::Ui, stream: &ResponseStream) {
+        let state = stream.lock();
+        let mut cancel = false;
+        ui.horizontal(|ui| {
+            ui.spinner();
+            match state.status {
+                StreamStatus::Streaming if state.event_stream => {
+                    ui.label(format!(
+                        "Streaming, {} events in {}",
+                        state.events.len(),
+                        format_size(state.body.len())
+                    ));
+                }
+                StreamStatus::Streaming => {
+                    ui.label(format!("Streaming, {}", format_size(state.body.len())));
+                }
+                _ => {
+                    ui.label("Waiting for the response");
+                }
+            }
+            cancel = ui
+                .button("Cancel")
+                .on_hover_text("Stops the request, keeping what arrived so far")
+                .clicked();
+        });
+        // chunks arrive without any input to trigger a repaint
+        ui.ctx()
+            .request_repaint_after(std::time::Duration::from_millis(100));
+        ui.add_space(4.0);
+        ui.separator();
+
+        if state.event_stream {
+            show_events(ui, &state.events);
+        } else {
+            let start = state.body.len().saturating_sub(MAX_LIVE_BYTES);
+            let body = String::from_utf8_lossy(&state.body[start..]);
+            egui::ScrollArea::vertical()
+                .id_salt("stream.body")
+                .auto_shrink([false, false])
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    if start > 0 {
+                        ui.weak(format!("{} earlier not shown", format_size(start)));
+                    }
+                    ui.add(egui::Label::new(RichText::new(body).monospace()).wrap());
+                });
+        }
+        drop(state);
+        if cancel {
+            stream.cancel();
+        }
+    }
+
+    /// Notes a cancelled stream and lists the events of one that's over, returns true when
+    /// they're shown instead of the body
+    pub fn show_done(&mut self, ui: &mut egui::Ui, stream: &ResponseStream) -> bool {
+        let state = stream.lock();
+        if state.status == StreamStatus::Cancelled {
+            ui.weak(format!(
+                "Cancelled after {}, the body is what arrived until then",
+                format_size(state.body.len())
+            ));
+        }
+        let done = matches!(state.status, StreamStatus::Done | StreamStatus::Cancelled);
+        if !done || !state.event_stream {
+            return false;
+        }
+        ui.horizontal(|ui| {
+            let events = format!("Events ({})", state.events.len());
+            ui.selectable_value(&mut self.body, false, events);
+            ui.selectable_value(&mut self.body, true, "Body");
+        });
+        if self.body {
+            return false;
+        }
+        ui.add_space(4.0);
+        ui.separator();
+        show_events(ui, &state.events);
+        true
+    }
+}
+
+fn show_events(ui: &mut egui::Ui, events: &[ServerEvent]) {
+    if events.is_empty() {
+        ui.weak("No events yet");
+        return;
+    }
+    let row_height = ui.text_style_height(&TextStyle::Monospace);
+    egui::ScrollArea::both()
+        .id_salt("stream.events")
+        .auto_shrink([false, false])
+        .stick_to_bottom(true)
+        .show(ui, |ui| {
+            egui::Grid::new("stream.events.grid")
+                .num_columns(5)
+                .spacing(egui::Vec2::new(12.0, 4.0))
+                .min_row_height(row_height)
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong("Time");
+                    ui.strong("Id");
+                    ui.strong("Event");
+                    ui.strong("Data");
+                    ui.strong("Retry");
+                    ui.end_row();
+                    for event in events {
+                        ui.weak(format!("{:.3} s", event.at.as_secs_f64()))
+                            .on_hover_text("Since the response headers arrived");
+                        ui.monospace(event.id.as_deref().unwrap_or(""));
+                        ui.monospace(&event.event);
+                        ui.add(egui::Label::new(RichText::new(&event.data).monospace()).wrap());
+                        match event.retry {
+                            Some(retry) => ui.monospace(format!("{retry} ms")),
+                            None => ui.label(""),
+                        };
+                        ui.end_row();
+                    }
+                });
+        });
+}
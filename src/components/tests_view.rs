@@ -0,0 +1,139 @@
+use std::sync::Mutex;
+
+use egui::{Color32, RichText};
+
+use crate::assertions::{Assertion, AssertionResult, Check};
+
+pub const PASSED_COLOR: Color32 = Color32::from_rgb(80, 200, 120);
+
+pub fn show(
+    ui: &mut egui::Ui,
+    assertions: &mut Vec<Assertion>,
+    results: &Mutex<Vec<AssertionResult>>,
+) {
+    show_results(ui, &results.lock().unwrap());
+    ui.separator();
+
+    ui.horizontal(|ui| {
+        ui.label("Assertions");
+        if ui.small_button("Add").clicked() {
+            assertions.push(Default::default());
+        }
+    });
+    ui.add_space(4.0);
+
+    let mut removed = None;
+    egui::ScrollArea::vertical()
+        .id_salt("tests.assertions")
+        .show(ui, |ui| {
+            egui::Grid::new("tests.assertions.grid")
+                .num_columns(4)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (index, assertion) in assertions.iter_mut().enumerate() {
+                        ui.checkbox(&mut assertion.enabled, "");
+                        show_kind(ui, index, &mut assertion.check);
+                        ui.horizontal(|ui| show_values(ui, &mut assertion.check));
+                        if ui
+                            .small_button("\u{e92e}")
+                            .on_hover_text("Delete")
+                            .clicked()
+                        {
+                            removed = Some(index);
+                        }
+                        ui.end_row();
+                    }
+                });
+            if assertions.is_empty() {
+                ui.weak("No assertions, add one to check the responses of this request");
+            }
+        });
+    if let Some(index) = removed {
+        assertions.remove(index);
+    }
+}
+
+/// Results of the last run, also used for each request of a folder run
+pub fn show_results(ui: &mut egui::Ui, results: &[AssertionResult]) {
+    if results.is_empty() {
+        ui.weak("Send the request to run its assertions");
+        return;
+    }
+
+    let passed = results.iter().filter(|result| result.passed).count();
+    let summary = format!("{passed} of {} passed", results.len());
+    if passed == results.len() {
+        ui.label(RichText::new(summary).strong());
+    } else {
+        ui.label(RichText::new(summary).color(ui.visuals().error_fg_color));
+    }
+    for result in results {
+        ui.horizontal_wrapped(|ui| {
+            if result.passed {
+                ui.label(RichText::new("\u{e5ca}").color(PASSED_COLOR));
+            } else {
+                ui.label(RichText::new("\u{e5cd}").color(ui.visuals().error_fg_color));
+            }
+            ui.label(&result.description);
+            if !result.message.is_empty() {
+                ui.weak(&result.message);
+            }
+        });
+    }
+}
+
+fn show_kind(ui: &mut egui::Ui, index: usize, check: &mut Check) {
+    egui::ComboBox::from_id_salt(("tests.kind", index))
+        .selected_text(check.kind_name())
+        .width(160.0)
+        .show_ui(ui, |ui| {
+            for kind in Check::kinds() {
+                let selected = check.same_kind(&kind);
+                if ui.selectable_label(selected, kind.kind_name()).clicked() && !selected {
+                    *check = kind;
+                }
+            }
+        });
+}
+
+fn show_values(ui: &mut egui::Ui, check: &mut Check) {
+    match check {
+        Check::StatusEquals(status) => {
+            ui.add(egui::DragValue::new(status).range(100..=599));
+        }
+        Check::StatusInRange { min, max } => {
+            ui.add(egui::DragValue::new(min).range(100..=599));
+            ui.label("to");
+            ui.add(egui::DragValue::new(max).range(100..=599));
+        }
+        Check::HeaderPresent { name } => {
+            text_field(ui, name, "Content-Type");
+        }
+        Check::HeaderMatches { name, pattern } => {
+            text_field(ui, name, "Content-Type");
+            text_field(ui, pattern, "^application/json");
+        }
+        Check::JsonPathEquals { path, expected } => {
+            text_field(ui, path, "$.items[0].id");
+            text_field(ui, expected, "42, \"text\", true or null");
+        }
+        Check::BodyContains(text) => {
+            text_field(ui, text, "text");
+        }
+        Check::BodyMatches(pattern) => {
+            text_field(ui, pattern, "regular expression");
+        }
+        Check::ResponseTimeUnder(millis) => {
+            ui.add(egui::DragValue::new(millis).speed(10).suffix(" ms"));
+        }
+    }
+}
+
+fn text_field(ui: &mut egui::Ui, text: &mut String, hint: &str) {
+    ui.add(
+        egui::TextEdit::singleline(text)
+            .code_editor()
+            .hint_text(hint)
+            .desired_width(160.0),
+    );
+}
@@ -0,0 +1,295 @@
+use cookie::time::OffsetDateTime;
+use egui::{Color32, RichText, TextStyle};
+use web_time::UNIX_EPOCH;
+
+use crate::{
+    environment::Variables,
+    websocket::{
+        self, Direction, Event, Frame, MessageKind, MessageTemplate, Session, Status,
+        WebSocketOptions,
+    },
+};
+
+/// Composer and log of a WebSocket request's connection
+pub struct WebSocketView {
+    close_code: u16,
+    close_reason: String,
+    template_name: String,
+    /// Why the last message couldn't be sent
+    error: Option<String>,
+}
+
+impl Default for WebSocketView {
+    fn default() -> Self {
+        Self {
+            close_code: 1000,
+            close_reason: String::new(),
+            template_name: String::new(),
+            error: None,
+        }
+    }
+}
+
+impl WebSocketView {
+    /// Writing, sending and saving messages, and closing the connection
+    pub fn show_composer(
+        &mut self,
+        ui: &mut egui::Ui,
+        options: &mut WebSocketOptions,
+        session: &Session,
+        variables: &Variables,
+    ) {
+        let open = matches!(session.status(), Status::Open);
+        ui.horizontal(|ui| {
+            for kind in MessageKind::values() {
+                ui.selectable_value(&mut options.kind, kind, kind.to_string());
+            }
+        });
+        ui.add_space(4.0);
+        let hint = match options.kind {
+            MessageKind::Binary => "Hex bytes like 48 65 6c 6c 6f",
+            _ => "Message, {{variables}} are substituted",
+        };
+        ui.add(
+            egui::TextEdit::multiline(&mut options.message)
+                .code_editor()
+                .desired_width(ui.available_width())
+                .desired_rows(8)
+                .hint_text(hint),
+        );
+
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(open, egui::Button::new("Send"))
+                .on_disabled_hover_text("Connect first")
+                .clicked()
+            {
+                self.send(session, options.kind, &options.message, variables);
+            }
+            if ui
+                .add_enabled(open, egui::Button::new("Ping"))
+                .on_hover_text("The server answers with a pong")
+                .clicked()
+            {
+                self.error = session.send(Frame::Ping(vec![])).err();
+            }
+            ui.separator();
+            ui.label("Close Code");
+            ui.add(egui::DragValue::new(&mut self.close_code).range(1000..=4999))
+                .on_hover_text(websocket::close_code_name(self.close_code));
+            ui.add(
+                egui::TextEdit::singleline(&mut self.close_reason)
+                    .desired_width(160.0)
+                    .hint_text("Reason"),
+            );
+            if ui.add_enabled(open, egui::Button::new("Close")).clicked() {
+                let frame = Frame::Close {
+                    code: Some(self.close_code),
+                    reason: self.close_reason.clone(),
+                };
+                self.error = session.send(frame).err();
+            }
+        });
+        if let Some(err) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, err);
+        }
+        ui.add_space(8.0);
+        ui.separator();
+
+        ui.strong("Saved Messages");
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.template_name)
+                    .desired_width(200.0)
+                    .hint_text("Name"),
+            );
+            let name = self.template_name.trim();
+            if ui
+                .add_enabled(!name.is_empty(), egui::Button::new("Save"))
+                .on_hover_text("Saves the message above, replacing one with the same name")
+                .clicked()
+            {
+                let template = MessageTemplate {
+                    name: name.to_owned(),
+                    kind: options.kind,
+                    text: options.message.clone(),
+                };
+                match options
+                    .templates
+                    .iter_mut()
+                    .find(|saved| saved.name == name)
+                {
+                    Some(saved) => *saved = template,
+                    None => options.templates.push(template),
+                }
+            }
+        });
+        let mut removed = None;
+        let mut loaded = None;
+        egui::Grid::new("websocket.templates")
+            .num_columns(3)
+            .spacing(egui::Vec2::splat(6.0))
+            .striped(true)
+            .show(ui, |ui| {
+                for (index, template) in options.templates.iter().enumerate() {
+                    ui.label(&template.name);
+                    ui.weak(template.kind.to_string());
+                    ui.horizontal(|ui| {
+                        if ui.small_button("Load").clicked() {
+                            loaded = Some(index);
+                        }
+                        if ui
+                            .add_enabled(open, egui::Button::new("Send").small())
+                            .clicked()
+                        {
+                            self.send(session, template.kind, &template.text, variables);
+                        }
+                        if ui
+                            .small_button("\u{e5cd}")
+                            .on_hover_text("Remove")
+                            .clicked()
+                        {
+                            removed = Some(index);
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+        if let Some(template) = loaded.and_then(|index| options.templates.get(index)) {
+            options.kind = template.kind;
+            options.message = template.text.clone();
+            self.template_name = template.name.clone();
+        }
+        if let Some(index) = removed {
+            options.templates.remove(index);
+        }
+    }
+
+    fn send(&mut self, session: &Session, kind: MessageKind, text: &str, variables: &Variables) {
+        self.error = Frame::encode(kind, text, variables)
+            .and_then(|frame| session.send(frame))
+            .err();
+    }
+
+    /// The connection's status and everything that went over it
+    pub fn show_log(&mut self, ui: &mut egui::Ui, session: &Session) {
+        let status = session.status();
+        ui.horizontal(|ui| {
+            match &status {
+                Status::Idle => {
+                    ui.label("Not connected");
+                }
+                Status::Connecting => {
+                    ui.spinner();
+                    ui.label("Connecting");
+                }
+                Status::Open => {
+                    ui.colored_label(Color32::from_rgb(90, 180, 90), "Connected");
+                }
+                Status::Closing => {
+                    ui.spinner();
+                    ui.label("Closing");
+                }
+                Status::Closed { code, reason } => {
+                    let frame = Frame::Close {
+                        code: *code,
+                        reason: reason.clone(),
+                    };
+                    ui.label(format!("Closed, {}", frame.describe()));
+                }
+                Status::Failed(err) => {
+                    ui.colored_label(ui.visuals().error_fg_color, err);
+                }
+            }
+            if ui.button("Clear").clicked() {
+                session.lock().log.clear();
+            }
+        });
+        if status.is_active() {
+            // messages arrive without any input to trigger a repaint
+            ui.ctx()
+                .request_repaint_after(std::time::Duration::from_millis(100));
+        }
+        ui.add_space(4.0);
+        ui.separator();
+
+        let state = session.lock();
+        if state.log.is_empty() {
+            ui.weak("Nothing sent or received yet");
+            return;
+        }
+        let row_height = ui.text_style_height(&TextStyle::Monospace);
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                egui::Grid::new("websocket.log")
+                    .num_columns(4)
+                    .spacing(egui::Vec2::new(8.0, 4.0))
+                    .min_row_height(row_height)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for entry in &state.log {
+                            let time = time_of_day(entry.at);
+                            ui.weak(RichText::new(time).monospace())
+                                .on_hover_text("UTC");
+                            match &entry.event {
+                                Event::Opened => {
+                                    ui.label("");
+                                    ui.label("Open");
+                                    ui.weak("Connected");
+                                }
+                                Event::Frame(direction, frame) => {
+                                    match direction {
+                                        // arrow up and arrow down
+                                        Direction::Sent => ui.label("\u{e5d8}"),
+                                        Direction::Received => ui.label("\u{e5db}"),
+                                    }
+                                    .on_hover_text(
+                                        match direction {
+                                            Direction::Sent => "Sent",
+                                            Direction::Received => "Received",
+                                        },
+                                    );
+                                    ui.label(frame.name());
+                                    show_frame(ui, frame);
+                                }
+                                Event::Error(err) => {
+                                    ui.label("");
+                                    ui.label("Error");
+                                    ui.colored_label(ui.visuals().error_fg_color, err);
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+}
+
+fn show_frame(ui: &mut egui::Ui, frame: &Frame) {
+    let text = frame.describe();
+    match frame {
+        Frame::Text(_) | Frame::Binary(_) => {
+            ui.add(egui::Label::new(RichText::new(text).monospace()).wrap());
+        }
+        _ => {
+            ui.weak(RichText::new(text).monospace());
+        }
+    }
+}
+
+/// Like `14:03:27.125`
+fn time_of_day(at: web_time::SystemTime) -> String {
+    let since_epoch = at.duration_since(UNIX_EPOCH).unwrap_or_default();
+    match OffsetDateTime::from_unix_timestamp_nanos(since_epoch.as_nanos() as i128) {
+        Ok(time) => format!(
+            "{:02}:{:02}:{:02}.{:03}",
+            time.hour(),
+            time.minute(),
+            time.second(),
+            time.millisecond()
+        ),
+        Err(_) => String::new(),
+    }
+}
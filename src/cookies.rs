@@ -1,13 +1,149 @@
 use bytes::Bytes;
+use cookie::{
+    SameSite,
+    time::{OffsetDateTime, format_description::well_known::Rfc2822},
+};
+use cookie_store::{CookieDomain, CookieExpiration};
 use http::HeaderValue;
 use std::sync::Mutex;
 
 pub struct BasicCookieStore(Mutex<cookie_store::CookieStore>);
 
+/// A stored cookie in a shape that's easy to show and edit
+#[derive(Clone, Default, PartialEq)]
+pub struct CookieEntry {
+    pub name: String,
+    pub value: String,
+    pub domain: String,
+    /// Whether subdomains get the cookie too, i.e. it came with a Domain attribute
+    pub include_subdomains: bool,
+    pub path: String,
+    /// RFC 2822 date in UTC, empty for cookies that end with the session
+    pub expires: String,
+    pub secure: bool,
+    pub http_only: bool,
+    pub same_site: Option<SameSite>,
+}
+
+impl CookieEntry {
+    fn from_stored(cookie: &cookie_store::Cookie<'static>) -> Self {
+        let (domain, include_subdomains) = match &cookie.domain {
+            CookieDomain::HostOnly(domain) => (domain.clone(), false),
+            CookieDomain::Suffix(domain) => (domain.clone(), true),
+            CookieDomain::NotPresent | CookieDomain::Empty => (String::new(), false),
+        };
+        let expires = match &cookie.expires {
+            CookieExpiration::AtUtc(at) => at.format(&Rfc2822).unwrap_or_default(),
+            CookieExpiration::SessionEnd => String::new(),
+        };
+        Self {
+            name: cookie.name().to_owned(),
+            value: cookie.value().to_owned(),
+            domain,
+            include_subdomains,
+            path: String::from(&cookie.path),
+            expires,
+            secure: cookie.secure().unwrap_or(false),
+            http_only: cookie.http_only().unwrap_or(false),
+            same_site: cookie.same_site(),
+        }
+    }
+
+    fn to_raw(&self) -> Result<(cookie::Cookie<'static>, url::Url), String> {
+        let domain = self.domain.trim().trim_start_matches('.');
+        if self.name.trim().is_empty() {
+            return Err("name is required".to_owned());
+        }
+        if domain.is_empty() {
+            return Err("domain is required".to_owned());
+        }
+        let path = match self.path.trim() {
+            "" => "/",
+            path => path,
+        };
+
+        let mut builder = cookie::Cookie::build((self.name.trim().to_owned(), self.value.clone()))
+            .path(path.to_owned())
+            .secure(self.secure)
+            .http_only(self.http_only);
+        if self.include_subdomains {
+            builder = builder.domain(domain.to_owned());
+        }
+        if let Some(same_site) = self.same_site {
+            builder = builder.same_site(same_site);
+        }
+        if !self.expires.trim().is_empty() {
+            let expires = OffsetDateTime::parse(self.expires.trim(), &Rfc2822).map_err(|err| {
+                format!("expires: {err}, expected e.g. Fri, 01 Jan 2027 00:00:00 +0000")
+            })?;
+            builder = builder.expires(expires);
+        }
+
+        // the store checks cookies against the url they came from
+        let url = url::Url::parse(&format!("https://{domain}{path}"))
+            .map_err(|err| format!("domain: {err}"))?;
+        Ok((builder.build(), url))
+    }
+}
+
 impl BasicCookieStore {
     pub fn new() -> Self {
         Self(Mutex::new(cookie_store::CookieStore::new()))
     }
+
+    /// Persistent cookies saved with `to_json`, starts empty if they can't be read
+    pub fn from_json(json: &str) -> Self {
+        let store = cookie_store::serde::json::load(json.as_bytes()).unwrap_or_else(|err| {
+            log::warn!("failed to load cookies {err}");
+            cookie_store::CookieStore::new()
+        });
+        Self(Mutex::new(store))
+    }
+
+    /// Cookies that outlive the session, as JSON
+    pub fn to_json(&self) -> String {
+        let mut json = vec![];
+        let store = self.0.lock().unwrap();
+        if let Err(err) = cookie_store::serde::json::save(&store, &mut json) {
+            log::warn!("failed to save cookies {err}");
+        }
+        String::from_utf8(json).unwrap_or_default()
+    }
+
+    /// Unexpired cookies, sorted by domain, path and name
+    pub fn entries(&self) -> Vec<CookieEntry> {
+        let store = self.0.lock().unwrap();
+        let mut entries = store
+            .iter_unexpired()
+            .map(CookieEntry::from_stored)
+            .collect::<Vec<_>>();
+        entries.sort_by(|a, b| (&a.domain, &a.path, &a.name).cmp(&(&b.domain, &b.path, &b.name)));
+        entries
+    }
+
+    /// Adds the cookie, replacing one with the same domain, path and name
+    pub fn insert(&self, entry: &CookieEntry) -> Result<(), String> {
+        let (cookie, url) = entry.to_raw()?;
+        let mut store = self.0.lock().unwrap();
+        store
+            .insert_raw(&cookie, &url)
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    }
+
+    pub fn remove(&self, entry: &CookieEntry) {
+        let path = if entry.path.is_empty() {
+            "/"
+        } else {
+            &entry.path
+        };
+        let mut store = self.0.lock().unwrap();
+        store.remove(&entry.domain, path, &entry.name);
+    }
+
+    pub fn clear(&self) {
+        self.0.lock().unwrap().clear();
+    }
 }
 
 impl reqwest::cookie::CookieStore for BasicCookieStore {
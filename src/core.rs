@@ -1,3 +1,4 @@
+#[cfg(feature = "gui")]
 pub trait View {
     fn view(&mut self, ui: &mut egui::Ui);
 }
@@ -12,8 +13,16 @@ use std::{
 use http::{HeaderValue, Method, Uri, request, uri::PathAndQuery};
 
 use crate::{
+    assertions::{Assertion, AssertionResult},
     auth::{RequestAuth, RequestAuthType},
-    http::{HttpError, HttpMethod, HttpResponse},
+    body::BodyOptions,
+    client::ClientSettings,
+    grpc::GrpcOptions,
+    http::{HttpError, HttpMethod, HttpResult},
+    import::ImportOrigin,
+    script::{ScriptOutput, Scripts},
+    stream::ResponseStream,
+    websocket::{self, WebSocketOptions},
 };
 
 static ID_COUNTER: AtomicUsize = AtomicUsize::new(1);
@@ -22,6 +31,11 @@ pub fn get_new_id() -> usize {
     ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::AcqRel)
 }
 
+/// Makes sure ids handed out from now on don't collide with ones restored from storage
+pub fn reserve_ids_up_to(id: usize) {
+    ID_COUNTER.fetch_max(id + 1, std::sync::atomic::Ordering::AcqRel);
+}
+
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
 pub struct RequestId(pub usize);
 
@@ -31,23 +45,91 @@ impl RequestId {
     }
 }
 
-#[derive(serde::Deserialize, serde::Serialize)]
+/// What the request is, the method only applies to HTTP ones
+#[derive(Clone, Copy, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum RequestKind {
+    #[default]
+    Http,
+    /// A connection kept open to exchange messages, see `websocket::Session`
+    WebSocket,
+    /// A call of a method described by `grpc::GrpcOptions`
+    Grpc,
+}
+
+/// Clones share the response, script output and test results with the original,
+/// so whatever sends a clone updates what's shown for the request
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
 pub struct RequestState {
+    #[serde(default)]
+    pub name: String,
     pub url: String,
+    #[serde(default)]
+    pub kind: RequestKind,
     pub method: HttpMethod,
     pub query: Vec<Param>,
     pub headers: Vec<Param>,
+    /// Text of raw and JSON bodies, the GraphQL query, or the gRPC message as JSON
     pub body: String,
+    #[serde(default)]
+    pub body_options: BodyOptions,
     pub auth: RequestAuth,
-    pub response: Arc<Mutex<Option<HttpResponse>>>,
+    pub response: Arc<Mutex<Option<HttpResult>>>,
+    /// The response's body while it's read
+    #[serde(skip)]
+    pub stream: ResponseStream,
+    /// Set on requests generated from a spec, so re-imports can update them
+    #[serde(default)]
+    pub origin: Option<Box<ImportOrigin>>,
+    /// Replaces the global client settings for this request
+    #[serde(default)]
+    pub settings: Option<ClientSettings>,
+    #[serde(default)]
+    pub scripts: Scripts,
+    #[serde(skip)]
+    pub script_output: Arc<Mutex<ScriptOutput>>,
+    #[serde(default)]
+    pub assertions: Vec<Assertion>,
+    /// Results of the assertions against the last response
+    #[serde(skip)]
+    pub test_results: Arc<Mutex<Vec<AssertionResult>>>,
+    /// Messages of a WebSocket request
+    #[serde(default)]
+    pub websocket: WebSocketOptions,
+    #[serde(skip)]
+    pub session: websocket::Session,
+    /// What a gRPC request calls
+    #[serde(default)]
+    pub grpc: GrpcOptions,
+}
+
+impl RequestState {
+    /// Name shown in the collections tree, falls back to the method and url
+    pub fn label(&self) -> String {
+        if !self.name.is_empty() {
+            return self.name.clone();
+        }
+        let url = if self.url.is_empty() {
+            "<empty>"
+        } else {
+            &self.url
+        };
+        match self.kind {
+            RequestKind::Http => format!("{} {}", self.method, url),
+            RequestKind::WebSocket => format!("WS {url}"),
+            RequestKind::Grpc => format!("gRPC {url}"),
+        }
+    }
 }
 
 impl Default for RequestState {
     fn default() -> Self {
         RequestState {
+            name: String::new(),
             // url: String::new(),
             url: "http://httpbin.org/get".to_owned(),
             body: String::new(),
+            body_options: Default::default(),
+            kind: RequestKind::Http,
             method: HttpMethod::Get,
             // query: vec![Default::default()],
             query: vec![
@@ -55,30 +137,46 @@ impl Default for RequestState {
                     enabled: true,
                     key: "aaa".into(),
                     value: "value 1".into(),
+                    file: false,
                 },
                 Param {
                     enabled: true,
                     key: "bbb".into(),
                     value: "value 2".into(),
+                    file: false,
                 },
                 Param {
                     enabled: true,
                     key: "ccc".into(),
                     value: "value 3".into(),
+                    file: false,
                 },
             ],
             headers: vec![Default::default()],
             auth: Default::default(),
             response: Arc::new(Mutex::new(None)),
+            stream: Default::default(),
+            origin: None,
+            settings: None,
+            scripts: Default::default(),
+            script_output: Default::default(),
+            assertions: vec![],
+            test_results: Default::default(),
+            websocket: Default::default(),
+            session: Default::default(),
+            grpc: Default::default(),
         }
     }
 }
 
-#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct Param {
     pub enabled: bool,
     pub key: String,
     pub value: String,
+    /// Multipart parts only, the value is the path of a file to send
+    #[serde(default)]
+    pub file: bool,
 }
 
 impl Default for Param {
@@ -87,6 +185,7 @@ impl Default for Param {
             enabled: true,
             key: Default::default(),
             value: Default::default(),
+            file: false,
         }
     }
 }
@@ -97,6 +196,7 @@ impl Param {
             enabled: true,
             key,
             value,
+            file: false,
         }
     }
 }
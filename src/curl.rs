@@ -0,0 +1,522 @@
+use crate::{
+    auth::{RequestAuth, SigV4Params},
+    body::BodySource,
+    core::{Param, RequestState},
+    http::HttpMethod,
+};
+
+#[derive(Debug)]
+pub enum CurlError {
+    NotCurl,
+    UnterminatedQuote,
+    MissingValue(String),
+    MissingUrl,
+    UnsupportedMethod(String),
+}
+
+impl std::fmt::Display for CurlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotCurl => write!(f, "not a curl command"),
+            Self::UnterminatedQuote => write!(f, "unterminated quote"),
+            Self::MissingValue(option) => write!(f, "option {option} is missing its value"),
+            Self::MissingUrl => write!(f, "no url found"),
+            Self::UnsupportedMethod(method) => write!(f, "method {method} is not supported"),
+        }
+    }
+}
+
+impl std::error::Error for CurlError {}
+
+pub fn is_curl_command(input: &str) -> bool {
+    let input = input.trim_start();
+    input.starts_with("curl ") || input.starts_with("curl\t")
+}
+
+/// Parses a curl command line, as copied from docs or browser dev tools, into a request
+pub fn parse(input: &str) -> Result<RequestState, CurlError> {
+    let args = split_args(input)?;
+    let mut args = args.into_iter();
+    if args.next().as_deref() != Some("curl") {
+        return Err(CurlError::NotCurl);
+    }
+
+    let mut url = None;
+    let mut method = None;
+    let mut headers = vec![];
+    let mut data: Vec<String> = vec![];
+    let mut data_as_query = false;
+    let mut user = None;
+    let mut aws_sigv4 = None;
+    let mut bearer = None;
+
+    while let Some(arg) = args.next() {
+        let (option, attached) = split_option(&arg);
+        let mut value = |option: &str| match &attached {
+            Some(value) => Ok(value.clone()),
+            None => args
+                .next()
+                .ok_or_else(|| CurlError::MissingValue(option.to_owned())),
+        };
+
+        match option.as_str() {
+            "-X" | "--request" => method = Some(value(&option)?),
+            "-H" | "--header" => {
+                let header = value(&option)?;
+                if let Some((key, value)) = header.split_once(':') {
+                    headers.push(Param::enabled(
+                        key.trim().to_owned(),
+                        value.trim().to_owned(),
+                    ));
+                }
+            }
+            "-d" | "--data" | "--data-raw" | "--data-binary" | "--data-ascii" => {
+                data.push(value(&option)?)
+            }
+            "--data-urlencode" => data.push(urlencode_data(&value(&option)?)),
+            "--json" => {
+                data.push(value(&option)?);
+                set_default_header(&mut headers, "Content-Type", "application/json");
+                set_default_header(&mut headers, "Accept", "application/json");
+            }
+            "-G" | "--get" => data_as_query = true,
+            "-I" | "--head" => method = Some("HEAD".to_owned()),
+            "-u" | "--user" => user = Some(value(&option)?),
+            "--aws-sigv4" => aws_sigv4 = Some(value(&option)?),
+            "--oauth2-bearer" => bearer = Some(value(&option)?),
+            "-A" | "--user-agent" => {
+                headers.push(Param::enabled("User-Agent".to_owned(), value(&option)?))
+            }
+            "-e" | "--referer" => {
+                headers.push(Param::enabled("Referer".to_owned(), value(&option)?))
+            }
+            "-b" | "--cookie" => {
+                // without a '=' the value is a cookie file, which we can't read
+                let cookie = value(&option)?;
+                if cookie.contains('=') {
+                    headers.push(Param::enabled("Cookie".to_owned(), cookie));
+                }
+            }
+            "--url" => url = Some(value(&option)?),
+            option if takes_value(option) => {
+                let ignored = value(option)?;
+                log::warn!("ignoring curl option {option} {ignored}");
+            }
+            option if option.starts_with('-') && option.len() > 1 => {
+                log::warn!("ignoring curl option {option}");
+            }
+            _ => url = Some(arg),
+        }
+    }
+
+    let url = url.ok_or(CurlError::MissingUrl)?;
+    let url = if url.contains("://") {
+        url
+    } else {
+        format!("http://{url}")
+    };
+    let (url, mut query) = split_url(&url);
+
+    let mut body = String::new();
+    if !data.is_empty() {
+        let data = data.join("&");
+        if data_as_query {
+            query.extend(parse_query(&data));
+        } else {
+            body = data;
+            set_default_header(
+                &mut headers,
+                "Content-Type",
+                "application/x-www-form-urlencoded",
+            );
+        }
+    }
+
+    let method = match method {
+        Some(method) => {
+            HttpMethod::from_name(&method).ok_or(CurlError::UnsupportedMethod(method))?
+        }
+        None if !body.is_empty() => HttpMethod::Post,
+        None => HttpMethod::Get,
+    };
+
+    let auth = match (aws_sigv4, user, bearer) {
+        (Some(provider), user, _) => {
+            RequestAuth::AwsSigV4(sigv4_params(&provider, user.as_deref(), &mut headers))
+        }
+        (None, Some(user), _) => {
+            let (username, password) = user.split_once(':').unwrap_or((&user, ""));
+            RequestAuth::BasicAuth {
+                username: username.to_owned(),
+                password: password.to_owned(),
+            }
+        }
+        (None, None, Some(token)) => RequestAuth::Bearer { token },
+        (None, None, None) => RequestAuth::None,
+    };
+
+    Ok(RequestState {
+        url,
+        method,
+        query,
+        headers,
+        body,
+        auth,
+        ..Default::default()
+    })
+}
+
+/// Renders the request exactly as it'll be sent, expects auth to be applied already. Files
+/// of the body are passed by path
+pub fn to_command(request: &http::Request<Vec<u8>>, body: &BodySource) -> String {
+    let method = request.method();
+    let method = if method == http::Method::HEAD {
+        " --head".to_owned()
+    } else if method != http::Method::GET || !request.body().is_empty() {
+        format!(" -X {method}")
+    } else {
+        String::new()
+    };
+    let mut parts = vec![format!(
+        "curl{method} {}",
+        quote(&request.uri().to_string())
+    )];
+
+    for (name, value) in request.headers() {
+        // curl picks the multipart boundary, ours wouldn't match it
+        if name == http::header::CONTENT_TYPE && matches!(body, BodySource::Multipart(_)) {
+            continue;
+        }
+        let value = String::from_utf8_lossy(value.as_bytes());
+        parts.push(format!("-H {}", quote(&format!("{name}: {value}"))));
+    }
+
+    match body {
+        BodySource::Text(text) if !text.is_empty() => {
+            parts.push(format!("--data-raw {}", quote(text)))
+        }
+        BodySource::Text(_) => {}
+        BodySource::File(path) => {
+            parts.push(format!("--data-binary {}", quote(&format!("@{path}"))))
+        }
+        BodySource::Multipart(form) => {
+            for part in form {
+                match part.file {
+                    true => parts.push(format!(
+                        "-F {}",
+                        quote(&format!("{}=@{}", part.name, part.value))
+                    )),
+                    // taken literally, -F would read a value starting with @ or < as a file
+                    false => parts.push(format!(
+                        "--form-string {}",
+                        quote(&format!("{}={}", part.name, part.value))
+                    )),
+                }
+            }
+        }
+    }
+
+    parts.join(" \\\n  ")
+}
+
+/// Quotes for a POSIX shell, single quotes can't be escaped inside single quotes so they're
+/// closed, escaped and reopened
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Splits `-XPOST` into `-X` and `POST`, and `--data=x` into `--data` and `x`
+fn split_option(arg: &str) -> (String, Option<String>) {
+    if let Some(long) = arg.strip_prefix("--") {
+        return match long.split_once('=') {
+            Some((name, value)) if !name.is_empty() => {
+                (format!("--{name}"), Some(value.to_owned()))
+            }
+            _ => (arg.to_owned(), None),
+        };
+    }
+    if arg.starts_with('-') && arg.len() > 2 {
+        let (option, rest) = arg.split_at(2);
+        if takes_value(option) || matches!(option, "-X" | "-H" | "-d" | "-u" | "-A" | "-e" | "-b") {
+            return (option.to_owned(), Some(rest.to_owned()));
+        }
+    }
+    (arg.to_owned(), None)
+}
+
+/// Options we don't map but have to skip the value of
+fn takes_value(option: &str) -> bool {
+    matches!(
+        option,
+        "-o" | "--output"
+            | "-m"
+            | "--max-time"
+            | "--connect-timeout"
+            | "-x"
+            | "--proxy"
+            | "-U"
+            | "--proxy-user"
+            | "--cacert"
+            | "--capath"
+            | "-E"
+            | "--cert"
+            | "--key"
+            | "-w"
+            | "--write-out"
+            | "--retry"
+            | "-T"
+            | "--upload-file"
+            | "-F"
+            | "--form"
+            | "-c"
+            | "--cookie-jar"
+            | "-r"
+            | "--range"
+            | "--resolve"
+            | "--max-redirs"
+            | "--limit-rate"
+    )
+}
+
+fn split_url(url: &str) -> (String, Vec<Param>) {
+    let url = url.split_once('#').map_or(url, |(url, _)| url);
+    match url.split_once('?') {
+        Some((url, query)) => (url.to_owned(), parse_query(query)),
+        None => (url.to_owned(), vec![]),
+    }
+}
+
+fn parse_query(query: &str) -> Vec<Param> {
+    url::form_urlencoded::parse(query.as_bytes())
+        .map(|(key, value)| Param::enabled(key.into_owned(), value.into_owned()))
+        .collect()
+}
+
+/// `--data-urlencode` takes `content`, `name=content` or `=content` and only encodes the content
+fn urlencode_data(data: &str) -> String {
+    let encode =
+        |value: &str| url::form_urlencoded::byte_serialize(value.as_bytes()).collect::<String>();
+    match data.split_once('=') {
+        Some(("", content)) => encode(content),
+        Some((name, content)) => format!("{name}={}", encode(content)),
+        None => encode(data),
+    }
+}
+
+/// `--aws-sigv4 "aws:amz:region:service"` with the keys given through `-u access:secret`
+fn sigv4_params(provider: &str, user: Option<&str>, headers: &mut Vec<Param>) -> SigV4Params {
+    let mut provider = provider.split(':').skip(2);
+    let region = provider.next().unwrap_or_default().to_owned();
+    let service = provider.next().unwrap_or_default().to_owned();
+    let (access_key, secret_key) = user
+        .and_then(|user| user.split_once(':'))
+        .unwrap_or((user.unwrap_or_default(), ""));
+
+    // the session token is passed as a plain header to curl, it belongs to the auth here
+    let session_token = headers
+        .iter()
+        .position(|h| h.key.eq_ignore_ascii_case("x-amz-security-token"))
+        .map(|index| headers.remove(index).value)
+        .unwrap_or_default();
+
+    SigV4Params {
+        access_key: access_key.to_owned(),
+        secret_key: secret_key.to_owned(),
+        session_token,
+        service,
+        region,
+        ..Default::default()
+    }
+}
+
+fn set_default_header(headers: &mut Vec<Param>, key: &str, value: &str) {
+    if !headers.iter().any(|h| h.key.eq_ignore_ascii_case(key)) {
+        headers.push(Param::enabled(key.to_owned(), value.to_owned()));
+    }
+}
+
+/// Splits a command line into arguments following POSIX shell quoting rules, including
+/// `$'...'` strings that browsers use when copying requests as curl
+fn split_args(input: &str) -> Result<Vec<String>, CurlError> {
+    let mut args = vec![];
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut chars = input.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\'' => {
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(ch) => current.push(ch),
+                        None => return Err(CurlError::UnterminatedQuote),
+                    }
+                }
+            }
+            '"' => {
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(ch @ ('"' | '\\' | '$' | '`')) => current.push(ch),
+                            Some('\n') => {}
+                            Some(ch) => {
+                                current.push('\\');
+                                current.push(ch);
+                            }
+                            None => return Err(CurlError::UnterminatedQuote),
+                        },
+                        Some(ch) => current.push(ch),
+                        None => return Err(CurlError::UnterminatedQuote),
+                    }
+                }
+            }
+            '$' if chars.peek() == Some(&'\'') => {
+                chars.next();
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => current.push('\n'),
+                            Some('t') => current.push('\t'),
+                            Some('r') => current.push('\r'),
+                            Some(ch) => current.push(ch),
+                            None => return Err(CurlError::UnterminatedQuote),
+                        },
+                        Some(ch) => current.push(ch),
+                        None => return Err(CurlError::UnterminatedQuote),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                // line continuation
+                Some('\n') => {}
+                Some('\r') if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                }
+                Some(ch) => {
+                    in_arg = true;
+                    current.push(ch);
+                }
+                None => {}
+            },
+            ch if ch.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            ch => {
+                in_arg = true;
+                current.push(ch);
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(key: &str, value: &str) -> Param {
+        Param::enabled(key.to_owned(), value.to_owned())
+    }
+
+    #[test]
+    fn splits_quoted_args() {
+        let args = split_args(r#"curl 'a b' "c \"d\" \$e" f\ g $'h\ni' 'it'\''s'"#).unwrap();
+        assert_eq!(args, ["curl", "a b", r#"c "d" $e"#, "f g", "h\ni", "it's"]);
+        assert!(matches!(
+            split_args("curl 'open"),
+            Err(CurlError::UnterminatedQuote)
+        ));
+        assert!(matches!(
+            split_args(r#"curl "open"#),
+            Err(CurlError::UnterminatedQuote)
+        ));
+    }
+
+    #[test]
+    fn joins_continued_lines() {
+        let lf = split_args("curl \\\n  -X POST \\\n  example.com").unwrap();
+        let crlf = split_args("curl \\\r\n  -X POST \\\r\n  example.com").unwrap();
+        assert_eq!(lf, ["curl", "-X", "POST", "example.com"]);
+        assert_eq!(crlf, lf);
+    }
+
+    #[test]
+    fn parses_options() {
+        let request = parse(
+            "curl -X PUT 'https://example.com/items?id=1' -H 'Accept: application/json' \
+             -H 'X-Trace:abc' --data-raw '{\"a\":1}' -u user:secret --compressed",
+        )
+        .unwrap();
+        assert_eq!(request.url, "https://example.com/items");
+        assert_eq!(request.method, HttpMethod::Put);
+        assert_eq!(request.query, [header("id", "1")]);
+        assert_eq!(
+            request.headers,
+            [
+                header("Accept", "application/json"),
+                header("X-Trace", "abc"),
+                header("Content-Type", "application/x-www-form-urlencoded"),
+            ]
+        );
+        assert_eq!(request.body, r#"{"a":1}"#);
+        assert!(matches!(
+            request.auth,
+            RequestAuth::BasicAuth { username, password } if username == "user" && password == "secret"
+        ));
+    }
+
+    #[test]
+    fn data_defaults_to_post() {
+        let request = parse("curl example.com -d a=1 -d b=2").unwrap();
+        assert_eq!(request.url, "http://example.com");
+        assert_eq!(request.method, HttpMethod::Post);
+        assert_eq!(request.body, "a=1&b=2");
+    }
+
+    #[test]
+    fn parses_combined_short_flags() {
+        let request = parse("curl -sL -XDELETE -HAccept:text/plain -dx=1 example.com").unwrap();
+        assert_eq!(request.method, HttpMethod::Delete);
+        assert_eq!(request.headers[0], header("Accept", "text/plain"));
+        assert_eq!(request.body, "x=1");
+        assert_eq!(request.url, "http://example.com");
+    }
+
+    #[test]
+    fn round_trips() {
+        let body = r#"{"name": "it's"}"#;
+        let request = http::Request::builder()
+            .method("PATCH")
+            .uri("https://example.com/items/1?full=true")
+            .header("Content-Type", "application/json")
+            .header("Authorization", "Bearer token")
+            .body(body.as_bytes().to_vec())
+            .unwrap();
+        let command = to_command(&request, &BodySource::Text(body.to_owned()));
+
+        let parsed = parse(&command).unwrap();
+        assert_eq!(parsed.url, "https://example.com/items/1");
+        assert_eq!(parsed.method, HttpMethod::Patch);
+        assert_eq!(parsed.query, [header("full", "true")]);
+        assert_eq!(
+            parsed.headers,
+            [
+                header("content-type", "application/json"),
+                header("authorization", "Bearer token"),
+            ]
+        );
+        assert_eq!(parsed.body, body);
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+
+use crate::core::Param;
+
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+pub struct Environment {
+    pub name: String,
+    pub variables: Vec<Param>,
+}
+
+impl Environment {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            variables: vec![Default::default()],
+        }
+    }
+}
+
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+pub struct Environments {
+    pub items: Vec<Environment>,
+    pub active: Option<usize>,
+}
+
+impl Environments {
+    pub fn active(&self) -> Option<&Environment> {
+        self.active.and_then(|index| self.items.get(index))
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index >= self.items.len() {
+            return;
+        }
+        self.items.remove(index);
+        self.active = match self.active {
+            Some(active) if active == index => None,
+            Some(active) if active > index => Some(active - 1),
+            active => active,
+        };
+    }
+
+    /// Sets a variable of the active environment, adding it if needed.
+    /// Returns false if no environment is selected
+    pub fn set_variable(&mut self, key: &str, value: &str) -> bool {
+        let Some(environment) = self.active.and_then(|index| self.items.get_mut(index)) else {
+            return false;
+        };
+        match environment
+            .variables
+            .iter_mut()
+            .find(|v| v.key.trim() == key)
+        {
+            Some(variable) => {
+                variable.value = value.to_owned();
+                variable.enabled = true;
+            }
+            None => environment
+                .variables
+                .push(Param::enabled(key.to_owned(), value.to_owned())),
+        }
+        true
+    }
+
+    /// Variables of the active environment, empty if none is selected
+    pub fn variables(&self) -> Variables {
+        self.active()
+            .map(|env| Variables::from_params(&env.variables))
+            .unwrap_or_default()
+    }
+}
+
+/// Resolved set of variables used for `{{name}}` substitution
+#[derive(Default, Clone, Debug)]
+pub struct Variables(HashMap<String, String>);
+
+impl Variables {
+    pub fn from_params(params: &[Param]) -> Self {
+        Self(
+            params
+                .iter()
+                .filter(|p| p.enabled && !p.key.is_empty())
+                .map(|p| (p.key.trim().to_owned(), p.value.clone()))
+                .collect(),
+        )
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(String::as_str)
+    }
+
+    pub fn set(&mut self, name: &str, value: &str) {
+        self.0.insert(name.to_owned(), value.to_owned());
+    }
+
+    /// Replaces every `{{name}}` with its value, unknown variables are left untouched
+    /// so they stay visible in the sent request instead of silently becoming empty
+    pub fn interpolate(&self, input: &str) -> String {
+        let mut output = String::with_capacity(input.len());
+        let mut rest = input;
+
+        while let Some(start) = rest.find("{{") {
+            let (before, after_open) = rest.split_at(start);
+            output.push_str(before);
+
+            let Some(end) = after_open[2..].find("}}") else {
+                rest = after_open;
+                break;
+            };
+
+            let name = after_open[2..2 + end].trim();
+            match self.get(name) {
+                Some(value) => output.push_str(value),
+                None => output.push_str(&after_open[..end + 4]),
+            }
+            rest = &after_open[end + 4..];
+        }
+
+        output.push_str(rest);
+        output
+    }
+
+    pub fn interpolate_params(&self, params: &[Param]) -> Vec<Param> {
+        params
+            .iter()
+            .map(|p| Param {
+                enabled: p.enabled,
+                key: self.interpolate(&p.key),
+                value: self.interpolate(&p.value),
+                file: p.file,
+            })
+            .collect()
+    }
+}
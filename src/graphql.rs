@@ -0,0 +1,866 @@
+use std::{
+    collections::HashMap,
+    ops::Range,
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Deserializer};
+
+/// The standard introspection query, deep enough for lists of non-null lists of non-null types
+pub const INTROSPECTION_QUERY: &str = r#"query IntrospectionQuery {
+  __schema {
+    queryType { name }
+    mutationType { name }
+    subscriptionType { name }
+    types { ...FullType }
+  }
+}
+
+fragment FullType on __Type {
+  kind
+  name
+  description
+  fields(includeDeprecated: true) {
+    name
+    description
+    args { ...InputValue }
+    type { ...TypeRef }
+    isDeprecated
+  }
+  inputFields { ...InputValue }
+  interfaces { ...TypeRef }
+  enumValues(includeDeprecated: true) { name description isDeprecated }
+  possibleTypes { ...TypeRef }
+}
+
+fragment InputValue on __InputValue {
+  name
+  description
+  type { ...TypeRef }
+  defaultValue
+}
+
+fragment TypeRef on __Type {
+  kind
+  name
+  ofType {
+    kind
+    name
+    ofType {
+      kind
+      name
+      ofType {
+        kind
+        name
+        ofType {
+          kind
+          name
+          ofType { kind name ofType { kind name ofType { kind name } } }
+        }
+      }
+    }
+  }
+}"#;
+
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TypeKind {
+    Scalar,
+    Object,
+    Interface,
+    Union,
+    Enum,
+    InputObject,
+    List,
+    NonNull,
+}
+
+impl TypeKind {
+    /// Whether fields are selected from it, the others are leaves
+    pub fn is_composite(self) -> bool {
+        matches!(self, Self::Object | Self::Interface | Self::Union)
+    }
+}
+
+impl std::fmt::Display for TypeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Scalar => write!(f, "scalar"),
+            Self::Object => write!(f, "type"),
+            Self::Interface => write!(f, "interface"),
+            Self::Union => write!(f, "union"),
+            Self::Enum => write!(f, "enum"),
+            Self::InputObject => write!(f, "input"),
+            Self::List => write!(f, "list"),
+            Self::NonNull => write!(f, "non-null"),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Schema {
+    query_type: Option<RootType>,
+    mutation_type: Option<RootType>,
+    subscription_type: Option<RootType>,
+    pub types: Vec<SchemaType>,
+}
+
+#[derive(Deserialize)]
+struct RootType {
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaType {
+    pub kind: TypeKind,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default, deserialize_with = "null_as_empty")]
+    pub fields: Vec<Field>,
+    #[serde(default, deserialize_with = "null_as_empty")]
+    pub input_fields: Vec<InputValue>,
+    #[serde(default, deserialize_with = "null_as_empty")]
+    pub interfaces: Vec<TypeRef>,
+    #[serde(default, deserialize_with = "null_as_empty")]
+    pub enum_values: Vec<EnumValue>,
+    #[serde(default, deserialize_with = "null_as_empty")]
+    pub possible_types: Vec<TypeRef>,
+}
+
+impl SchemaType {
+    pub fn field(&self, name: &str) -> Option<&Field> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Field {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default, deserialize_with = "null_as_empty")]
+    pub args: Vec<InputValue>,
+    #[serde(rename = "type")]
+    pub type_ref: TypeRef,
+    #[serde(default)]
+    pub is_deprecated: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InputValue {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(rename = "type")]
+    pub type_ref: TypeRef,
+    pub default_value: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnumValue {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub is_deprecated: bool,
+}
+
+/// A type as used by a field, named ones are wrapped in lists and non-nulls
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeRef {
+    pub kind: TypeKind,
+    pub name: Option<String>,
+    pub of_type: Option<Box<TypeRef>>,
+}
+
+impl TypeRef {
+    /// Name of the type inside the wrappers
+    pub fn named(&self) -> &str {
+        match (&self.name, &self.of_type) {
+            (Some(name), _) => name,
+            (None, Some(of_type)) => of_type.named(),
+            (None, None) => "",
+        }
+    }
+}
+
+/// In the SDL notation, like `[User!]!`
+impl std::fmt::Display for TypeRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.kind, &self.of_type) {
+            (TypeKind::List, Some(of_type)) => write!(f, "[{of_type}]"),
+            (TypeKind::NonNull, Some(of_type)) => write!(f, "{of_type}!"),
+            _ => write!(f, "{}", self.named()),
+        }
+    }
+}
+
+fn null_as_empty<'de, D: Deserializer<'de>, T: Deserialize<'de>>(
+    deserializer: D,
+) -> Result<Vec<T>, D::Error> {
+    Ok(Option::<Vec<T>>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+impl Schema {
+    pub fn get(&self, name: &str) -> Option<&SchemaType> {
+        self.types
+            .iter()
+            .find(|schema_type| schema_type.name == name)
+    }
+
+    /// Names of the query, mutation and subscription types the schema has
+    pub fn roots(&self) -> Vec<(&'static str, &str)> {
+        [
+            ("query", &self.query_type),
+            ("mutation", &self.mutation_type),
+            ("subscription", &self.subscription_type),
+        ]
+        .into_iter()
+        .filter_map(|(operation, root)| Some((operation, root.as_ref()?.name.as_str())))
+        .collect()
+    }
+
+    fn root(&self, operation: &str) -> Option<&SchemaType> {
+        let (_, name) = self
+            .roots()
+            .into_iter()
+            .find(|(root, _)| *root == operation)?;
+        self.get(name)
+    }
+}
+
+/// The schema from the body of an introspection response
+pub fn parse_schema(body: &str) -> Result<Schema, String> {
+    #[derive(Deserialize)]
+    struct Response {
+        data: Option<Data>,
+        #[serde(default, deserialize_with = "null_as_empty")]
+        errors: Vec<ResponseError>,
+    }
+    #[derive(Deserialize)]
+    struct Data {
+        #[serde(rename = "__schema")]
+        schema: Schema,
+    }
+    #[derive(Deserialize)]
+    struct ResponseError {
+        message: String,
+    }
+
+    let response: Response = serde_json::from_str(body)
+        .map_err(|err| format!("the response isn't an introspection result: {err}"))?;
+    match response.data {
+        Some(data) => Ok(data.schema),
+        None if !response.errors.is_empty() => Err(response
+            .errors
+            .into_iter()
+            .map(|error| error.message)
+            .collect::<Vec<_>>()
+            .join("\n")),
+        None => Err("the response has no schema".to_owned()),
+    }
+}
+
+#[derive(Clone, Default)]
+pub enum SchemaStatus {
+    #[default]
+    Idle,
+    Fetching,
+    Failed(String),
+}
+
+#[derive(Clone, Default)]
+pub struct CachedSchema {
+    pub schema: Option<Arc<Schema>>,
+    pub status: SchemaStatus,
+}
+
+/// Schemas by the url they were introspected at, requests to the same endpoint share one.
+/// Kept in memory only
+#[derive(Clone, Default)]
+pub struct SchemaCache {
+    entries: Arc<Mutex<HashMap<String, CachedSchema>>>,
+}
+
+impl SchemaCache {
+    pub fn get(&self, url: &str) -> CachedSchema {
+        let entries = self.entries.lock().unwrap();
+        entries.get(url).cloned().unwrap_or_default()
+    }
+
+    pub fn set_status(&self, url: &str, status: SchemaStatus) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.entry(url.to_owned()).or_default().status = status;
+    }
+
+    /// A failed introspection keeps the schema fetched before
+    pub fn store(&self, url: &str, result: Result<Schema, String>) {
+        let mut entries = self.entries.lock().unwrap();
+        let entry = entries.entry(url.to_owned()).or_default();
+        match result {
+            Ok(schema) => {
+                entry.schema = Some(Arc::new(schema));
+                entry.status = SchemaStatus::Idle;
+            }
+            Err(err) => entry.status = SchemaStatus::Failed(err),
+        }
+    }
+}
+
+/// Something wrong with the query, at a byte offset in its text
+pub struct Problem {
+    pub offset: usize,
+    pub message: String,
+}
+
+impl Problem {
+    fn new(offset: usize, message: impl Into<String>) -> Self {
+        Self {
+            offset,
+            message: message.into(),
+        }
+    }
+
+    /// One-based line and column of the problem
+    pub fn position(&self, query: &str) -> (usize, usize) {
+        let before = &query[..self.offset.min(query.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before
+            .rsplit('\n')
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .count()
+            + 1;
+        (line, column)
+    }
+}
+
+/// Fields that fit where the cursor is
+pub struct Completions {
+    /// Byte range of the name being typed, replaced by the picked field
+    pub replace: Range<usize>,
+    /// Names of the fields with their types
+    pub fields: Vec<(String, String)>,
+}
+
+#[derive(Default)]
+pub struct Analysis {
+    pub problems: Vec<Problem>,
+    pub completions: Option<Completions>,
+}
+
+/// Checks the query's syntax, and its fields and arguments against the schema when there's one.
+/// Completions are found for the byte offset `cursor`
+pub fn analyze(query: &str, schema: Option<&Schema>, cursor: Option<usize>) -> Analysis {
+    let tokens = match tokenize(query) {
+        Ok(tokens) => tokens,
+        Err(problem) => {
+            return Analysis {
+                problems: vec![problem],
+                completions: None,
+            };
+        }
+    };
+    let mut walker = Walker {
+        query,
+        schema,
+        tokens,
+        next: 0,
+        cursor,
+        problems: vec![],
+        completion: None,
+    };
+    if let Err(problem) = walker.document() {
+        walker.problems.push(problem);
+    }
+    walker.problems.sort_by_key(|problem| problem.offset);
+
+    let completions = walker.completion.map(|(parent, replace, prefix)| {
+        let prefix = prefix.to_lowercase();
+        let mut fields = parent
+            .fields
+            .iter()
+            .filter(|field| field.name.to_lowercase().starts_with(&prefix))
+            .map(|field| (field.name.clone(), field.type_ref.to_string()))
+            .collect::<Vec<_>>();
+        if "__typename".starts_with(&prefix) && !prefix.is_empty() {
+            fields.push(("__typename".to_owned(), "String!".to_owned()));
+        }
+        Completions { replace, fields }
+    });
+    Analysis {
+        problems: walker.problems,
+        completions,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Token<'a> {
+    Name(&'a str),
+    Punctuator(char),
+    Spread,
+    /// Strings and numbers, their content doesn't matter here
+    Value,
+}
+
+#[derive(Clone, Copy)]
+struct Lexed<'a> {
+    token: Token<'a>,
+    start: usize,
+    end: usize,
+}
+
+fn tokenize(query: &str) -> Result<Vec<Lexed<'_>>, Problem> {
+    let bytes = query.as_bytes();
+    let name_char = |byte: u8| byte == b'_' || byte.is_ascii_alphanumeric();
+    let mut tokens = vec![];
+    let mut at = 0;
+    while at < bytes.len() {
+        let start = at;
+        let token = match bytes[at] {
+            b' ' | b'\t' | b'\r' | b'\n' | b',' => {
+                at += 1;
+                continue;
+            }
+            b'#' => {
+                while at < bytes.len() && bytes[at] != b'\n' {
+                    at += 1;
+                }
+                continue;
+            }
+            b'.' if query[at..].starts_with("...") => {
+                at += 3;
+                Token::Spread
+            }
+            b'"' if query[at..].starts_with(r#"""""#) => {
+                match query[at + 3..].find(r#"""""#) {
+                    Some(length) => at += length + 6,
+                    None => return Err(Problem::new(start, "unterminated block string")),
+                }
+                Token::Value
+            }
+            b'"' => {
+                at += 1;
+                loop {
+                    match bytes.get(at) {
+                        None | Some(b'\n') => {
+                            return Err(Problem::new(start, "unterminated string"));
+                        }
+                        Some(b'\\') => at += 2,
+                        Some(b'"') => break,
+                        Some(_) => at += 1,
+                    }
+                }
+                at += 1;
+                Token::Value
+            }
+            b'-' | b'0'..=b'9' => {
+                at += 1;
+                while at < bytes.len() && (name_char(bytes[at]) || b".+-".contains(&bytes[at])) {
+                    at += 1;
+                }
+                Token::Value
+            }
+            byte if name_char(byte) => {
+                while at < bytes.len() && name_char(bytes[at]) {
+                    at += 1;
+                }
+                Token::Name(&query[start..at])
+            }
+            byte if b"{}()[]:!$@=|&".contains(&byte) => {
+                at += 1;
+                Token::Punctuator(byte as char)
+            }
+            _ => {
+                let found = query[at..].chars().next().unwrap_or_default();
+                return Err(Problem::new(start, format!("unexpected '{found}'")));
+            }
+        };
+        tokens.push(Lexed {
+            token,
+            start,
+            end: at,
+        });
+    }
+    Ok(tokens)
+}
+
+/// Walks the query's selections while keeping track of the type they're selected from.
+/// Stops at the first syntax error, schema problems are collected along the way
+struct Walker<'a> {
+    query: &'a str,
+    schema: Option<&'a Schema>,
+    tokens: Vec<Lexed<'a>>,
+    next: usize,
+    cursor: Option<usize>,
+    problems: Vec<Problem>,
+    /// Type the cursor selects fields from, the range of the name there and what's typed of it
+    completion: Option<(&'a SchemaType, Range<usize>, &'a str)>,
+}
+
+impl<'a> Walker<'a> {
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.next).map(|lexed| lexed.token)
+    }
+
+    fn advance(&mut self) -> Option<Lexed<'a>> {
+        let lexed = self.tokens.get(self.next).copied();
+        self.next += 1;
+        lexed
+    }
+
+    fn unexpected(&self) -> Problem {
+        match self.tokens.get(self.next) {
+            Some(lexed) => Problem::new(
+                lexed.start,
+                format!("unexpected '{}'", &self.query[lexed.start..lexed.end]),
+            ),
+            None => Problem::new(self.query.len(), "the query ends too early"),
+        }
+    }
+
+    fn expect(&mut self, punctuator: char) -> Result<Lexed<'a>, Problem> {
+        match self.tokens.get(self.next) {
+            Some(lexed) if lexed.token == Token::Punctuator(punctuator) => {
+                self.next += 1;
+                Ok(*lexed)
+            }
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn expect_name(&mut self) -> Result<(&'a str, Lexed<'a>), Problem> {
+        match self.tokens.get(self.next).copied() {
+            Some(
+                lexed @ Lexed {
+                    token: Token::Name(name),
+                    ..
+                },
+            ) => {
+                self.next += 1;
+                Ok((name, lexed))
+            }
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn document(&mut self) -> Result<(), Problem> {
+        while let Some(token) = self.peek() {
+            match token {
+                Token::Punctuator('{') => {
+                    let root = self.root("query");
+                    self.selection_set(root)?;
+                }
+                Token::Name(operation @ ("query" | "mutation" | "subscription")) => {
+                    let start = self.tokens[self.next].start;
+                    self.advance();
+                    if let Some(Token::Name(_)) = self.peek() {
+                        self.advance();
+                    }
+                    if self.peek() == Some(Token::Punctuator('(')) {
+                        self.skip_balanced('(', ')')?;
+                    }
+                    self.directives()?;
+                    let root = self.root(operation);
+                    if root.is_none() && self.schema.is_some() {
+                        self.problems.push(Problem::new(
+                            start,
+                            format!("the schema has no {operation} type"),
+                        ));
+                    }
+                    self.selection_set(root)?;
+                }
+                Token::Name("fragment") => {
+                    self.advance();
+                    self.expect_name()?;
+                    let on = self.type_condition()?;
+                    self.directives()?;
+                    self.selection_set(on)?;
+                }
+                _ => return Err(self.unexpected()),
+            }
+        }
+        Ok(())
+    }
+
+    fn root(&self, operation: &str) -> Option<&'a SchemaType> {
+        self.schema?.root(operation)
+    }
+
+    /// The type of `on Type`
+    fn type_condition(&mut self) -> Result<Option<&'a SchemaType>, Problem> {
+        match self.expect_name()? {
+            ("on", _) => {}
+            (_, lexed) => return Err(Problem::new(lexed.start, "expected 'on'")),
+        }
+        let (name, lexed) = self.expect_name()?;
+        let Some(schema) = self.schema else {
+            return Ok(None);
+        };
+        let found = schema.get(name);
+        if found.is_none() {
+            self.problems
+                .push(Problem::new(lexed.start, format!("unknown type '{name}'")));
+        }
+        Ok(found)
+    }
+
+    /// `parent` is None when the type isn't known, nothing inside is checked then
+    fn selection_set(&mut self, parent: Option<&'a SchemaType>) -> Result<(), Problem> {
+        let mut previous_end = self.expect('{')?.end;
+        loop {
+            self.complete_at(parent, previous_end);
+            let Some(lexed) = self.advance() else {
+                return Err(Problem::new(self.query.len(), "missing '}'"));
+            };
+            match lexed.token {
+                Token::Punctuator('}') => return Ok(()),
+                Token::Spread => match self.peek() {
+                    Some(Token::Name("on")) => {
+                        let on = self.type_condition()?;
+                        self.directives()?;
+                        self.selection_set(on)?;
+                    }
+                    Some(Token::Name(_)) => {
+                        self.advance();
+                        self.directives()?;
+                    }
+                    _ => {
+                        self.directives()?;
+                        self.selection_set(parent)?;
+                    }
+                },
+                Token::Name(name) => {
+                    let (name, lexed) = if self.peek() == Some(Token::Punctuator(':')) {
+                        let colon = self.expect(':')?;
+                        self.complete_at(parent, colon.end);
+                        self.expect_name()?
+                    } else {
+                        (name, lexed)
+                    };
+                    self.field(parent, name, lexed)?;
+                }
+                _ => {
+                    self.next -= 1;
+                    return Err(self.unexpected());
+                }
+            }
+            previous_end = self.tokens[self.next - 1].end;
+        }
+    }
+
+    /// Remembers the fields of `parent` when the cursor is on the next name, or in the blank
+    /// space before it
+    fn complete_at(&mut self, parent: Option<&'a SchemaType>, from: usize) {
+        let (Some(parent), Some(cursor)) = (parent, self.cursor) else {
+            return;
+        };
+        if self.completion.is_some() || cursor < from {
+            return;
+        }
+        match self.tokens.get(self.next) {
+            Some(lexed) if cursor <= lexed.start => {
+                self.completion = Some((parent, cursor..cursor, ""));
+            }
+            Some(Lexed {
+                token: Token::Name(_),
+                start,
+                end,
+            }) if cursor <= *end => {
+                self.completion = Some((parent, *start..*end, &self.query[*start..cursor]));
+            }
+            Some(_) => {}
+            None => self.completion = Some((parent, cursor..cursor, "")),
+        }
+    }
+
+    fn field(
+        &mut self,
+        parent: Option<&'a SchemaType>,
+        name: &str,
+        lexed: Lexed<'a>,
+    ) -> Result<(), Problem> {
+        let mut field = None;
+        if let Some(parent) = parent.filter(|_| name != "__typename") {
+            field = parent.field(name);
+            let introspection = matches!(name, "__schema" | "__type")
+                && self.root("query").map(|root| root.name.as_str()) == Some(parent.name.as_str());
+            if field.is_none() && !introspection {
+                self.problems.push(Problem::new(
+                    lexed.start,
+                    format!("'{}' has no field '{name}'", parent.name),
+                ));
+            }
+        }
+
+        if self.peek() == Some(Token::Punctuator('(')) {
+            self.arguments(field)?;
+        }
+        self.directives()?;
+
+        let field_type =
+            field.and_then(|field| Some((field, self.schema?.get(field.type_ref.named())?)));
+        if self.peek() == Some(Token::Punctuator('{')) {
+            match field_type {
+                Some((field, field_type)) if !field_type.kind.is_composite() => {
+                    self.problems.push(Problem::new(
+                        lexed.start,
+                        format!(
+                            "'{name}' is the {} {}, it has no fields to select",
+                            field_type.kind, field.type_ref
+                        ),
+                    ));
+                    self.selection_set(None)
+                }
+                _ => self.selection_set(field_type.map(|(_, field_type)| field_type)),
+            }
+        } else {
+            if let Some((field, field_type)) = field_type {
+                if field_type.kind.is_composite() {
+                    self.problems.push(Problem::new(
+                        lexed.start,
+                        format!("'{name}' is {}, select some of its fields", field.type_ref),
+                    ));
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn arguments(&mut self, field: Option<&Field>) -> Result<(), Problem> {
+        self.expect('(')?;
+        while self.peek() != Some(Token::Punctuator(')')) {
+            let (name, lexed) = self.expect_name()?;
+            if let Some(field) = field {
+                if !field.args.iter().any(|arg| arg.name == name) {
+                    self.problems.push(Problem::new(
+                        lexed.start,
+                        format!("'{}' has no argument '{name}'", field.name),
+                    ));
+                }
+            }
+            self.expect(':')?;
+            self.value()?;
+        }
+        self.expect(')')?;
+        Ok(())
+    }
+
+    fn value(&mut self) -> Result<(), Problem> {
+        match self.peek() {
+            Some(Token::Punctuator('$')) => {
+                self.advance();
+                self.expect_name()?;
+            }
+            Some(Token::Value | Token::Name(_)) => {
+                self.advance();
+            }
+            Some(Token::Punctuator('[')) => {
+                self.advance();
+                while self.peek() != Some(Token::Punctuator(']')) {
+                    self.value()?;
+                }
+                self.advance();
+            }
+            Some(Token::Punctuator('{')) => {
+                self.advance();
+                while self.peek() != Some(Token::Punctuator('}')) {
+                    self.expect_name()?;
+                    self.expect(':')?;
+                    self.value()?;
+                }
+                self.advance();
+            }
+            _ => return Err(self.unexpected()),
+        }
+        Ok(())
+    }
+
+    fn directives(&mut self) -> Result<(), Problem> {
+        while self.peek() == Some(Token::Punctuator('@')) {
+            self.advance();
+            self.expect_name()?;
+            if self.peek() == Some(Token::Punctuator('(')) {
+                self.skip_balanced('(', ')')?;
+            }
+        }
+        Ok(())
+    }
+
+    fn skip_balanced(&mut self, open: char, close: char) -> Result<(), Problem> {
+        self.expect(open)?;
+        let mut depth = 1;
+        while depth > 0 {
+            match self.advance().map(|lexed| lexed.token) {
+                Some(Token::Punctuator(found)) if found == open => depth += 1,
+                Some(Token::Punctuator(found)) if found == close => depth -= 1,
+                Some(_) => {}
+                None => return Err(Problem::new(self.query.len(), format!("missing '{close}'"))),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An error of a GraphQL response
+pub struct ResponseError {
+    pub message: String,
+    /// Like `user.posts.0`
+    pub path: String,
+    /// Like `3:5`, where in the query it happened
+    pub locations: Vec<String>,
+}
+
+/// The `errors` of a response shaped like a GraphQL result, None for any other body
+pub fn response_errors(body: &str) -> Option<Vec<ResponseError>> {
+    let serde_json::Value::Object(result) = serde_json::from_str(body).ok()? else {
+        return None;
+    };
+    let graphql_keys = result
+        .keys()
+        .all(|key| matches!(key.as_str(), "data" | "errors" | "extensions"));
+    let errors = result.get("errors")?.as_array()?;
+    if !graphql_keys || errors.is_empty() {
+        return None;
+    }
+    let errors = errors
+        .iter()
+        .map(|error| ResponseError {
+            message: match error.get("message") {
+                Some(serde_json::Value::String(message)) => message.clone(),
+                _ => error.to_string(),
+            },
+            path: error
+                .get("path")
+                .and_then(|path| path.as_array())
+                .map(|path| {
+                    path.iter()
+                        .map(|part| match part {
+                            serde_json::Value::String(part) => part.clone(),
+                            part => part.to_string(),
+                        })
+                        .collect::<Vec<_>>()
+                        .join(".")
+                })
+                .unwrap_or_default(),
+            locations: error
+                .get("locations")
+                .and_then(|locations| locations.as_array())
+                .map(|locations| {
+                    locations
+                        .iter()
+                        .map(|location| {
+                            let at = |key| location.get(key).and_then(|at| at.as_u64());
+                            format!("{}:{}", at("line").unwrap_or(0), at("column").unwrap_or(0))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+        .collect();
+    Some(errors)
+}
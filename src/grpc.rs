@@ -0,0 +1,684 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use http::{HeaderMap, HeaderValue, Uri};
+use prost_reflect::{
+    DescriptorPool, DynamicMessage, Kind, MessageDescriptor, MethodDescriptor, SerializeOptions,
+    ServiceDescriptor, Value,
+};
+
+use crate::http::{HttpError, HttpResponse};
+
+/// Where the services and messages of a gRPC request are described
+#[derive(Clone, Copy, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum DescriptorSource {
+    #[default]
+    ProtoFiles,
+    /// Asked from the server, which needs reflection enabled
+    Reflection,
+}
+
+impl DescriptorSource {
+    pub fn values() -> [Self; 2] {
+        [Self::ProtoFiles, Self::Reflection]
+    }
+}
+
+impl fmt::Display for DescriptorSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ProtoFiles => write!(f, "Proto Files"),
+            Self::Reflection => write!(f, "Server Reflection"),
+        }
+    }
+}
+
+/// What a gRPC request calls, its message is the request's body as JSON
+#[derive(Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct GrpcOptions {
+    pub source: DescriptorSource,
+    /// Paths of the `.proto` files with the services
+    pub proto_files: Vec<String>,
+    /// Directories imports are looked up in, besides the one of each file
+    pub import_paths: Vec<String>,
+    /// Full name like `helloworld.Greeter`
+    pub service: String,
+    pub method: String,
+}
+
+impl GrpcOptions {
+    /// Requests loading the same files, or reflecting the same server, share descriptors
+    pub fn cache_key(&self, url: &str) -> String {
+        match self.source {
+            DescriptorSource::ProtoFiles => format!(
+                "protos:{}:{}",
+                self.proto_files.join(","),
+                self.import_paths.join(",")
+            ),
+            DescriptorSource::Reflection => format!("reflection:{}", origin(url)),
+        }
+    }
+}
+
+/// Like `https://example.com:50051`, what reflection asks
+fn origin(url: &str) -> String {
+    match url.parse::<Uri>() {
+        Ok(uri) => format!(
+            "{}://{}",
+            uri.scheme_str().unwrap_or("http"),
+            uri.authority()
+                .map(|authority| authority.as_str())
+                .unwrap_or("")
+        ),
+        Err(_) => url.to_owned(),
+    }
+}
+
+#[derive(Clone, Default)]
+pub enum DescriptorStatus {
+    #[default]
+    Idle,
+    Loading,
+    Failed(String),
+}
+
+#[derive(Clone, Default)]
+pub struct CachedDescriptors {
+    pub pool: Option<DescriptorPool>,
+    pub status: DescriptorStatus,
+}
+
+/// Descriptors by `GrpcOptions::cache_key`. Kept in memory only
+#[derive(Clone, Default)]
+pub struct DescriptorCache {
+    entries: Arc<Mutex<HashMap<String, CachedDescriptors>>>,
+}
+
+impl DescriptorCache {
+    pub fn get(&self, key: &str) -> CachedDescriptors {
+        let entries = self.entries.lock().unwrap();
+        entries.get(key).cloned().unwrap_or_default()
+    }
+
+    pub fn set_status(&self, key: &str, status: DescriptorStatus) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.entry(key.to_owned()).or_default().status = status;
+    }
+
+    /// A failed load keeps the descriptors loaded before
+    pub fn store(&self, key: &str, result: Result<DescriptorPool, String>) {
+        let mut entries = self.entries.lock().unwrap();
+        let entry = entries.entry(key.to_owned()).or_default();
+        match result {
+            Ok(pool) => {
+                entry.pool = Some(pool);
+                entry.status = DescriptorStatus::Idle;
+            }
+            Err(err) => entry.status = DescriptorStatus::Failed(err),
+        }
+    }
+}
+
+/// Parses the files with what they import, google/protobuf ones are built in
+#[cfg(not(target_arch = "wasm32"))]
+pub fn parse_proto_files(
+    files: &[String],
+    import_paths: &[String],
+) -> Result<DescriptorPool, String> {
+    use std::path::Path;
+
+    let files = non_empty(files);
+    if files.is_empty() {
+        return Err("there are no .proto files to load".to_owned());
+    }
+    let mut parser = protobuf_parse::Parser::new();
+    parser.pure();
+    parser.includes(non_empty(import_paths));
+    for file in &files {
+        // so files next to it can be imported by name
+        let directory = Path::new(file).parent().filter(|dir| dir != &Path::new(""));
+        parser.include(directory.unwrap_or(Path::new(".")));
+        parser.input(file);
+    }
+    let parsed = parser
+        .parse_and_typecheck()
+        .map_err(|err| format!("{err:#}"))?;
+    // protobuf-parse has its own descriptor types, prost-reflect reads them encoded
+    let set = protobuf::descriptor::FileDescriptorSet {
+        file: parsed.file_descriptors,
+        ..Default::default()
+    };
+    let encoded = protobuf::Message::write_to_bytes(&set).map_err(|err| err.to_string())?;
+    DescriptorPool::decode(encoded.as_slice()).map_err(|err| err.to_string())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn non_empty(paths: &[String]) -> Vec<&str> {
+    paths
+        .iter()
+        .map(|path| path.trim())
+        .filter(|path| !path.is_empty())
+        .collect()
+}
+
+/// Services that can be called, by name. Reflection's own isn't of interest
+pub fn services(pool: &DescriptorPool) -> Vec<ServiceDescriptor> {
+    let mut services = pool
+        .services()
+        .filter(|service| !service.full_name().starts_with("grpc.reflection."))
+        .collect::<Vec<_>>();
+    services.sort_by(|a, b| a.full_name().cmp(b.full_name()));
+    services
+}
+
+pub fn find_method(
+    pool: &DescriptorPool,
+    service: &str,
+    method: &str,
+) -> Result<MethodDescriptor, String> {
+    if service.is_empty() || method.is_empty() {
+        return Err("pick the service and method to call".to_owned());
+    }
+    let service = pool
+        .get_service_by_name(service)
+        .ok_or_else(|| format!("there's no service '{service}' in the descriptors"))?;
+    let found = service.methods().find(|found| found.name() == method);
+    found.ok_or_else(|| format!("'{}' has no method '{method}'", service.full_name()))
+}
+
+/// Like "unary" or "server streaming"
+pub fn call_kind(method: &MethodDescriptor) -> &'static str {
+    match (method.is_client_streaming(), method.is_server_streaming()) {
+        (false, false) => "unary",
+        (false, true) => "server streaming",
+        (true, false) => "client streaming",
+        (true, true) => "bidirectional streaming",
+    }
+}
+
+/// The message with every field at its default, to fill in
+pub fn message_template(message: &MessageDescriptor) -> String {
+    let options = SerializeOptions::new().skip_default_fields(false);
+    template(message, 0)
+        .serialize_with_options(serde_json::value::Serializer, &options)
+        .and_then(|value| serde_json::to_string_pretty(&value))
+        .unwrap_or_default()
+}
+
+/// Unset message fields would be left out, so they're filled in too, as deep as a
+/// recursive message could go sensibly
+fn template(message: &MessageDescriptor, depth: usize) -> DynamicMessage {
+    let mut filled = DynamicMessage::new(message.clone());
+    if depth >= 4 {
+        return filled;
+    }
+    for field in message.fields() {
+        // only the first of a oneof, the others would replace it
+        let first_of_oneof = field
+            .containing_oneof()
+            .is_none_or(|oneof| oneof.fields().next().as_ref() == Some(&field));
+        if let Kind::Message(nested) = field.kind() {
+            if !field.is_list() && !field.is_map() && first_of_oneof {
+                let nested = template(&nested, depth + 1);
+                filled.set_field(&field, Value::Message(nested));
+            }
+        }
+    }
+    filled
+}
+
+fn encode_message(message: &MessageDescriptor, json: &str) -> Result<Vec<u8>, String> {
+    let json = match json.trim() {
+        "" => "{}",
+        json => json,
+    };
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    let decoded = DynamicMessage::deserialize(message.clone(), &mut deserializer)
+        .and_then(|decoded| deserializer.end().map(|_| decoded))
+        .map_err(|err| format!("the message isn't a valid {}: {err}", message.full_name()))?;
+    Ok(prost::Message::encode_to_vec(&decoded))
+}
+
+fn decode_message(message: &MessageDescriptor, bytes: &[u8]) -> Result<serde_json::Value, String> {
+    let decoded = DynamicMessage::decode(message.clone(), bytes)
+        .map_err(|err| format!("the answer isn't a valid {}: {err}", message.full_name()))?;
+    decoded
+        .serialize_with_options(serde_json::value::Serializer, &SerializeOptions::new())
+        .map_err(|err| err.to_string())
+}
+
+/// A message as gRPC sends it, prefixed with its compressed flag and length
+fn frame(message: &[u8]) -> Vec<u8> {
+    let mut framed = Vec::with_capacity(message.len() + 5);
+    framed.push(0);
+    framed.extend_from_slice(&(message.len() as u32).to_be_bytes());
+    framed.extend_from_slice(message);
+    framed
+}
+
+/// Takes the complete messages off the front of `buffer`
+fn unframe(buffer: &mut Vec<u8>) -> Result<Vec<Vec<u8>>, String> {
+    let mut messages = vec![];
+    let mut start = 0;
+    while buffer.len() - start >= 5 {
+        let header = &buffer[start..start + 5];
+        if header[0] != 0 {
+            // nothing asks for compression, so servers shouldn't use it
+            return Err("the server sent a compressed message".to_owned());
+        }
+        let length = u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+        if buffer.len() - start - 5 < length {
+            break;
+        }
+        messages.push(buffer[start + 5..start + 5 + length].to_vec());
+        start += 5 + length;
+    }
+    buffer.drain(..start);
+    Ok(messages)
+}
+
+/// Turns the built request into the call of `method`. The JSON message is encoded as the
+/// body and the url's path names the method, headers stay as the call's metadata
+pub fn prepare(
+    request: &mut http::Request<Vec<u8>>,
+    method: &MethodDescriptor,
+    message: &str,
+) -> Result<(), HttpError> {
+    if method.is_client_streaming() {
+        return Err(HttpError::Grpc(format!(
+            "'{}' is a {} call, only unary and server streaming ones can be made",
+            method.name(),
+            call_kind(method)
+        )));
+    }
+    let message = encode_message(&method.input(), message).map_err(HttpError::Grpc)?;
+    let path = format!("{}/{}", method.parent_service().full_name(), method.name());
+    into_call(request, &path, frame(&message))
+}
+
+fn into_call(
+    request: &mut http::Request<Vec<u8>>,
+    path: &str,
+    body: Vec<u8>,
+) -> Result<(), HttpError> {
+    let uri = request.uri();
+    // a path in the url prefixes the method's, for servers behind a routing proxy
+    let prefix = uri.path().trim_end_matches('/');
+    let url = format!("{}{prefix}/{path}", origin(&uri.to_string()));
+    *request.uri_mut() =
+        url.parse()
+            .map_err(|err: http::uri::InvalidUri| HttpError::InvalidUrl {
+                url,
+                reason: err.to_string(),
+            })?;
+    *request.method_mut() = http::Method::POST;
+    *request.body_mut() = body;
+    let headers = request.headers_mut();
+    headers.remove(http::header::CONTENT_LENGTH);
+    headers.insert(
+        http::header::CONTENT_TYPE,
+        HeaderValue::from_static("application/grpc"),
+    );
+    headers.insert(http::header::TE, HeaderValue::from_static("trailers"));
+    Ok(())
+}
+
+/// Turns the messages of a response into JSON as they arrive
+pub struct MessageDecoder {
+    output: MessageDescriptor,
+    buffer: Vec<u8>,
+    messages: Vec<serde_json::Value>,
+}
+
+impl MessageDecoder {
+    pub fn new(method: &MethodDescriptor) -> Self {
+        Self {
+            output: method.output(),
+            buffer: vec![],
+            messages: vec![],
+        }
+    }
+
+    /// The JSON of each message the chunk completes, a line each
+    pub fn push(&mut self, chunk: &[u8]) -> Result<String, String> {
+        self.buffer.extend_from_slice(chunk);
+        let mut lines = String::new();
+        for message in unframe(&mut self.buffer)? {
+            let value = decode_message(&self.output, &message)?;
+            lines.push_str(&value.to_string());
+            lines.push('\n');
+            self.messages.push(value);
+        }
+        Ok(lines)
+    }
+
+    /// The body as text and prettified. Streamed messages get a line each, and prettify
+    /// to a list
+    pub fn finish(self) -> Result<(String, Option<String>), String> {
+        if !self.buffer.is_empty() {
+            return Err("the response ended in the middle of a message".to_owned());
+        }
+        let pretty = |value: &serde_json::Value| serde_json::to_string_pretty(value).ok();
+        Ok(match self.messages.as_slice() {
+            [] => (String::new(), None),
+            [message] => (message.to_string(), pretty(message)),
+            messages => {
+                let lines = messages.iter().map(|message| message.to_string());
+                let body = lines.collect::<Vec<_>>().join("\n");
+                (body, pretty(&serde_json::Value::from(messages.to_vec())))
+            }
+        })
+    }
+}
+
+/// How a call ended
+pub struct GrpcStatus {
+    pub code: u32,
+    pub message: String,
+}
+
+impl GrpcStatus {
+    pub fn is_ok(&self) -> bool {
+        self.code == 0
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self.code {
+            0 => "OK",
+            1 => "CANCELLED",
+            2 => "UNKNOWN",
+            3 => "INVALID_ARGUMENT",
+            4 => "DEADLINE_EXCEEDED",
+            5 => "NOT_FOUND",
+            6 => "ALREADY_EXISTS",
+            7 => "PERMISSION_DENIED",
+            8 => "RESOURCE_EXHAUSTED",
+            9 => "FAILED_PRECONDITION",
+            10 => "ABORTED",
+            11 => "OUT_OF_RANGE",
+            12 => "UNIMPLEMENTED",
+            13 => "INTERNAL",
+            14 => "UNAVAILABLE",
+            15 => "DATA_LOSS",
+            16 => "UNAUTHENTICATED",
+            _ => "UNKNOWN",
+        }
+    }
+}
+
+impl fmt::Display for GrpcStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.message.is_empty() {
+            true => write!(f, "{} {}", self.code, self.name()),
+            false => write!(f, "{} {}: {}", self.code, self.name(), self.message),
+        }
+    }
+}
+
+/// From the trailers, or the headers of a response with nothing but them
+pub fn status(response: &HttpResponse) -> Option<GrpcStatus> {
+    read_status(|name| {
+        let mut all = response.trailers.iter().chain(&response.headers);
+        all.find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    })
+}
+
+fn status_of(headers: &HeaderMap) -> Option<GrpcStatus> {
+    read_status(|name| headers.get(name).and_then(|value| value.to_str().ok()))
+}
+
+fn read_status<'a>(find: impl Fn(&str) -> Option<&'a str>) -> Option<GrpcStatus> {
+    Some(GrpcStatus {
+        code: find("grpc-status")?.trim().parse().ok()?,
+        message: percent_decode(find("grpc-message").unwrap_or_default()),
+    })
+}
+
+/// grpc-message is percent encoded
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = bytes
+            .get(index + 1..index + 3)
+            .filter(|_| bytes[index] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use reflection::reflect;
+
+/// Asks the server for its descriptors, see
+/// <https://github.com/grpc/grpc/blob/master/doc/server-reflection.md>
+#[cfg(not(target_arch = "wasm32"))]
+mod reflection {
+    use std::collections::HashMap;
+
+    use http_body_util::BodyExt;
+    use prost::Message;
+    use prost_reflect::DescriptorPool;
+    use prost_types::FileDescriptorProto;
+
+    use super::{frame, into_call, status_of, unframe};
+    use crate::http::HttpError;
+
+    const V1: &str = "grpc.reflection.v1.ServerReflection/ServerReflectionInfo";
+    const V1_ALPHA: &str = "grpc.reflection.v1alpha.ServerReflection/ServerReflectionInfo";
+    /// Rounds of asking for files imported by the ones received
+    const MAX_ROUNDS: usize = 10;
+
+    #[derive(Clone, PartialEq, Message)]
+    struct ReflectionRequest {
+        #[prost(string, tag = "1")]
+        host: String,
+        #[prost(oneof = "Query", tags = "3, 4, 7")]
+        query: Option<Query>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    enum Query {
+        #[prost(string, tag = "3")]
+        FileByFilename(String),
+        #[prost(string, tag = "4")]
+        FileContainingSymbol(String),
+        #[prost(string, tag = "7")]
+        ListServices(String),
+    }
+
+    #[derive(Clone, PartialEq, Message)]
+    struct ReflectionResponse {
+        #[prost(oneof = "Answer", tags = "4, 6, 7")]
+        answer: Option<Answer>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    enum Answer {
+        #[prost(message, tag = "4")]
+        Files(FileDescriptorResponse),
+        #[prost(message, tag = "6")]
+        Services(ListServiceResponse),
+        #[prost(message, tag = "7")]
+        Error(ErrorResponse),
+    }
+
+    #[derive(Clone, PartialEq, Message)]
+    struct FileDescriptorResponse {
+        #[prost(bytes = "vec", repeated, tag = "1")]
+        file_descriptor_proto: Vec<Vec<u8>>,
+    }
+
+    #[derive(Clone, PartialEq, Message)]
+    struct ListServiceResponse {
+        #[prost(message, repeated, tag = "1")]
+        service: Vec<ServiceResponse>,
+    }
+
+    #[derive(Clone, PartialEq, Message)]
+    struct ServiceResponse {
+        #[prost(string, tag = "1")]
+        name: String,
+    }
+
+    #[derive(Clone, PartialEq, Message)]
+    struct ErrorResponse {
+        #[prost(int32, tag = "1")]
+        error_code: i32,
+        #[prost(string, tag = "2")]
+        error_message: String,
+    }
+
+    struct Failed {
+        /// The gRPC status, when the server got to answer with one
+        code: Option<u32>,
+        message: String,
+    }
+
+    /// The descriptors of every service of the server. `base` carries the url, metadata
+    /// and auth, with `client` speaking HTTP/2
+    pub async fn reflect(
+        client: reqwest::Client,
+        base: http::Request<Vec<u8>>,
+    ) -> Result<DescriptorPool, String> {
+        let list = [Query::ListServices(String::new())];
+        // older servers only have the alpha version
+        let (path, answers) = match call(&client, &base, V1, &list).await {
+            Err(Failed { code: Some(12), .. }) => {
+                (V1_ALPHA, call(&client, &base, V1_ALPHA, &list).await)
+            }
+            answers => (V1, answers),
+        };
+        let mut queries = vec![];
+        for answer in answers.map_err(|failed| failed.message)? {
+            if let Answer::Services(services) = answer {
+                let names = services.service.into_iter().map(|service| service.name);
+                queries.extend(names.map(Query::FileContainingSymbol));
+            }
+        }
+
+        let mut files = HashMap::<String, FileDescriptorProto>::new();
+        for _ in 0..MAX_ROUNDS {
+            if queries.is_empty() {
+                break;
+            }
+            let answers = call(&client, &base, path, &queries)
+                .await
+                .map_err(|failed| failed.message)?;
+            for answer in answers {
+                if let Answer::Files(found) = answer {
+                    for encoded in found.file_descriptor_proto {
+                        let file = FileDescriptorProto::decode(encoded.as_slice())
+                            .map_err(|err| format!("the server sent an invalid file: {err}"))?;
+                        files.insert(file.name().to_owned(), file);
+                    }
+                }
+            }
+            let mut missing = files
+                .values()
+                .flat_map(|file| &file.dependency)
+                .filter(|name| !files.contains_key(*name))
+                .cloned()
+                .collect::<Vec<_>>();
+            missing.sort();
+            missing.dedup();
+            queries = missing.into_iter().map(Query::FileByFilename).collect();
+        }
+        if files.is_empty() {
+            return Err("the server described no services".to_owned());
+        }
+        let mut pool = DescriptorPool::new();
+        pool.add_file_descriptor_protos(files.into_values())
+            .map_err(|err| err.to_string())?;
+        Ok(pool)
+    }
+
+    /// One stream with every query, the server answers each in turn
+    async fn call(
+        client: &reqwest::Client,
+        base: &http::Request<Vec<u8>>,
+        path: &str,
+        queries: &[Query],
+    ) -> Result<Vec<Answer>, Failed> {
+        let failed = |message: String| Failed {
+            code: None,
+            message,
+        };
+        let host = base.uri().host().unwrap_or_default().to_owned();
+        let mut request = http::Request::new(vec![]);
+        *request.uri_mut() = base.uri().clone();
+        *request.headers_mut() = base.headers().clone();
+        let body = queries
+            .iter()
+            .flat_map(|query| {
+                let message = ReflectionRequest {
+                    host: host.clone(),
+                    query: Some(query.clone()),
+                };
+                frame(&message.encode_to_vec())
+            })
+            .collect();
+        into_call(&mut request, path, body).map_err(|err| failed(err.to_string()))?;
+
+        let request = reqwest::Request::try_from(request).map_err(|err| failed(err.to_string()))?;
+        let response = client
+            .execute(request)
+            .await
+            .map_err(|err| failed(HttpError::from_reqwest(err, &host).to_string()))?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let collected = http::Response::from(response)
+            .into_body()
+            .collect()
+            .await
+            .map_err(|err| failed(HttpError::from_reqwest(err, &host).to_string()))?;
+        let trailers = collected.trailers().cloned().unwrap_or_default();
+        match status_of(&trailers).or_else(|| status_of(&headers)) {
+            Some(grpc_status) if !grpc_status.is_ok() => {
+                return Err(Failed {
+                    code: Some(grpc_status.code),
+                    message: format!("reflection failed with {grpc_status}"),
+                });
+            }
+            None if !status.is_success() => {
+                return Err(failed(format!("the server answered {status}")));
+            }
+            _ => {}
+        }
+
+        let mut body = collected.to_bytes().to_vec();
+        let mut answers = vec![];
+        for message in unframe(&mut body).map_err(failed)? {
+            let response = ReflectionResponse::decode(message.as_slice())
+                .map_err(|err| failed(format!("the server sent an invalid answer: {err}")))?;
+            match response.answer {
+                Some(Answer::Error(error)) => {
+                    return Err(Failed {
+                        code: u32::try_from(error.error_code).ok(),
+                        message: format!("reflection failed: {}", error.error_message),
+                    });
+                }
+                Some(answer) => answers.push(answer),
+                None => {}
+            }
+        }
+        Ok(answers)
+    }
+}
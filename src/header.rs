@@ -1,13 +1,19 @@
 use egui::{FontId, FontSelection, Layout, Margin, Vec2};
 
 use crate::{
-    app::GlobalContext,
-    core::RequestState,
+    core::{RequestKind, RequestState},
+    curl,
     http::{self, HttpMethod},
-    tasks,
+    tasks::{self, GlobalContext, RequestContext},
+    websocket::Frame,
 };
 
-pub fn show<'a>(ui: &mut egui::Ui, state: &mut RequestState, global_context: &GlobalContext) {
+pub fn show<'a>(
+    ui: &mut egui::Ui,
+    state: &mut RequestState,
+    context: &RequestContext,
+    global_context: &GlobalContext,
+) {
     // The central panel the region left after adding TopPanel's and SidePanel's
     ui.add_space(10.0);
     ui.horizontal(|ui| {
@@ -15,12 +21,25 @@ pub fn show<'a>(ui: &mut egui::Ui, state: &mut RequestState, global_context: &Gl
         ui.scope(|ui| {
             ui.style_mut().spacing.button_padding = Vec2::new(8.0, 6.5);
 
+            let websocket = state.kind == RequestKind::WebSocket;
+            let grpc = state.kind == RequestKind::Grpc;
             egui::ComboBox::from_id_salt("http.method")
-                .selected_text(state.method.to_string())
+                .selected_text(match state.kind {
+                    RequestKind::WebSocket => "WS".to_owned(),
+                    RequestKind::Grpc => "gRPC".to_owned(),
+                    _ => state.method.to_string(),
+                })
                 .show_ui(ui, |ui| {
                     for method in HttpMethod::values_iter() {
-                        ui.selectable_value(&mut state.method, method, method.to_string());
+                        let selected = !websocket && !grpc && state.method == method;
+                        if ui.selectable_label(selected, method.to_string()).clicked() {
+                            state.kind = RequestKind::Http;
+                            state.method = method;
+                        }
                     }
+                    ui.separator();
+                    ui.selectable_value(&mut state.kind, RequestKind::WebSocket, "WebSocket");
+                    ui.selectable_value(&mut state.kind, RequestKind::Grpc, "gRPC");
                 });
 
             ui.allocate_ui_with_layout(
@@ -37,19 +56,83 @@ pub fn show<'a>(ui: &mut egui::Ui, state: &mut RequestState, global_context: &Gl
                         //      return a typed response
                     }
 
-                    if ui.button("SEND").clicked() {
-                        tasks::execute(state, &global_context.async_runtime)
-                        // if ui.button("Send").clicked() {
+                    if websocket {
+                        // the connection stays up until either side closes it
+                        if state.session.status().is_active() {
+                            if ui.button("DISCONNECT").clicked() {
+                                let close = Frame::Close {
+                                    code: Some(1000),
+                                    reason: String::new(),
+                                };
+                                if state.session.send(close).is_err() {
+                                    state.session.fail("disconnected".to_owned());
+                                }
+                            }
+                        } else if ui.button("CONNECT").clicked() {
+                            tasks::connect_websocket(state, context, global_context);
+                        }
+                    } else {
+                        // a gRPC call is binary framed, curl can't make it
+                        if !grpc
+                            && ui
+                                .button("\u{e14d}")
+                                .on_hover_text("Copy as cURL")
+                                .clicked()
+                        {
+                            match tasks::prepare_snippet(
+                                state,
+                                context,
+                                &global_context.oauth_tokens,
+                            ) {
+                                Ok((request, body)) => {
+                                    ui.ctx().copy_text(curl::to_command(&request, &body))
+                                }
+                                // shown where a request that failed to send shows it
+                                Err(err) => *state.response.lock().unwrap() = Some(Err(err)),
+                            }
+                        }
+
+                        if state.stream.is_active() {
+                            if ui.button("CANCEL").clicked() {
+                                state.stream.cancel();
+                            }
+                        } else if ui.button("SEND").clicked() {
+                            tasks::execute(state, context, global_context)
+                            // if ui.button("Send").clicked() {
+                        }
                     }
 
-                    ui.add_sized(
+                    let response = ui.add_sized(
                         ui.available_size_before_wrap(),
                         egui::TextEdit::singleline(&mut state.url)
                             .code_editor()
                             .font(FontSelection::FontId(FontId::monospace(14.0)))
                             .margin(Margin::same(6))
-                            .hint_text("http://httpbin.org/get"),
+                            .hint_text(match state.kind {
+                                RequestKind::WebSocket => "wss://echo.websocket.org",
+                                RequestKind::Grpc => "http://localhost:50051",
+                                _ => "http://httpbin.org/get",
+                            }),
                     );
+                    // pasting a curl command replaces what it describes of the request
+                    if response.changed() && curl::is_curl_command(&state.url) {
+                        match curl::parse(&state.url) {
+                            // only what the command holds, the rest of the request stays
+                            Ok(parsed) => {
+                                state.kind = parsed.kind;
+                                state.method = parsed.method;
+                                state.url = parsed.url;
+                                state.query = parsed.query;
+                                state.headers = parsed.headers;
+                                state.auth = parsed.auth;
+                                // a raw body, its content type is among the headers
+                                state.body = parsed.body;
+                                state.body_options.kind = parsed.body_options.kind;
+                                state.body_options.content_type = parsed.body_options.content_type;
+                            }
+                            Err(err) => log::warn!("failed to parse curl command: {err}"),
+                        }
+                    }
                 },
             );
 
@@ -0,0 +1,168 @@
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::{
+    auth::RequestAuth,
+    body::BodyOptions,
+    core::{Param, RequestKind, RequestState},
+    grpc::GrpcOptions,
+    http::{HttpError, HttpMethod, HttpResponse},
+};
+
+/// Oldest entries are dropped past this
+const MAX_ENTRIES: usize = 500;
+
+/// The request as it went over the wire, with variables, defaults and auth resolved
+#[derive(Clone, Default, serde::Deserialize, serde::Serialize)]
+pub struct SentRequest {
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl SentRequest {
+    pub fn from_request(request: &http::Request<Vec<u8>>) -> Self {
+        Self {
+            url: request.uri().to_string(),
+            headers: request
+                .headers()
+                .iter()
+                .map(|(name, value)| {
+                    let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+                    (name.to_string(), value)
+                })
+                .collect(),
+            body: String::from_utf8_lossy(request.body()).into_owned(),
+        }
+    }
+}
+
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+pub struct HistoryEntry {
+    pub id: usize,
+    /// Unix time in milliseconds
+    pub timestamp: u64,
+    pub name: String,
+
+    // the request as it was in the editor, restored as is
+    #[serde(default)]
+    pub kind: RequestKind,
+    pub method: HttpMethod,
+    pub url: String,
+    pub query: Vec<Param>,
+    pub headers: Vec<Param>,
+    pub body: String,
+    #[serde(default)]
+    pub body_options: BodyOptions,
+    pub auth: RequestAuth,
+    #[serde(default)]
+    pub grpc: GrpcOptions,
+
+    pub sent: SentRequest,
+    pub response: HttpResponse,
+    /// Set instead of `response` when no response came back
+    #[serde(default)]
+    pub error: Option<HttpError>,
+}
+
+impl HistoryEntry {
+    /// Entry for a request about to be sent, the response is filled in once it arrives
+    pub fn new(state: &RequestState, sent: SentRequest) -> Self {
+        Self {
+            id: 0,
+            timestamp: now_millis(),
+            name: state.name.clone(),
+            kind: state.kind,
+            method: state.method,
+            url: state.url.clone(),
+            query: state.query.clone(),
+            headers: state.headers.clone(),
+            body: state.body.clone(),
+            body_options: state.body_options.clone(),
+            auth: state.auth.clone(),
+            grpc: state.grpc.clone(),
+            sent,
+            response: Default::default(),
+            error: None,
+        }
+    }
+
+    /// Case insensitive search over the name, method, url and status
+    pub fn matches(&self, search: &str) -> bool {
+        let search = search.trim().to_lowercase();
+        search.is_empty()
+            || [
+                self.name.as_str(),
+                &self.method_label(),
+                &self.sent.url,
+                &self.response.status.to_string(),
+            ]
+            .iter()
+            .any(|field| field.to_lowercase().contains(&search))
+    }
+
+    /// Like `GET`, or `gRPC` for a gRPC call
+    pub fn method_label(&self) -> String {
+        match self.kind {
+            RequestKind::Grpc => "gRPC".to_owned(),
+            _ => self.method.to_string(),
+        }
+    }
+
+    /// A fresh request with what the editor had when this was sent
+    pub fn restore(&self) -> RequestState {
+        RequestState {
+            name: self.name.clone(),
+            kind: self.kind,
+            method: self.method,
+            url: self.url.clone(),
+            query: self.query.clone(),
+            headers: self.headers.clone(),
+            body: self.body.clone(),
+            body_options: self.body_options.clone(),
+            auth: self.auth.clone(),
+            grpc: self.grpc.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+pub struct History {
+    /// Oldest first
+    entries: Vec<HistoryEntry>,
+    next_id: usize,
+}
+
+impl History {
+    pub fn push(&mut self, mut entry: HistoryEntry) {
+        entry.id = self.next_id;
+        self.next_id += 1;
+        self.entries.push(entry);
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.drain(..self.entries.len() - MAX_ENTRIES);
+        }
+    }
+
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    pub fn get(&self, id: usize) -> Option<&HistoryEntry> {
+        self.entries.iter().find(|entry| entry.id == id)
+    }
+
+    pub fn remove(&mut self, id: usize) {
+        self.entries.retain(|entry| entry.id != id);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_millis() as u64)
+        .unwrap_or_default()
+}
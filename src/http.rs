@@ -1,12 +1,22 @@
 use core::fmt;
-use std::{
-    error::Error,
-    time::{Duration, Instant},
-};
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
+use std::{error::Error, pin::pin, time::Duration};
 
+use futures_channel::oneshot;
+use futures_util::{
+    StreamExt,
+    future::{self, Either},
+};
 use http::{HeaderValue, Request};
+use web_time::Instant;
 
-use crate::core::{Param, RequestState};
+use crate::{
+    core::{Param, RequestState},
+    grpc::{self, MessageDecoder},
+    stream::StreamFeed,
+    timing::{self, Timing},
+};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
 pub enum HttpMethod {
@@ -34,6 +44,11 @@ impl HttpMethod {
         ]
         .into_iter()
     }
+
+    /// Case insensitive lookup by method name, e.g. "post"
+    pub fn from_name(name: &str) -> Option<HttpMethod> {
+        Self::values_iter().find(|method| method.to_string().eq_ignore_ascii_case(name.trim()))
+    }
 }
 
 impl fmt::Display for HttpMethod {
@@ -61,57 +76,436 @@ pub struct HttpResponse {
 
     pub body_pretty: Option<String>,
     pub duration: Duration,
+    #[serde(default)]
+    pub timing: Timing,
+    #[serde(default)]
+    pub sizes: Sizes,
+    /// Redirects followed before getting this response
+    #[serde(default)]
+    pub redirects: Vec<Redirect>,
+    /// Sent after the body, like the status of gRPC calls
+    #[serde(default)]
+    pub trailers: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct Redirect {
+    pub status: u16,
+    /// Where the redirect came from
+    pub url: String,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+tokio::task_local! {
+    static REDIRECTS: std::cell::RefCell<Vec<Redirect>>;
 }
 
-#[derive(Debug)]
+/// Notes a redirect of the request being sent on this task
+#[cfg(not(target_arch = "wasm32"))]
+pub fn record_redirect(status: u16, url: &str) {
+    let _ = REDIRECTS.try_with(|redirects| {
+        redirects.borrow_mut().push(Redirect {
+            status,
+            url: url.to_owned(),
+        })
+    });
+}
+
+/// Sizes in bytes, headers are counted as HTTP/1.1 would frame them
+#[derive(Default, Debug, Clone, Copy, serde::Deserialize, serde::Serialize)]
+pub struct Sizes {
+    pub request_headers: usize,
+    pub request_body: usize,
+    pub response_headers: usize,
+    pub response_body: usize,
+}
+
+fn headers_size<'a>(
+    start_line: &str,
+    headers: impl IntoIterator<Item = (&'a str, &'a [u8])>,
+) -> usize {
+    // "name: value\r\n" per header, and the blank line ending them
+    start_line.len()
+        + 2
+        + headers
+            .into_iter()
+            .map(|(name, value)| name.len() + value.len() + 4)
+            .sum::<usize>()
+        + 2
+}
+
+/// Why a request didn't produce a response
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 pub enum HttpError {
+    InvalidUrl {
+        url: String,
+        reason: String,
+    },
+    InvalidHeader {
+        name: String,
+        reason: String,
+    },
+    /// Signing or otherwise applying the auth failed
+    Auth(String),
+    /// The host name couldn't be resolved
+    Dns {
+        host: String,
+        reason: String,
+    },
+    ConnectionRefused {
+        host: String,
+    },
+    Timeout,
+    Tls(String),
+    /// Connected, but reading the response failed
+    Body(String),
+    /// The client couldn't be set up with the configured settings
+    Settings(String),
+    /// The pre-request script failed
+    Script(String),
+    /// The body couldn't be made, like when a file to send can't be read
+    RequestBody(String),
+    /// WebSocket requests can't be sent as one-shot HTTP calls
+    WebSocket(String),
+    /// The gRPC call couldn't be made, like when its method isn't in the descriptors
+    Grpc(String),
+    /// Cancelled before the response headers arrived
+    Cancelled,
     Unknown(String),
 }
 
-pub async fn execute_new(input: Request<Vec<u8>>) -> Result<HttpResponse, Box<dyn Error>> {
-    let client = reqwest::Client::new();
-    let response = client
+pub type HttpResult = Result<HttpResponse, HttpError>;
+
+impl HttpError {
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::InvalidUrl { .. } => "Invalid URL",
+            Self::InvalidHeader { .. } => "Invalid header",
+            Self::Auth(_) => "Auth failed",
+            Self::Dns { .. } => "Could not resolve host",
+            Self::ConnectionRefused { .. } => "Connection refused",
+            Self::Timeout => "Request timed out",
+            Self::Tls(_) => "TLS error",
+            Self::Body(_) => "Failed to read the response",
+            Self::Settings(_) => "Invalid client settings",
+            Self::Script(_) => "Pre-request script failed",
+            Self::RequestBody(_) => "Invalid request body",
+            Self::WebSocket(_) => "WebSocket request",
+            Self::Grpc(_) => "Invalid gRPC call",
+            Self::Cancelled => "Request cancelled",
+            Self::Unknown(_) => "Request failed",
+        }
+    }
+
+    /// What the user can do about it
+    pub fn hint(&self) -> &'static str {
+        match self {
+            Self::InvalidUrl { .. } => {
+                "Check the url includes a scheme like https:// and that all variables are defined"
+            }
+            Self::InvalidHeader { .. } => {
+                "Header names can't contain spaces or special characters, values can't contain line breaks"
+            }
+            Self::Auth(_) => "Check the credentials and settings in the Auth tab",
+            Self::Dns { .. } => "Check the host name for typos, and your network or VPN connection",
+            Self::ConnectionRefused { .. } => {
+                "Nothing is listening on that port, check the server is running and the port is right"
+            }
+            Self::Timeout => {
+                "The server took too long to answer, try again or check it's reachable"
+            }
+            Self::Tls(_) => {
+                "The server's certificate couldn't be verified, check the url uses the right scheme and host"
+            }
+            Self::Body(_) => "The connection dropped or the body wasn't what the headers announced",
+            Self::Settings(_) => "Check the proxy url and certificate files in Settings",
+            Self::Script(_) => "The request wasn't sent, see the console in the Script tab",
+            Self::RequestBody(_) => "Check the files of the body exist and can be read",
+            Self::WebSocket(_) => "Connect it from the editor to exchange messages",
+            Self::Grpc(_) => "Check the descriptors, method and message in the Body tab",
+            Self::Cancelled => "No response arrived before it was cancelled, send it again",
+            Self::Unknown(_) => "See the details below",
+        }
+    }
+
+    /// Classifies a reqwest error by looking through its sources
+    pub(crate) fn from_reqwest(err: reqwest::Error, host: &str) -> Self {
+        let mut details = vec![err.to_string()];
+        let mut source = err.source();
+        let mut refused = false;
+        while let Some(inner) = source {
+            if let Some(io) = inner.downcast_ref::<std::io::Error>() {
+                refused |= io.kind() == std::io::ErrorKind::ConnectionRefused;
+            }
+            details.push(inner.to_string());
+            source = inner.source();
+        }
+        let reason = details.last().cloned().unwrap_or_default();
+        let all = details.join(": ").to_lowercase();
+
+        if err.is_timeout() {
+            Self::Timeout
+        } else if err.is_builder() {
+            Self::InvalidUrl {
+                url: err.url().map(|url| url.to_string()).unwrap_or_default(),
+                reason,
+            }
+        } else if all.contains("dns error") || all.contains("failed to lookup address") {
+            Self::Dns {
+                host: host.to_owned(),
+                reason,
+            }
+        } else if refused {
+            Self::ConnectionRefused {
+                host: host.to_owned(),
+            }
+        } else if all.contains("certificate") || all.contains("tls") || all.contains("ssl") {
+            Self::Tls(reason)
+        } else if err.is_body() || err.is_decode() {
+            Self::Body(reason)
+        } else {
+            Self::Unknown(details.join(": "))
+        }
+    }
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidUrl { url, reason } => write!(f, "invalid url '{url}': {reason}"),
+            Self::InvalidHeader { name, reason } => write!(f, "invalid header '{name}': {reason}"),
+            Self::Auth(reason) => write!(f, "failed to apply auth: {reason}"),
+            Self::Dns { host, reason } => write!(f, "could not resolve '{host}': {reason}"),
+            Self::ConnectionRefused { host } => write!(f, "connection to '{host}' refused"),
+            Self::Timeout => write!(f, "request timed out"),
+            Self::Tls(reason) => write!(f, "tls error: {reason}"),
+            Self::Body(reason) => write!(f, "failed to read response: {reason}"),
+            Self::Settings(reason) => write!(f, "invalid client settings: {reason}"),
+            Self::Script(reason) => write!(f, "pre-request script: {reason}"),
+            Self::RequestBody(reason) => write!(f, "request body: {reason}"),
+            Self::WebSocket(reason) => write!(f, "websocket: {reason}"),
+            Self::Grpc(reason) => write!(f, "grpc: {reason}"),
+            Self::Cancelled => write!(f, "request cancelled"),
+            Self::Unknown(reason) => write!(f, "{reason}"),
+        }
+    }
+}
+
+impl Error for HttpError {}
+
+/// Sends the request with `client`, see `client::Clients`
+pub async fn execute_new(client: reqwest::Client, input: Request<Vec<u8>>) -> HttpResult {
+    execute_streamed(client, input, None, None).await
+}
+
+/// Like `execute_new`, also passing the body to `stream` as it arrives. The request stops
+/// once `cancelled` resolves, with the body read until then when the headers are in.
+/// The body of a gRPC call is decoded with its `method`
+pub async fn execute_streamed(
+    client: reqwest::Client,
+    input: Request<Vec<u8>>,
+    stream: Option<(StreamFeed, oneshot::Receiver<()>)>,
+    method: Option<prost_reflect::MethodDescriptor>,
+) -> HttpResult {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let recorder = Arc::new(timing::PhaseRecorder::default());
+        let request = timing::record(recorder, send(client, input, stream, method));
+        REDIRECTS.scope(Default::default(), request).await
+    }
+    #[cfg(target_arch = "wasm32")]
+    send(client, input, stream, method).await
+}
+
+/// Part of a response body
+enum Chunk {
+    Data(bytes::Bytes),
+    Trailers(http::HeaderMap),
+}
+
+async fn send(
+    client: reqwest::Client,
+    input: Request<Vec<u8>>,
+    stream: Option<(StreamFeed, oneshot::Receiver<()>)>,
+    method: Option<prost_reflect::MethodDescriptor>,
+) -> HttpResult {
+    let (stream, cancelled) = match stream {
+        Some((stream, cancelled)) => (Some(stream), Some(cancelled)),
+        None => (None, None),
+    };
+    // dropping the sender cancels too, a newer request took over the stream then
+    let mut cancelled = pin!(async move {
+        match cancelled {
+            Some(cancelled) => drop(cancelled.await),
+            None => future::pending().await,
+        }
+    });
+
+    let request_line = format!(
+        "{} {} HTTP/1.1",
+        input.method(),
+        input.uri().path_and_query().map_or("/", |p| p.as_str())
+    );
+    let request_headers_size = headers_size(
+        &request_line,
+        input
+            .headers()
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_bytes())),
+    );
+    let request_body_size = input.body().len();
+    let host = input.uri().host().unwrap_or_default().to_owned();
+
+    let start = Instant::now();
+    let request = client
         .request(input.method().clone(), input.uri().to_string())
         .headers(input.headers().clone())
         .body(input.into_body())
-        .send()
-        .await?;
+        .send();
+    let response = match future::select(pin!(request), cancelled.as_mut()).await {
+        Either::Left((response, _)) => {
+            response.map_err(|err| HttpError::from_reqwest(err, &host))?
+        }
+        Either::Right(_) => return Err(HttpError::Cancelled),
+    };
 
-    let headers = response
-        .headers()
-        .into_iter()
-        .map(|(key, value)| (key.to_string(), value.to_str().unwrap().to_string()))
-        .collect::<Vec<(String, String)>>();
+    let headers = header_pairs(response.headers());
     let status = response.status();
+    let headers_received = start.elapsed();
+    // the connection timed is the last redirect's, which may have switched to https
+    let secure = response.url().scheme() == "https";
+    let response_headers_size = headers_size(
+        &format!("HTTP/1.1 {status}"),
+        response
+            .headers()
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_bytes())),
+    );
+
+    let event_stream = response
+        .headers()
+        .get(http::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.trim_start().starts_with("text/event-stream"));
+    if let Some(stream) = &stream {
+        stream.headers(event_stream);
+    }
+    // read chunk by chunk so streamed bodies show up as they arrive
+    #[cfg(not(target_arch = "wasm32"))]
+    let chunks = {
+        use http_body_util::BodyStream;
+        let body = http::Response::from(response).into_body();
+        BodyStream::new(body).map(|frame| {
+            frame.map(|frame| match frame.into_data() {
+                Ok(data) => Chunk::Data(data),
+                Err(frame) => Chunk::Trailers(frame.into_trailers().unwrap_or_default()),
+            })
+        })
+    };
+    // the browser keeps trailers to itself
+    #[cfg(target_arch = "wasm32")]
+    let chunks = response.bytes_stream().map(|chunk| chunk.map(Chunk::Data));
+    let mut chunks = pin!(chunks);
+    let mut body_bytes = vec![];
+    let mut trailers = vec![];
+    let mut decoder = method.as_ref().map(MessageDecoder::new);
+    let mut was_cancelled = false;
+    loop {
+        match future::select(chunks.next(), cancelled.as_mut()).await {
+            Either::Left((Some(chunk), _)) => {
+                match chunk.map_err(|err| HttpError::from_reqwest(err, &host))? {
+                    Chunk::Data(chunk) => {
+                        // what's shown live of a gRPC call is its messages as JSON
+                        let decoded = match &mut decoder {
+                            Some(decoder) => Some(decoder.push(&chunk).map_err(HttpError::Body)?),
+                            None => None,
+                        };
+                        if let Some(stream) = &stream {
+                            match &decoded {
+                                Some(decoded) => stream.append(decoded.as_bytes()),
+                                None => stream.append(&chunk),
+                            }
+                        }
+                        body_bytes.extend_from_slice(&chunk);
+                    }
+                    Chunk::Trailers(map) => trailers.extend(header_pairs(&map)),
+                }
+            }
+            Either::Left((None, _)) => break,
+            Either::Right(_) => {
+                was_cancelled = true;
+                break;
+            }
+        }
+    }
+    if let Some(stream) = &stream {
+        stream.finish(was_cancelled);
+    }
+    let total = start.elapsed();
+    #[cfg(not(target_arch = "wasm32"))]
+    let (dns, connect, redirects) = (
+        timing::current().and_then(|recorder| recorder.dns()),
+        timing::current().and_then(|recorder| recorder.connect()),
+        REDIRECTS
+            .try_with(|redirects| redirects.take())
+            .unwrap_or_default(),
+    );
+    #[cfg(target_arch = "wasm32")]
+    let (dns, connect, redirects) = (None, None, vec![]);
 
-    let body_bytes = &response.bytes().await?;
-    let body_str = std::str::from_utf8(&body_bytes).unwrap_or_default();
-    let parsed = serde_json::from_slice::<serde_json::Value>(&body_bytes);
-    let body_pretty = match parsed {
-        Ok(value) => Some(serde_json::to_string_pretty(&value)?),
-        Err(e) => {
-            log::warn!("failed to parse response body {}", e);
-            None
+    let (body_raw, body_pretty) = match decoder {
+        Some(decoder) => decoder.finish().map_err(HttpError::Body)?,
+        None => {
+            let body_str = std::str::from_utf8(&body_bytes).unwrap_or_default();
+            let parsed = serde_json::from_slice::<serde_json::Value>(&body_bytes);
+            let body_pretty = match parsed {
+                Ok(value) => serde_json::to_string_pretty(&value).ok(),
+                Err(e) => {
+                    log::warn!("failed to parse response body {}", e);
+                    None
+                }
+            };
+            (body_str.to_string(), body_pretty)
         }
     };
 
-    let response = HttpResponse {
+    let mut response = HttpResponse {
         headers,
         ok: status.is_success(),
         status: status.as_u16(),
         status_text: status.to_string(),
-        body_raw: body_str.to_string(),
+        body_raw,
         body_pretty,
-        duration: Default::default(),
+        duration: total,
+        timing: Timing::new(dns, connect, headers_received, total, secure),
+        sizes: Sizes {
+            request_headers: request_headers_size,
+            request_body: request_body_size,
+            response_headers: response_headers_size,
+            response_body: body_bytes.len(),
+        },
+        redirects,
+        trailers,
     };
+    // a gRPC call fails with a status, the HTTP one is 200 anyway
+    if method.is_some() {
+        response.ok &= grpc::status(&response).is_some_and(|status| status.is_ok());
+    }
 
     Ok(response)
 }
 
-pub fn execute(
-    input: Request<Vec<u8>>,
-    callback: impl 'static + Send + FnOnce(Result<HttpResponse, HttpError>),
-) {
+fn header_pairs(headers: &http::HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(key, value)| {
+            let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+            (key.to_string(), value)
+        })
+        .collect()
+}
+
+pub fn execute(input: Request<Vec<u8>>, callback: impl 'static + Send + FnOnce(HttpResult)) {
     let request = {
         let headers = input
             .headers()
@@ -159,9 +553,16 @@ pub fn execute(
                     body_raw: body.to_owned(),
                     body_pretty,
                     duration: Default::default(),
+                    timing: Default::default(),
+                    sizes: Sizes {
+                        response_body: response.bytes.len(),
+                        ..Default::default()
+                    },
+                    redirects: vec![],
+                    trailers: vec![],
                 }
             })
-            .map_err(|err| HttpError::Unknown(err));
+            .map_err(HttpError::Unknown);
 
         log::info!("{:?}", mapped);
         callback(mapped);
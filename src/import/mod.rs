@@ -0,0 +1,262 @@
+pub(crate) mod openapi;
+pub(crate) mod postman;
+
+use serde_json::Value;
+
+use crate::{
+    auth::RequestAuth,
+    body::BodyOptions,
+    collections::{CollectionItem, Collections, Folder},
+    core::{Param, RequestState},
+    environment::{Environment, Environments},
+    http::HttpMethod,
+};
+
+#[derive(Debug)]
+pub enum ImportError {
+    /// Input isn't valid JSON or YAML
+    Parse(String),
+    /// Valid input, but not a format we know how to import
+    UnknownFormat,
+}
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parse(err) => write!(f, "failed to parse input: {err}"),
+            Self::UnknownFormat => write!(
+                f,
+                "unrecognised format, expected a Postman collection or environment, or an OpenAPI/Swagger spec"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+/// What an import produced. Anything that couldn't be mapped is listed in `warnings`
+/// rather than being dropped silently
+#[derive(Default)]
+pub struct ImportResult {
+    pub collections: Vec<Folder>,
+    pub environments: Vec<Environment>,
+    pub summary: ImportSummary,
+}
+
+#[derive(Default)]
+pub struct ImportSummary {
+    pub requests: usize,
+    /// Requests imported before that picked up upstream changes
+    pub updated: usize,
+    pub folders: usize,
+    pub environments: usize,
+    pub warnings: Vec<String>,
+}
+
+impl ImportSummary {
+    pub fn warn(&mut self, location: &str, message: impl std::fmt::Display) {
+        self.warnings.push(format!("{location}: {message}"));
+    }
+}
+
+/// A request as it was last imported. Comparing it to the local copy tells local edits apart
+/// from upstream changes when importing again
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+pub struct ImportOrigin {
+    /// Identifies the source across imports, e.g. `openapi:Petstore:GET /pets/{petId}`
+    pub key: String,
+    pub name: String,
+    pub url: String,
+    pub method: HttpMethod,
+    pub query: Vec<Param>,
+    pub headers: Vec<Param>,
+    pub body: String,
+    #[serde(default)]
+    pub body_options: BodyOptions,
+    pub auth: RequestAuth,
+}
+
+impl ImportOrigin {
+    pub fn of(key: String, state: &RequestState) -> Self {
+        Self {
+            key,
+            name: state.name.clone(),
+            url: state.url.clone(),
+            method: state.method,
+            query: state.query.clone(),
+            headers: state.headers.clone(),
+            body: state.body.clone(),
+            body_options: state.body_options.clone(),
+            auth: state.auth.clone(),
+        }
+    }
+}
+
+/// Detects the format of `input` and imports it
+pub fn import(input: &str) -> Result<ImportResult, ImportError> {
+    // YAML is a superset of JSON, but JSON errors are easier to read
+    let value =
+        serde_json::from_str::<Value>(input).or_else(|json_err| {
+            match serde_yaml::from_str::<Value>(input) {
+                Ok(value) if value.is_object() => Ok(value),
+                _ => Err(ImportError::Parse(json_err.to_string())),
+            }
+        })?;
+
+    let mut result = if postman::is_collection(&value) {
+        postman::import_collection(value)?
+    } else if postman::is_environment(&value) {
+        postman::import_environment(value)?
+    } else if openapi::is_spec(&value) {
+        openapi::import_spec(value)?
+    } else {
+        return Err(ImportError::UnknownFormat);
+    };
+    result.summary.environments = result.environments.len();
+    Ok(result)
+}
+
+/// Adds an import to the workspace. Requests imported before are updated in place, wherever
+/// they've been moved to, keeping any fields that were edited locally
+pub fn merge_into(
+    result: ImportResult,
+    collections: &mut Collections,
+    environments: &mut Environments,
+) -> ImportSummary {
+    let mut summary = result.summary;
+
+    for mut folder in result.collections {
+        summary.updated += update_existing(&mut folder, collections);
+
+        // whatever is left is new and goes next to the earlier import, if there is one
+        let existing = collections.items.iter_mut().find(|collection| {
+            collection.name == folder.name && collection.has_imported_requests()
+        });
+        match existing {
+            Some(existing) => existing.merge(folder),
+            None => collections.items.push(folder),
+        }
+    }
+
+    for environment in result.environments {
+        let existing = environments
+            .items
+            .iter_mut()
+            .find(|existing| existing.name == environment.name);
+        match existing {
+            // values may have been filled in locally, only new variables are added
+            Some(existing) => {
+                for variable in environment.variables {
+                    if !existing.variables.iter().any(|v| v.key == variable.key) {
+                        existing.variables.push(variable);
+                    }
+                }
+            }
+            None => environments.items.push(environment),
+        }
+    }
+
+    summary
+}
+
+/// Merges requests that already exist locally and drops them from `folder`,
+/// returns how many had upstream changes
+fn update_existing(folder: &mut Folder, collections: &mut Collections) -> usize {
+    let mut updated = 0;
+    folder.items.retain_mut(|item| match item {
+        CollectionItem::Folder(child) => {
+            let was_empty = child.items.is_empty();
+            updated += update_existing(child, collections);
+            was_empty || !child.items.is_empty()
+        }
+        CollectionItem::Request(_, state) => {
+            let Some(origin) = &state.origin else {
+                return true;
+            };
+            let Some(local) = collections.find_imported_request_mut(&origin.key) else {
+                return true;
+            };
+            if merge_request(local, std::mem::take(state)) {
+                updated += 1;
+            }
+            false
+        }
+    });
+    updated
+}
+
+/// Three way merge of an imported request into its local copy, a field only takes the
+/// upstream value if it wasn't edited locally. Returns whether anything changed upstream
+fn merge_request(local: &mut RequestState, remote: RequestState) -> bool {
+    let Some(remote_origin) = remote.origin else {
+        return false;
+    };
+    let Some(base) = local.origin.take() else {
+        local.origin = Some(remote_origin);
+        return false;
+    };
+
+    let changed = base.name != remote_origin.name
+        || base.url != remote_origin.url
+        || base.method != remote_origin.method
+        || base.query != remote_origin.query
+        || base.headers != remote_origin.headers
+        || base.body != remote_origin.body
+        || base.body_options != remote_origin.body_options
+        || base.auth != remote_origin.auth;
+
+    merge_field(&mut local.name, &base.name, remote.name);
+    merge_field(&mut local.url, &base.url, remote.url);
+    merge_field(&mut local.method, &base.method, remote.method);
+    merge_field(&mut local.body, &base.body, remote.body);
+    merge_field(
+        &mut local.body_options,
+        &base.body_options,
+        remote.body_options,
+    );
+    merge_field(&mut local.auth, &base.auth, remote.auth);
+    merge_params(&mut local.query, &base.query, remote.query);
+    merge_params(&mut local.headers, &base.headers, remote.headers);
+
+    local.origin = Some(remote_origin);
+    changed
+}
+
+fn merge_field<T: PartialEq>(local: &mut T, base: &T, remote: T) {
+    if local == base {
+        *local = remote;
+    }
+}
+
+/// Same as `merge_field` but per param, matched by key
+fn merge_params(local: &mut Vec<Param>, base: &[Param], remote: Vec<Param>) {
+    // dropped upstream, and untouched here
+    local.retain(|param| !base.contains(param) || remote.iter().any(|r| r.key == param.key));
+
+    for param in remote {
+        let base_param = base.iter().find(|p| p.key == param.key);
+        let local_param = local.iter_mut().find(|p| p.key == param.key);
+        match (local_param, base_param) {
+            (Some(local_param), Some(base_param)) if local_param == base_param => {
+                *local_param = param
+            }
+            // edited, or deleted locally
+            (Some(_), _) | (None, Some(_)) => {}
+            (None, None) => local.push(param),
+        }
+    }
+}
+
+fn set_default_header(headers: &mut Vec<Param>, key: &str, value: &str) {
+    if !headers.iter().any(|h| h.key.eq_ignore_ascii_case(key)) {
+        headers.push(Param::enabled(key.to_owned(), value.to_owned()));
+    }
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
@@ -0,0 +1,646 @@
+use serde_json::{Map, Value};
+
+use crate::{
+    auth::{ApiKeyParams, AuthLocation, RequestAuth},
+    collections::{CollectionItem, Folder},
+    core::{Param, RequestId, RequestState},
+    environment::Environment,
+    http::HttpMethod,
+    import::{
+        ImportError, ImportOrigin, ImportResult, ImportSummary, set_default_header, value_to_string,
+    },
+};
+
+const METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// Nested `$ref`s and schemas deeper than this are cut off, specs can be recursive
+const MAX_DEPTH: usize = 8;
+
+pub fn is_spec(value: &Value) -> bool {
+    let version = |key: &str| value.get(key).and_then(Value::as_str);
+    version("openapi").is_some_and(|v| v.starts_with('3'))
+        || version("swagger").is_some_and(|v| v.starts_with('2'))
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Version {
+    Swagger2,
+    OpenApi3,
+}
+
+struct Importer<'a> {
+    root: &'a Value,
+    version: Version,
+    title: String,
+    /// `consumes` from the root of Swagger 2 specs
+    consumes: Vec<&'a str>,
+    /// Generated variables like path params and credentials, collected into one environment
+    variables: Vec<Param>,
+    summary: ImportSummary,
+}
+
+pub fn import_spec(value: Value) -> Result<ImportResult, ImportError> {
+    let version = if value.get("swagger").is_some() {
+        Version::Swagger2
+    } else {
+        Version::OpenApi3
+    };
+    let mut importer = Importer {
+        root: &value,
+        version,
+        title: value
+            .pointer("/info/title")
+            .and_then(Value::as_str)
+            .unwrap_or("OpenAPI")
+            .to_owned(),
+        consumes: str_array(value.get("consumes")),
+        variables: vec![],
+        summary: ImportSummary::default(),
+    };
+
+    let mut root = Folder::new(importer.title.clone());
+    root.defaults.base_url = importer.base_url();
+    root.defaults.auth = match value.get("security") {
+        Some(security) => importer.auth(security, "security"),
+        None => RequestAuth::None,
+    };
+
+    let paths = value
+        .get("paths")
+        .and_then(Value::as_object)
+        .ok_or_else(|| ImportError::Parse("spec has no paths".to_owned()))?;
+
+    // operations are grouped into one folder per tag, in the order tags are first seen
+    let mut tags: Vec<Folder> = vec![];
+    for (path, path_item) in paths {
+        let path_item = importer.resolve(path_item);
+        let shared_params = path_item.get("parameters");
+
+        for method in METHODS {
+            let Some(operation) = path_item.get(method) else {
+                continue;
+            };
+            let Some(state) = importer.request(path, method, operation, shared_params) else {
+                continue;
+            };
+            importer.summary.requests += 1;
+
+            let item = CollectionItem::Request(RequestId::next(), Box::new(state));
+            match operation.pointer("/tags/0").and_then(Value::as_str) {
+                Some(tag) => match tags.iter_mut().find(|folder| folder.name == tag) {
+                    Some(folder) => folder.items.push(item),
+                    None => {
+                        let mut folder = Folder::new(tag.to_owned());
+                        folder.items.push(item);
+                        tags.push(folder);
+                    }
+                },
+                None => root.items.push(item),
+            }
+        }
+    }
+
+    importer.summary.folders = tags.len();
+    root.items.splice(
+        0..0,
+        tags.into_iter()
+            .map(|tag| CollectionItem::Folder(Box::new(tag))),
+    );
+
+    let mut result = ImportResult {
+        collections: vec![root],
+        environments: vec![],
+        summary: importer.summary,
+    };
+    if !importer.variables.is_empty() {
+        let mut environment = Environment::new(format!("{} variables", importer.title));
+        environment.variables = importer.variables;
+        result.environments.push(environment);
+    }
+    Ok(result)
+}
+
+impl<'a> Importer<'a> {
+    /// Registers a variable the first time it's seen, returns the `{{name}}` to use it
+    fn variable(&mut self, key: &str, value: String) -> String {
+        if !self.variables.iter().any(|p| p.key == key) {
+            self.variables.push(Param::enabled(key.to_owned(), value));
+        }
+        format!("{{{{{key}}}}}")
+    }
+
+    /// Follows local `$ref`s like `#/components/schemas/Pet`, remote refs are left as they are
+    fn resolve(&self, mut value: &'a Value) -> &'a Value {
+        for _ in 0..MAX_DEPTH {
+            let Some(reference) = value.get("$ref").and_then(Value::as_str) else {
+                break;
+            };
+            match reference
+                .strip_prefix('#')
+                .and_then(|pointer| self.root.pointer(pointer))
+            {
+                Some(target) => value = target,
+                None => break,
+            }
+        }
+        value
+    }
+
+    fn base_url(&mut self) -> String {
+        match self.version {
+            Version::Swagger2 => {
+                let scheme = self
+                    .root
+                    .pointer("/schemes/0")
+                    .and_then(Value::as_str)
+                    .unwrap_or("https");
+                let base_path = self
+                    .root
+                    .get("basePath")
+                    .and_then(Value::as_str)
+                    .unwrap_or("");
+                match self.root.get("host").and_then(Value::as_str) {
+                    Some(host) => format!("{scheme}://{host}{base_path}"),
+                    None => {
+                        self.summary
+                            .warn("host", "spec has no host, base url left relative");
+                        base_path.to_owned()
+                    }
+                }
+            }
+            Version::OpenApi3 => {
+                let Some(server) = self.root.pointer("/servers/0") else {
+                    self.summary
+                        .warn("servers", "spec has no servers, base url left empty");
+                    return String::new();
+                };
+                let mut url = server
+                    .get("url")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_owned();
+                // server variables are filled in with their defaults
+                if let Some(variables) = server.get("variables").and_then(Value::as_object) {
+                    for (name, variable) in variables {
+                        let default = variable.get("default").map(value_to_string);
+                        url = url.replace(&format!("{{{name}}}"), &default.unwrap_or_default());
+                    }
+                }
+                if url.starts_with('/') {
+                    self.summary.warn(
+                        "servers",
+                        "server url is relative, set the host on the collection",
+                    );
+                }
+                url
+            }
+        }
+    }
+
+    fn request(
+        &mut self,
+        path: &str,
+        method: &str,
+        operation: &'a Value,
+        shared_params: Option<&'a Value>,
+    ) -> Option<RequestState> {
+        let location = &format!("{} {path}", method.to_uppercase());
+        let Some(http_method) = HttpMethod::from_name(method) else {
+            self.summary.warn(
+                location,
+                format!("method '{method}' is not supported, skipped"),
+            );
+            return None;
+        };
+
+        let name = operation
+            .get("summary")
+            .or_else(|| operation.get("operationId"))
+            .and_then(Value::as_str)
+            .map(str::to_owned)
+            .unwrap_or_else(|| location.to_owned());
+
+        let mut url = path.to_owned();
+        let mut query = vec![];
+        let mut headers = vec![];
+        let mut form = vec![];
+        let mut body = String::new();
+
+        for param in self.parameters(shared_params, operation.get("parameters")) {
+            let Some(param_name) = param.get("name").and_then(Value::as_str) else {
+                continue;
+            };
+            let required = param.get("required").and_then(Value::as_bool) == Some(true);
+            let example = self.param_example(param);
+
+            match param.get("in").and_then(Value::as_str).unwrap_or_default() {
+                "path" => {
+                    let variable = self.variable(param_name, example);
+                    url = url.replace(&format!("{{{param_name}}}"), &variable);
+                }
+                "query" => query.push(Param {
+                    enabled: required,
+                    key: param_name.to_owned(),
+                    value: example,
+                    file: false,
+                }),
+                "header" => {
+                    // these are described by other parts of the spec and get ignored in params
+                    if !["accept", "content-type", "authorization"]
+                        .contains(&param_name.to_lowercase().as_str())
+                    {
+                        headers.push(Param {
+                            enabled: required,
+                            key: param_name.to_owned(),
+                            value: example,
+                            file: false,
+                        });
+                    }
+                }
+                "formData" => form.push((param_name.to_owned(), example)),
+                "body" => {
+                    let schema = param.get("schema").unwrap_or(&Value::Null);
+                    body = pretty_json(&self.schema_example(schema, &mut vec![]));
+                    set_default_header(
+                        &mut headers,
+                        "Content-Type",
+                        self.swagger_consumes(operation, "application/json"),
+                    );
+                }
+                "cookie" => self.summary.warn(
+                    location,
+                    format!("cookie parameter '{param_name}' not imported"),
+                ),
+                other => self.summary.warn(
+                    location,
+                    format!("parameter '{param_name}' in '{other}' not imported"),
+                ),
+            }
+        }
+
+        if !form.is_empty() {
+            let content_type =
+                self.swagger_consumes(operation, "application/x-www-form-urlencoded");
+            if content_type.starts_with("multipart/") {
+                self.summary
+                    .warn(location, "multipart form body not imported");
+            } else {
+                body = serde_urlencoded::to_string(&form).unwrap_or_default();
+                set_default_header(&mut headers, "Content-Type", content_type);
+            }
+        }
+
+        if let Some(request_body) = operation.get("requestBody") {
+            let request_body = self.resolve(request_body);
+            if let Some((content_type, media)) = pick_media_type(request_body.get("content")) {
+                body = self.media_example(content_type, media, location);
+                set_default_header(&mut headers, "Content-Type", content_type);
+            }
+        }
+
+        let auth = match operation.get("security") {
+            Some(security) => self.auth(security, location),
+            None => RequestAuth::Inherit,
+        };
+
+        let state = RequestState {
+            name,
+            url,
+            method: http_method,
+            query,
+            headers,
+            body,
+            auth,
+            ..Default::default()
+        };
+        let key = format!("openapi:{}:{location}", self.title);
+        Some(RequestState {
+            origin: Some(Box::new(ImportOrigin::of(key, &state))),
+            ..state
+        })
+    }
+
+    /// Path level parameters overridden by operation level ones with the same name and location
+    fn parameters(&self, shared: Option<&'a Value>, own: Option<&'a Value>) -> Vec<&'a Value> {
+        let mut params: Vec<&Value> = vec![];
+        let all = [shared, own]
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_array)
+            .flatten()
+            .map(|param| self.resolve(param));
+        for param in all {
+            let same = |other: &&Value| {
+                other.get("name") == param.get("name") && other.get("in") == param.get("in")
+            };
+            match params.iter().position(same) {
+                Some(index) => params[index] = param,
+                None => params.push(param),
+            }
+        }
+        params
+    }
+
+    /// Explicit examples only, generated placeholders would just be noise in the query
+    fn param_example(&self, param: &Value) -> String {
+        let schema = param.get("schema").map(|schema| self.resolve(schema));
+        let first_example = param
+            .get("examples")
+            .and_then(Value::as_object)
+            .and_then(|examples| examples.values().next())
+            .map(|example| self.resolve(example))
+            .and_then(|example| example.get("value"));
+
+        // swagger 2 keeps type, default and enum on the parameter itself
+        [
+            param.get("example"),
+            first_example,
+            param.get("x-example"),
+            schema.and_then(|schema| schema.get("example")),
+            schema.and_then(|schema| schema.get("default")),
+            schema.and_then(|schema| schema.pointer("/enum/0")),
+            param.get("default"),
+            param.pointer("/enum/0"),
+        ]
+        .into_iter()
+        .flatten()
+        .next()
+        .map(value_to_string)
+        .unwrap_or_default()
+    }
+
+    fn media_example(&mut self, content_type: &str, media: &'a Value, location: &str) -> String {
+        let example = media.get("example").cloned().or_else(|| {
+            media
+                .get("examples")
+                .and_then(Value::as_object)
+                .and_then(|examples| examples.values().next())
+                .map(|example| self.resolve(example))
+                .and_then(|example| example.get("value"))
+                .cloned()
+        });
+        let example = example.unwrap_or_else(|| {
+            let schema = media.get("schema").unwrap_or(&Value::Null);
+            self.schema_example(schema, &mut vec![])
+        });
+
+        if is_json(content_type) {
+            pretty_json(&example)
+        } else if content_type == "application/x-www-form-urlencoded" {
+            let fields = example
+                .as_object()
+                .map(|fields| {
+                    fields
+                        .iter()
+                        .map(|(key, value)| (key.clone(), value_to_string(value)))
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            serde_urlencoded::to_string(fields).unwrap_or_default()
+        } else if let Value::String(example) = example {
+            example
+        } else {
+            self.summary.warn(
+                location,
+                format!("no example for '{content_type}' body, body left empty"),
+            );
+            String::new()
+        }
+    }
+
+    /// Builds an example value from a schema, preferring the examples and defaults it declares
+    fn schema_example(&self, schema: &'a Value, refs: &mut Vec<&'a str>) -> Value {
+        // recursive schemas stop at the first repeated `$ref`
+        let reference = schema.get("$ref").and_then(Value::as_str);
+        if let Some(reference) = reference {
+            if refs.contains(&reference) || refs.len() > MAX_DEPTH {
+                return Value::Null;
+            }
+            refs.push(reference);
+        }
+        let example = self.resolved_schema_example(self.resolve(schema), refs);
+        if reference.is_some() {
+            refs.pop();
+        }
+        example
+    }
+
+    fn resolved_schema_example(&self, schema: &'a Value, refs: &mut Vec<&'a str>) -> Value {
+        let declared = ["example", "default", "const"]
+            .into_iter()
+            .find_map(|key| schema.get(key))
+            .or_else(|| schema.pointer("/enum/0"));
+        if let Some(declared) = declared {
+            return declared.clone();
+        }
+
+        if let Some(all_of) = schema.get("allOf").and_then(Value::as_array) {
+            let mut merged = Map::new();
+            for part in all_of {
+                if let Value::Object(fields) = self.schema_example(part, refs) {
+                    merged.extend(fields);
+                }
+            }
+            return Value::Object(merged);
+        }
+        if let Some(first) = ["oneOf", "anyOf"]
+            .into_iter()
+            .find_map(|key| schema.get(key).and_then(|variants| variants.get(0)))
+        {
+            return self.schema_example(first, refs);
+        }
+
+        // 3.1 allows a list of types, e.g. ["string", "null"]
+        let kind = match schema.get("type") {
+            Some(Value::String(kind)) => Some(kind.as_str()),
+            Some(Value::Array(kinds)) => kinds
+                .iter()
+                .filter_map(Value::as_str)
+                .find(|kind| *kind != "null"),
+            _ => None,
+        };
+        let kind = kind.or_else(|| schema.get("properties").map(|_| "object"));
+
+        match kind {
+            Some("object") => {
+                let fields = schema
+                    .get("properties")
+                    .and_then(Value::as_object)
+                    .map(|properties| {
+                        properties
+                            .iter()
+                            .map(|(name, property)| {
+                                (name.clone(), self.schema_example(property, refs))
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                Value::Object(fields)
+            }
+            Some("array") => {
+                let items = schema.get("items").unwrap_or(&Value::Null);
+                Value::Array(vec![self.schema_example(items, refs)])
+            }
+            Some("string") => Value::from(match schema.get("format").and_then(Value::as_str) {
+                Some("date-time") => "2024-01-01T00:00:00Z",
+                Some("date") => "2024-01-01",
+                Some("uuid") => "00000000-0000-0000-0000-000000000000",
+                Some("email") => "user@example.com",
+                Some("uri" | "url") => "https://example.com",
+                _ => "string",
+            }),
+            Some("integer") => Value::from(0),
+            Some("number") => Value::from(0.0),
+            Some("boolean") => Value::from(true),
+            _ => Value::Null,
+        }
+    }
+
+    fn swagger_consumes(&self, operation: &'a Value, fallback: &'a str) -> &'a str {
+        let consumes = str_array(operation.get("consumes"));
+        consumes
+            .first()
+            .or(self.consumes.first())
+            .copied()
+            .unwrap_or(fallback)
+    }
+
+    /// Maps the first security requirement to an auth, credentials become environment variables
+    fn auth(&mut self, security: &Value, location: &str) -> RequestAuth {
+        // an empty list explicitly means no auth
+        let Some(requirement) = security
+            .as_array()
+            .and_then(|requirements| requirements.first())
+            .and_then(Value::as_object)
+        else {
+            return RequestAuth::None;
+        };
+        let Some(scheme_name) = requirement.keys().next() else {
+            return RequestAuth::None;
+        };
+        if requirement.len() > 1 {
+            self.summary.warn(
+                location,
+                format!(
+                    "only the first of {} required security schemes is used",
+                    requirement.len()
+                ),
+            );
+        }
+
+        let schemes = match self.version {
+            Version::Swagger2 => "/securityDefinitions",
+            Version::OpenApi3 => "/components/securitySchemes",
+        };
+        let Some(scheme) = self
+            .root
+            .pointer(schemes)
+            .and_then(|schemes| schemes.get(scheme_name))
+            .map(|scheme| self.resolve(scheme))
+        else {
+            self.summary.warn(
+                location,
+                format!("security scheme '{scheme_name}' is not defined"),
+            );
+            return RequestAuth::None;
+        };
+
+        let kind = scheme
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let http_scheme = scheme
+            .get("scheme")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_lowercase();
+        match (kind, http_scheme.as_str()) {
+            ("basic", _) | ("http", "basic") => RequestAuth::BasicAuth {
+                username: self.variable("username", String::new()),
+                password: self.variable("password", String::new()),
+            },
+            ("http", "bearer") => RequestAuth::Bearer {
+                token: self.variable(scheme_name, String::new()),
+            },
+            ("apiKey", _) => {
+                let key = scheme
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_owned();
+                let location_kind = match scheme.get("in").and_then(Value::as_str) {
+                    Some("query") => AuthLocation::Query,
+                    Some("header") => AuthLocation::Headers,
+                    other => {
+                        self.summary.warn(
+                            location,
+                            format!(
+                                "api key in '{}' is not supported",
+                                other.unwrap_or_default()
+                            ),
+                        );
+                        return RequestAuth::None;
+                    }
+                };
+                RequestAuth::ApiKey(ApiKeyParams {
+                    key,
+                    value: self.variable(scheme_name, String::new()),
+                    location: location_kind,
+                })
+            }
+            ("oauth2" | "openIdConnect", _) => {
+                self.summary.warn(
+                    location,
+                    format!("'{scheme_name}' is {kind}, imported as a bearer token to fill in"),
+                );
+                RequestAuth::Bearer {
+                    token: self.variable(scheme_name, String::new()),
+                }
+            }
+            _ => {
+                self.summary.warn(
+                    location,
+                    format!(
+                        "security scheme '{scheme_name}' ({kind} {http_scheme}) is not supported"
+                    ),
+                );
+                RequestAuth::None
+            }
+        }
+    }
+}
+
+/// JSON first, then forms, then whatever comes first
+fn pick_media_type(content: Option<&Value>) -> Option<(&str, &Value)> {
+    let content = content?.as_object()?;
+    content
+        .iter()
+        .find(|(content_type, _)| is_json(content_type))
+        .or_else(|| {
+            content
+                .iter()
+                .find(|(content_type, _)| *content_type == "application/x-www-form-urlencoded")
+        })
+        .or_else(|| content.iter().next())
+        .map(|(content_type, media)| (content_type.as_str(), media))
+}
+
+fn is_json(content_type: &str) -> bool {
+    content_type == "application/json" || content_type.ends_with("+json")
+}
+
+fn pretty_json(value: &Value) -> String {
+    if value.is_null() {
+        return String::new();
+    }
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
+
+fn str_array(value: Option<&Value>) -> Vec<&str> {
+    value
+        .and_then(Value::as_array)
+        .map(|values| values.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
@@ -0,0 +1,695 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::{
+    auth::{
+        ApiKeyParams, AuthLocation, ClientAuthentication, GrantType, HawkAlgorithm, HawkParams,
+        JwtAlgorithm, JwtParams, OAuth2Params, RequestAuth, SigV4Params,
+    },
+    body::{BodyKind, BodyOptions},
+    collections::{CollectionItem, Folder},
+    core::{Param, RequestId, RequestState},
+    environment::Environment,
+    http::HttpMethod,
+    import::{ImportError, ImportResult, ImportSummary, set_default_header, value_to_string},
+};
+
+#[derive(serde::Deserialize)]
+struct Collection {
+    info: Info,
+    #[serde(default)]
+    item: Vec<Item>,
+    auth: Option<Auth>,
+    #[serde(default)]
+    variable: Vec<KeyValue>,
+    #[serde(default)]
+    event: Vec<Value>,
+}
+
+#[derive(serde::Deserialize)]
+struct Info {
+    name: String,
+}
+
+/// Either a folder (has `item`) or a request (has `request`)
+#[derive(serde::Deserialize)]
+struct Item {
+    #[serde(default)]
+    name: String,
+    item: Option<Vec<Item>>,
+    request: Option<Request>,
+    auth: Option<Auth>,
+    #[serde(default)]
+    event: Vec<Value>,
+    #[serde(default)]
+    response: Vec<Value>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum Request {
+    Url(String),
+    Full(Box<FullRequest>),
+}
+
+#[derive(serde::Deserialize)]
+struct FullRequest {
+    url: Option<Url>,
+    method: Option<String>,
+    #[serde(default)]
+    header: Option<Value>,
+    body: Option<Body>,
+    auth: Option<Auth>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum Url {
+    Raw(String),
+    Full(UrlObject),
+}
+
+#[derive(serde::Deserialize)]
+struct UrlObject {
+    #[serde(default)]
+    raw: String,
+    protocol: Option<String>,
+    host: Option<Value>,
+    port: Option<String>,
+    path: Option<Value>,
+    #[serde(default)]
+    query: Vec<KeyValue>,
+    #[serde(default)]
+    variable: Vec<KeyValue>,
+}
+
+#[derive(serde::Deserialize)]
+struct Body {
+    #[serde(default)]
+    mode: String,
+    raw: Option<String>,
+    #[serde(default)]
+    urlencoded: Vec<KeyValue>,
+    #[serde(default)]
+    formdata: Vec<KeyValue>,
+    graphql: Option<Value>,
+    options: Option<Value>,
+    #[serde(default)]
+    disabled: bool,
+}
+
+#[derive(serde::Deserialize)]
+struct Auth {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(flatten)]
+    params: HashMap<String, Value>,
+}
+
+#[derive(serde::Deserialize)]
+struct KeyValue {
+    #[serde(default)]
+    key: Option<String>,
+    #[serde(default)]
+    value: Value,
+    #[serde(default)]
+    disabled: bool,
+    #[serde(default)]
+    enabled: Option<bool>,
+    /// `text` or `file` for form data
+    #[serde(default, rename = "type")]
+    kind: Option<String>,
+    /// Path of a form data file, a list when several are picked
+    #[serde(default)]
+    src: Value,
+}
+
+impl KeyValue {
+    fn key(&self) -> String {
+        self.key.clone().unwrap_or_default()
+    }
+
+    fn value(&self) -> String {
+        value_to_string(&self.value)
+    }
+
+    /// Collections mark inactive values with `disabled`, environments with `enabled`
+    fn is_enabled(&self) -> bool {
+        !self.disabled && self.enabled.unwrap_or(true)
+    }
+
+    fn to_param(&self) -> Param {
+        Param {
+            enabled: self.is_enabled(),
+            key: self.key(),
+            value: self.value(),
+            file: false,
+        }
+    }
+}
+
+impl Auth {
+    /// v2.1 stores params as `[{ key, value }]`, v2.0 as a plain object
+    fn param(&self, name: &str) -> String {
+        match self.params.get(&self.kind) {
+            Some(Value::Array(entries)) => entries
+                .iter()
+                .find(|entry| entry.get("key").and_then(Value::as_str) == Some(name))
+                .and_then(|entry| entry.get("value"))
+                .map(value_to_string)
+                .unwrap_or_default(),
+            Some(Value::Object(map)) => map.get(name).map(value_to_string).unwrap_or_default(),
+            _ => String::new(),
+        }
+    }
+}
+
+pub fn is_collection(value: &Value) -> bool {
+    value
+        .pointer("/info/schema")
+        .and_then(Value::as_str)
+        .is_some_and(|schema| schema.contains("getpostman.com") && schema.contains("collection"))
+}
+
+pub fn is_environment(value: &Value) -> bool {
+    value.get("name").is_some_and(Value::is_string)
+        && value.get("values").is_some_and(Value::is_array)
+}
+
+pub fn import_collection(value: Value) -> Result<ImportResult, ImportError> {
+    let collection = serde_json::from_value::<Collection>(value)
+        .map_err(|err| ImportError::Parse(err.to_string()))?;
+
+    let mut result = ImportResult::default();
+    let location = collection.info.name.clone();
+    let mut root = Folder::new(collection.info.name);
+
+    if let Some(auth) = &collection.auth {
+        root.defaults.auth = convert_auth(auth, &location, &mut result.summary);
+    }
+    warn_scripts(&collection.event, &location, &mut result.summary);
+
+    for item in collection.item {
+        convert_item(item, &mut root, &location, &mut result.summary);
+    }
+
+    // there are no collection scoped variables, so they become an environment of their own
+    if !collection.variable.is_empty() {
+        let name = format!("{} variables", root.name);
+        result.summary.warn(
+            &location,
+            format!("collection variables were imported as the environment '{name}'"),
+        );
+        result.environments.push(Environment {
+            name,
+            variables: collection.variable.iter().map(KeyValue::to_param).collect(),
+        });
+    }
+
+    result.collections.push(root);
+    Ok(result)
+}
+
+pub fn import_environment(value: Value) -> Result<ImportResult, ImportError> {
+    let name = value
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_owned();
+    let values = value
+        .get("values")
+        .cloned()
+        .map(serde_json::from_value::<Vec<KeyValue>>)
+        .transpose()
+        .map_err(|err| ImportError::Parse(err.to_string()))?
+        .unwrap_or_default();
+
+    let mut result = ImportResult::default();
+    result.environments.push(Environment {
+        name,
+        variables: values.iter().map(KeyValue::to_param).collect(),
+    });
+    Ok(result)
+}
+
+fn convert_item(item: Item, parent: &mut Folder, location: &str, summary: &mut ImportSummary) {
+    let location = format!("{location}/{}", item.name);
+    warn_scripts(&item.event, &location, summary);
+
+    if let Some(children) = item.item {
+        let mut folder = Folder::new(item.name);
+        if let Some(auth) = &item.auth {
+            folder.defaults.auth = convert_auth(auth, &location, summary);
+        }
+        for child in children {
+            convert_item(child, &mut folder, &location, summary);
+        }
+        summary.folders += 1;
+        parent.items.push(CollectionItem::Folder(Box::new(folder)));
+        return;
+    }
+
+    let Some(request) = item.request else {
+        summary.warn(
+            &location,
+            "item has neither a request nor children, skipped",
+        );
+        return;
+    };
+
+    if !item.response.is_empty() {
+        summary.warn(
+            &location,
+            format!(
+                "{} saved example response(s) not imported",
+                item.response.len()
+            ),
+        );
+    }
+
+    let mut state = convert_request(request, &location, summary);
+    state.name = item.name;
+    summary.requests += 1;
+    parent
+        .items
+        .push(CollectionItem::Request(RequestId::next(), Box::new(state)));
+}
+
+fn convert_request(request: Request, location: &str, summary: &mut ImportSummary) -> RequestState {
+    let request = match request {
+        Request::Url(url) => {
+            let (url, query) = split_raw_url(&url);
+            return RequestState {
+                url,
+                query,
+                ..empty_request()
+            };
+        }
+        Request::Full(request) => *request,
+    };
+
+    let method = match request.method.as_deref() {
+        None => HttpMethod::Get,
+        Some(name) => HttpMethod::from_name(name).unwrap_or_else(|| {
+            summary.warn(
+                location,
+                format!("method '{name}' is not supported, using GET"),
+            );
+            HttpMethod::Get
+        }),
+    };
+
+    let (url, query) = match request.url {
+        None => (String::new(), vec![]),
+        Some(Url::Raw(raw)) => split_raw_url(&raw),
+        Some(Url::Full(url)) => convert_url(url, location, summary),
+    };
+
+    let mut headers = convert_headers(request.header, location, summary);
+
+    let (body, body_options) = match request.body {
+        Some(body) => convert_body(body, &mut headers, location, summary),
+        None => Default::default(),
+    };
+
+    let auth = match &request.auth {
+        Some(auth) => convert_auth(auth, location, summary),
+        // requests without auth inherit the one from their folder
+        None => RequestAuth::Inherit,
+    };
+
+    RequestState {
+        url,
+        method,
+        query,
+        headers,
+        body,
+        body_options,
+        auth,
+        ..empty_request()
+    }
+}
+
+fn empty_request() -> RequestState {
+    RequestState {
+        query: vec![],
+        headers: vec![],
+        ..Default::default()
+    }
+}
+
+/// Splits `http://host/path?a=1` into the url without query and the query params
+fn split_raw_url(raw: &str) -> (String, Vec<Param>) {
+    let raw = raw.split_once('#').map_or(raw, |(url, _)| url);
+    match raw.split_once('?') {
+        Some((url, query)) => (
+            url.to_owned(),
+            url::form_urlencoded::parse(query.as_bytes())
+                .map(|(key, value)| Param::enabled(key.into_owned(), value.into_owned()))
+                .collect(),
+        ),
+        None => (raw.to_owned(), vec![]),
+    }
+}
+
+fn convert_url(
+    url: UrlObject,
+    location: &str,
+    summary: &mut ImportSummary,
+) -> (String, Vec<Param>) {
+    let (mut base, raw_query) = if url.raw.is_empty() {
+        (join_url_parts(&url), vec![])
+    } else {
+        split_raw_url(&url.raw)
+    };
+
+    // postman path variables look like `/users/:id`, they're turned into our `{{id}}` syntax
+    // unless postman already had a value for them
+    for variable in &url.variable {
+        let key = variable.key();
+        let value = variable.value();
+        let replacement = if value.is_empty() {
+            summary.warn(
+                location,
+                format!("path variable ':{key}' has no value, replaced with {{{{{key}}}}}"),
+            );
+            format!("{{{{{key}}}}}")
+        } else {
+            value
+        };
+        base = base
+            .split('/')
+            .map(|segment| {
+                if segment.strip_prefix(':') == Some(key.as_str()) {
+                    replacement.as_str()
+                } else {
+                    segment
+                }
+            })
+            .collect::<Vec<&str>>()
+            .join("/");
+    }
+
+    let query = if url.query.is_empty() {
+        raw_query
+    } else {
+        url.query.iter().map(KeyValue::to_param).collect()
+    };
+    (base, query)
+}
+
+fn join_url_parts(url: &UrlObject) -> String {
+    let join = |value: &Option<Value>, separator: &str| match value {
+        Some(Value::Array(parts)) => parts
+            .iter()
+            .map(value_to_string)
+            .collect::<Vec<String>>()
+            .join(separator),
+        Some(value) => value_to_string(value),
+        None => String::new(),
+    };
+
+    let mut joined = String::new();
+    if let Some(protocol) = &url.protocol {
+        joined.push_str(&format!("{protocol}://"));
+    }
+    joined.push_str(&join(&url.host, "."));
+    if let Some(port) = &url.port {
+        joined.push_str(&format!(":{port}"));
+    }
+    let path = join(&url.path, "/");
+    if !path.is_empty() {
+        joined.push('/');
+        joined.push_str(path.trim_start_matches('/'));
+    }
+    joined
+}
+
+fn convert_headers(
+    header: Option<Value>,
+    location: &str,
+    summary: &mut ImportSummary,
+) -> Vec<Param> {
+    match header {
+        None | Some(Value::Null) => vec![],
+        // headers can also be a single "Name: value\nName: value" string
+        Some(Value::String(raw)) => raw
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .map(|(key, value)| Param::enabled(key.trim().to_owned(), value.trim().to_owned()))
+            .collect(),
+        Some(value) => match serde_json::from_value::<Vec<KeyValue>>(value) {
+            Ok(headers) => headers.iter().map(KeyValue::to_param).collect(),
+            Err(err) => {
+                summary.warn(location, format!("headers could not be read: {err}"));
+                vec![]
+            }
+        },
+    }
+}
+
+fn convert_body(
+    body: Body,
+    headers: &mut Vec<Param>,
+    location: &str,
+    summary: &mut ImportSummary,
+) -> (String, BodyOptions) {
+    if body.disabled {
+        summary.warn(location, "body is disabled, not imported");
+        return Default::default();
+    }
+
+    let text = match body.mode.as_str() {
+        "raw" => {
+            let language = body
+                .options
+                .as_ref()
+                .and_then(|options| options.pointer("/raw/language"))
+                .and_then(Value::as_str);
+            let content_type = match language {
+                Some("json") => Some("application/json"),
+                Some("xml") => Some("application/xml"),
+                Some("html") => Some("text/html"),
+                Some("javascript") => Some("application/javascript"),
+                Some("text") => Some("text/plain"),
+                _ => None,
+            };
+            // postman adds the content type implicitly for raw bodies
+            if let Some(content_type) = content_type {
+                set_default_header(headers, "Content-Type", content_type);
+            }
+            body.raw.unwrap_or_default()
+        }
+        // the content type of forms comes with the body kind
+        "urlencoded" => {
+            let form = body.urlencoded.iter().map(KeyValue::to_param).collect();
+            return form_body(BodyKind::FormUrlEncoded, form);
+        }
+        "formdata" => {
+            let form = body
+                .formdata
+                .iter()
+                .map(|kv| convert_form_part(kv, location, summary))
+                .collect();
+            return form_body(BodyKind::Multipart, form);
+        }
+        "graphql" => {
+            let graphql = body.graphql.unwrap_or_default();
+            let field = |name| graphql.get(name).map(value_to_string).unwrap_or_default();
+            let options = BodyOptions {
+                kind: BodyKind::GraphQl,
+                graphql_variables: field("variables"),
+                ..Default::default()
+            };
+            return (field("query"), options);
+        }
+        "" => String::new(),
+        mode => {
+            summary.warn(
+                location,
+                format!("body mode '{mode}' is not supported, body not imported"),
+            );
+            String::new()
+        }
+    };
+    (text, BodyOptions::default())
+}
+
+fn form_body(kind: BodyKind, form: Vec<Param>) -> (String, BodyOptions) {
+    let options = BodyOptions {
+        kind,
+        form,
+        ..Default::default()
+    };
+    (String::new(), options)
+}
+
+fn convert_form_part(kv: &KeyValue, location: &str, summary: &mut ImportSummary) -> Param {
+    if kv.kind.as_deref() != Some("file") {
+        return kv.to_param();
+    }
+    let path = match &kv.src {
+        Value::Array(paths) => {
+            if paths.len() > 1 {
+                summary.warn(
+                    location,
+                    format!(
+                        "form part '{}' has several files, only the first is imported",
+                        kv.key()
+                    ),
+                );
+            }
+            paths.first().map(value_to_string).unwrap_or_default()
+        }
+        src => value_to_string(src),
+    };
+    Param {
+        value: path,
+        file: true,
+        ..kv.to_param()
+    }
+}
+
+fn convert_auth(auth: &Auth, location: &str, summary: &mut ImportSummary) -> RequestAuth {
+    match auth.kind.as_str() {
+        "noauth" => RequestAuth::None,
+        "inherit" => RequestAuth::Inherit,
+        "basic" => RequestAuth::BasicAuth {
+            username: auth.param("username"),
+            password: auth.param("password"),
+        },
+        "bearer" => RequestAuth::Bearer {
+            token: auth.param("token"),
+        },
+        "apikey" => RequestAuth::ApiKey(ApiKeyParams {
+            key: auth.param("key"),
+            value: auth.param("value"),
+            location: match auth.param("in").as_str() {
+                "query" => AuthLocation::Query,
+                _ => AuthLocation::Headers,
+            },
+        }),
+        "awsv4" => RequestAuth::AwsSigV4(SigV4Params {
+            access_key: auth.param("accessKey"),
+            secret_key: auth.param("secretKey"),
+            session_token: auth.param("sessionToken"),
+            service: auth.param("service"),
+            region: auth.param("region"),
+            ..Default::default()
+        }),
+        "oauth2" => RequestAuth::OAuth2(convert_oauth2(auth, location, summary)),
+        "digest" => RequestAuth::Digest {
+            username: auth.param("username"),
+            password: auth.param("password"),
+        },
+        "hawk" => RequestAuth::Hawk(HawkParams {
+            id: auth.param("authId"),
+            key: auth.param("authKey"),
+            algorithm: match auth.param("algorithm").to_lowercase().as_str() {
+                "sha1" => HawkAlgorithm::Sha1,
+                _ => HawkAlgorithm::Sha256,
+            },
+            ext: auth.param("extraData"),
+            include_payload_hash: auth.param("includePayloadHash") == "true",
+        }),
+        "jwt" => RequestAuth::Jwt(convert_jwt(auth, location, summary)),
+        kind => {
+            summary.warn(
+                location,
+                format!("auth type '{kind}' is not supported, no auth is used"),
+            );
+            RequestAuth::None
+        }
+    }
+}
+
+fn convert_jwt(auth: &Auth, location: &str, summary: &mut ImportSummary) -> JwtParams {
+    let algorithm = match auth.param("algorithm").as_str() {
+        "RS256" => JwtAlgorithm::Rs256,
+        "ES256" => JwtAlgorithm::Es256,
+        "HS256" | "" => JwtAlgorithm::Hs256,
+        algorithm => {
+            summary.warn(
+                location,
+                format!("JWT algorithm '{algorithm}' is not supported, HS256 is used"),
+            );
+            JwtAlgorithm::Hs256
+        }
+    };
+    if auth.param("addTokenTo") == "queryParam" {
+        summary.warn(
+            location,
+            "the JWT is sent in the Authorization header, not the query",
+        );
+    }
+    let defaults = JwtParams::default();
+    JwtParams {
+        algorithm,
+        secret: match algorithm {
+            JwtAlgorithm::Hs256 => auth.param("secret"),
+            JwtAlgorithm::Rs256 | JwtAlgorithm::Es256 => auth.param("privateKey"),
+        },
+        secret_base64: auth.param("isSecretBase64Encoded") == "true",
+        header: auth.param("header"),
+        claims: auth.param("payload"),
+        // postman leaves exp to the payload
+        expires_in: 0,
+        prefix: match auth.param("headerPrefix") {
+            prefix if prefix.is_empty() => defaults.prefix,
+            prefix => prefix,
+        },
+    }
+}
+
+fn convert_oauth2(auth: &Auth, location: &str, summary: &mut ImportSummary) -> OAuth2Params {
+    let grant_type = match auth.param("grant_type").as_str() {
+        "password_credentials" => GrantType::Password,
+        "authorization_code" | "authorization_code_with_pkce" => GrantType::AuthorizationCode,
+        "client_credentials" => GrantType::ClientCredentials,
+        // the default when postman leaves it out
+        "" => GrantType::AuthorizationCode,
+        grant_type => {
+            summary.warn(
+                location,
+                format!(
+                    "OAuth 2.0 grant '{grant_type}' is not supported, client credentials is used"
+                ),
+            );
+            GrantType::ClientCredentials
+        }
+    };
+    let defaults = OAuth2Params::default();
+    let redirect_uri = auth.param("redirect_uri");
+    OAuth2Params {
+        grant_type,
+        token_url: auth.param("accessTokenUrl"),
+        auth_url: auth.param("authUrl"),
+        redirect_uri: if redirect_uri.is_empty() {
+            defaults.redirect_uri
+        } else {
+            redirect_uri
+        },
+        pkce: auth.param("grant_type") != "authorization_code",
+        client_id: auth.param("clientId"),
+        client_secret: auth.param("clientSecret"),
+        client_authentication: match auth.param("client_authentication").as_str() {
+            "body" => ClientAuthentication::Body,
+            _ => ClientAuthentication::BasicHeader,
+        },
+        scope: auth.param("scope"),
+        username: auth.param("username"),
+        password: auth.param("password"),
+        refresh_token: String::new(),
+    }
+}
+
+fn warn_scripts(events: &[Value], location: &str, summary: &mut ImportSummary) {
+    for event in events {
+        let listen = event
+            .get("listen")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown");
+        summary.warn(location, format!("'{listen}' script not imported"));
+    }
+}
@@ -1,14 +1,42 @@
 #![warn(clippy::all, rust_2018_idioms)]
+// without the GUI only what the cli uses is reachable
+#![cfg_attr(not(feature = "gui"), allow(dead_code, unused_imports))]
+#[cfg(feature = "gui")]
 mod app;
+mod assertions;
 pub mod async_runtime;
 mod auth;
+mod body;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cli;
+mod client;
+mod codegen;
+mod collections;
+#[cfg(feature = "gui")]
 mod components;
 pub mod cookies;
 mod core;
+mod curl;
+mod environment;
+mod graphql;
+mod grpc;
+#[cfg(feature = "gui")]
 mod header;
+mod history;
 mod http;
+mod import;
+mod runner;
+mod script;
+mod stream;
+#[cfg(feature = "gui")]
 mod styles;
 pub mod tasks;
+#[cfg(feature = "gui")]
 mod tiles;
+mod timing;
+mod util;
+mod websocket;
+#[cfg(feature = "gui")]
 pub use app::App;
+#[cfg(feature = "gui")]
 pub use styles::customize_app_styles;
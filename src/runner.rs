@@ -0,0 +1,445 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use web_time::Instant;
+
+use crate::{
+    assertions::AssertionResult,
+    async_runtime,
+    collections::{Collections, FolderId, RequestDefaults},
+    core::{RequestId, RequestKind, RequestState},
+    environment::Variables,
+    grpc::DescriptorStatus,
+    http::HttpError,
+    tasks::{self, GlobalContext, RequestContext},
+};
+
+/// How a run goes through the requests
+#[derive(Clone, PartialEq)]
+pub struct RunOptions {
+    pub iterations: usize,
+    /// Wait between two requests, in milliseconds
+    pub delay: u64,
+    pub stop_on_failure: bool,
+    /// CSV with a header row or a JSON array of objects, see `parse_data`
+    pub data: String,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        Self {
+            iterations: 1,
+            delay: 0,
+            stop_on_failure: false,
+            data: String::new(),
+        }
+    }
+}
+
+/// Variables of one iteration, taken from a row of the data file
+pub type DataRow = Vec<(String, String)>;
+
+/// How one request of a run went
+#[derive(Clone)]
+pub struct RequestOutcome {
+    /// Starts at 0
+    pub iteration: usize,
+    pub request_id: RequestId,
+    pub name: String,
+    pub result: Result<Vec<AssertionResult>, HttpError>,
+    /// From building the request to having evaluated the assertions
+    pub duration: Duration,
+}
+
+impl RequestOutcome {
+    /// A request that got no response fails as well
+    pub fn passed(&self) -> bool {
+        self.result
+            .as_ref()
+            .is_ok_and(|results| results.iter().all(|result| result.passed))
+    }
+}
+
+/// Where a run is at, updated by the run as it goes
+#[derive(Default)]
+pub struct RunProgress {
+    pub outcomes: Vec<RequestOutcome>,
+    /// Request being sent, with its iteration
+    pub current: Option<(usize, String)>,
+    pub finished: bool,
+    /// Set to have the run stop before its next request
+    pub stop_requested: bool,
+}
+
+/// The requests of a folder sent one after the other, `iterations` times over
+pub struct FolderRun {
+    pub name: String,
+    pub requests: usize,
+    /// WebSocket requests of the folder, left out as a run can't hold a connection open
+    pub websockets: usize,
+    pub iterations: usize,
+    pub progress: Arc<Mutex<RunProgress>>,
+}
+
+impl FolderRun {
+    /// Sends every request of the folder and its subfolders, in the order they're listed.
+    /// The requests are copied as they are now, edits made while it runs don't affect it
+    pub fn start(
+        folder_id: FolderId,
+        options: &RunOptions,
+        collections: &Collections,
+        context: &RequestContext,
+        global_context: &GlobalContext,
+    ) -> Result<Self, String> {
+        let folder = collections
+            .find_folder(folder_id)
+            .ok_or("the folder no longer exists")?;
+        let rows = parse_data(&options.data)?;
+
+        let mut request_ids = vec![];
+        folder.request_ids(&mut request_ids);
+        let (websockets, requests): (Vec<_>, Vec<_>) = request_ids
+            .into_iter()
+            .filter_map(|request_id| {
+                let state = collections.find_request(request_id)?.clone();
+                Some((request_id, state, collections.defaults_for(request_id)))
+            })
+            .partition(|(_, state, _)| state.kind == RequestKind::WebSocket);
+        let descriptors = load_descriptors(&requests, context, global_context);
+
+        let run = FolderRun {
+            name: folder.name.clone(),
+            requests: requests.len(),
+            websockets: websockets.len(),
+            iterations: options.iterations.max(1),
+            progress: Default::default(),
+        };
+        let sequence = RunSequence {
+            requests,
+            descriptors,
+            rows,
+            iterations: run.iterations,
+            delay: Duration::from_millis(options.delay),
+            stop_on_failure: options.stop_on_failure,
+            context: context.clone(),
+            global_context: global_context.clone(),
+            progress: run.progress.clone(),
+        };
+        global_context.async_runtime.spawn_future(sequence.run());
+        Ok(run)
+    }
+
+    pub fn total(&self) -> usize {
+        self.requests * self.iterations
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.progress.lock().unwrap().finished
+    }
+
+    pub fn stop(&self) {
+        self.progress.lock().unwrap().stop_requested = true;
+    }
+}
+
+/// The app loads them from the Body tab, which a run can't count on having been opened.
+/// Starts loading what's missing for the gRPC requests, returns the keys to wait for
+fn load_descriptors(
+    requests: &[(RequestId, RequestState, RequestDefaults)],
+    context: &RequestContext,
+    global_context: &GlobalContext,
+) -> Vec<String> {
+    let mut keys = vec![];
+    for (_, state, defaults) in requests {
+        if state.kind != RequestKind::Grpc {
+            continue;
+        }
+        let context = RequestContext {
+            defaults: defaults.clone(),
+            ..context.clone()
+        };
+        let key = state.grpc.cache_key(&context.resolve_url(&state.url));
+        let cached = global_context.grpc_descriptors.get(&key);
+        if cached.pool.is_some() || keys.contains(&key) {
+            continue;
+        }
+        // the Body tab may have started it already
+        if !matches!(cached.status, DescriptorStatus::Loading) {
+            tasks::load_grpc_descriptors(state, &context, global_context);
+        }
+        keys.push(key);
+    }
+    keys
+}
+
+/// Everything the run needs, moved into its future
+struct RunSequence {
+    requests: Vec<(RequestId, RequestState, RequestDefaults)>,
+    /// Cache keys of the gRPC descriptors loading for the run
+    descriptors: Vec<String>,
+    rows: Vec<DataRow>,
+    iterations: usize,
+    delay: Duration,
+    stop_on_failure: bool,
+    context: RequestContext,
+    global_context: GlobalContext,
+    progress: Arc<Mutex<RunProgress>>,
+}
+
+impl RunSequence {
+    async fn run(self) {
+        let cache = &self.global_context.grpc_descriptors;
+        let loading = |key: &String| matches!(cache.get(key).status, DescriptorStatus::Loading);
+        while self.descriptors.iter().any(loading) {
+            async_runtime::sleep(Duration::from_millis(50)).await;
+        }
+
+        // variables set by scripts carry over to the following requests and iterations
+        let mut variables = self.context.variables.clone();
+        let mut first = true;
+
+        'run: for iteration in 0..self.iterations {
+            // rows are used in turn, starting over when there are more iterations than rows
+            if !self.rows.is_empty() {
+                for (key, value) in &self.rows[iteration % self.rows.len()] {
+                    variables.set(key, value);
+                }
+            }
+
+            for (request_id, state, defaults) in &self.requests {
+                if !first && !self.delay.is_zero() {
+                    async_runtime::sleep(self.delay).await;
+                }
+                first = false;
+                {
+                    let mut progress = self.progress.lock().unwrap();
+                    if progress.stop_requested {
+                        break 'run;
+                    }
+                    progress.current = Some((iteration, state.label()));
+                }
+
+                let started = Instant::now();
+                let (result, updated) = self.send(state, defaults, variables.clone()).await;
+                variables = updated;
+                let outcome = RequestOutcome {
+                    iteration,
+                    request_id: *request_id,
+                    name: state.label(),
+                    result,
+                    duration: started.elapsed(),
+                };
+                let failed = !outcome.passed();
+                self.progress.lock().unwrap().outcomes.push(outcome);
+                if failed && self.stop_on_failure {
+                    break 'run;
+                }
+            }
+        }
+
+        let mut progress = self.progress.lock().unwrap();
+        progress.current = None;
+        progress.finished = true;
+    }
+
+    async fn send(
+        &self,
+        state: &RequestState,
+        defaults: &RequestDefaults,
+        variables: Variables,
+    ) -> (Result<Vec<AssertionResult>, HttpError>, Variables) {
+        let context = RequestContext {
+            variables: variables.clone(),
+            defaults: defaults.clone(),
+            settings: self.context.settings.clone(),
+        };
+        match tasks::prepare_execution(state, &context, &self.global_context) {
+            Ok(execution) => execution.send().await,
+            Err(err) => (Err(err), variables),
+        }
+    }
+}
+
+/// Rows of a data file, a CSV with a header row naming the variables or a JSON array
+/// of objects. Empty input gives no rows
+pub fn parse_data(input: &str) -> Result<Vec<DataRow>, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(vec![]);
+    }
+    if input.starts_with('[') {
+        parse_json_data(input)
+    } else {
+        parse_csv_data(input)
+    }
+}
+
+fn parse_json_data(input: &str) -> Result<Vec<DataRow>, String> {
+    use serde_json::Value;
+
+    let rows = serde_json::from_str::<Vec<serde_json::Map<String, Value>>>(input)
+        .map_err(|err| format!("expected an array of objects: {err}"))?;
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|(key, value)| {
+                    let value = match value {
+                        Value::String(value) => value,
+                        Value::Null => String::new(),
+                        value => value.to_string(),
+                    };
+                    (key, value)
+                })
+                .collect()
+        })
+        .collect())
+}
+
+fn parse_csv_data(input: &str) -> Result<Vec<DataRow>, String> {
+    let mut records = parse_csv(input)?.into_iter();
+    let header = records.next().unwrap_or_default();
+    records
+        .enumerate()
+        .filter(|(_, record)| record.iter().any(|field| !field.is_empty()))
+        .map(|(index, record)| {
+            if record.len() != header.len() {
+                return Err(format!(
+                    "row {} has {} values, the header has {}",
+                    index + 2,
+                    record.len(),
+                    header.len()
+                ));
+            }
+            Ok(header
+                .iter()
+                .map(|name| name.trim().to_owned())
+                .zip(record)
+                .collect())
+        })
+        .collect()
+}
+
+/// Splits CSV into records, fields can be quoted to hold commas, quotes as `""` and newlines
+fn parse_csv(input: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut records = vec![];
+    let mut record = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, c) => field.push(c),
+            (false, '"') if field.is_empty() => quoted = true,
+            (false, ',') => record.push(std::mem::take(&mut field)),
+            (false, '\r') if chars.peek() == Some(&'\n') => {}
+            (false, '\n') => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            (false, c) => field.push(c),
+        }
+    }
+    if quoted {
+        return Err("a quoted value isn't closed".to_owned());
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        client::Clients,
+        collections::Folder,
+        cookies::BasicCookieStore,
+        grpc::{DescriptorSource, GrpcOptions},
+    };
+
+    const PROTO: &str = r#"
+syntax = "proto3";
+package helloworld;
+
+service Greeter {
+  rpc SayHello (HelloRequest) returns (HelloReply);
+}
+
+message HelloRequest { string name = 1; }
+message HelloReply { string message = 1; }
+"#;
+
+    #[test]
+    fn run_loads_grpc_descriptors() {
+        let dir = std::env::temp_dir().join(format!("restctl-run-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let proto = dir.join("greeter.proto");
+        std::fs::write(&proto, PROTO).unwrap();
+
+        let mut collections = Collections {
+            items: vec![Folder::new("Run".to_owned())],
+        };
+        let folder_id = collections.items[0].id;
+        let state = RequestState {
+            kind: RequestKind::Grpc,
+            // nothing listens there, the call itself fails
+            url: "http://127.0.0.1:9".to_owned(),
+            body: r#"{"name": "a"}"#.to_owned(),
+            grpc: GrpcOptions {
+                source: DescriptorSource::ProtoFiles,
+                proto_files: vec![proto.display().to_string()],
+                service: "helloworld.Greeter".to_owned(),
+                method: "SayHello".to_owned(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        collections.add_request(folder_id, state.clone()).unwrap();
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let cookie_jar = Arc::new(BasicCookieStore::new());
+        let global_context = GlobalContext {
+            cookie_jar: cookie_jar.clone(),
+            clients: Clients::new(cookie_jar),
+            async_runtime: async_runtime::AsyncRuntimeHandle::new_native(runtime.handle().clone()),
+            history: Default::default(),
+            script_variables: Default::default(),
+            oauth_tokens: Default::default(),
+            graphql_schemas: Default::default(),
+            grpc_descriptors: Default::default(),
+        };
+        let context = RequestContext::default();
+        let run = FolderRun::start(
+            folder_id,
+            &RunOptions::default(),
+            &collections,
+            &context,
+            &global_context,
+        )
+        .unwrap();
+
+        let started = Instant::now();
+        while !run.is_finished() {
+            assert!(started.elapsed() < Duration::from_secs(10), "the run hung");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let key = state.grpc.cache_key(&context.resolve_url(&state.url));
+        assert!(global_context.grpc_descriptors.get(&key).pool.is_some());
+        let outcomes = run.progress.lock().unwrap().outcomes.clone();
+        assert_eq!(outcomes.len(), 1);
+        let err = outcomes[0].result.as_ref().err().unwrap().to_string();
+        assert!(!err.contains("aren't loaded"), "{err}");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,402 @@
+use std::{cell::RefCell, rc::Rc};
+
+use base64::Engine as _;
+use hmac::{Hmac, Mac};
+use rhai::{Dynamic, Engine, EvalAltResult, Scope};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    core::{Param, RequestState},
+    environment::Variables,
+    history::now_millis,
+    http::{HttpMethod, HttpResponse},
+    util,
+};
+
+/// Rhai scripts of a request
+#[derive(Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Scripts {
+    /// Runs before the request is built, can change it and set variables
+    pub pre_request: String,
+    /// Runs once a response arrives, typically to pull values out into variables
+    pub post_response: String,
+}
+
+/// What the last run of the scripts left behind
+#[derive(Default)]
+pub struct ScriptOutput {
+    pub console: Vec<String>,
+}
+
+/// Variables set by a script, to be saved into the active environment
+pub type VariableUpdates = Vec<(String, String)>;
+
+/// The request as scripts see it, `{{name}}` variables aren't substituted yet
+#[derive(Clone)]
+struct ScriptRequest {
+    method: String,
+    url: String,
+    query: Vec<Param>,
+    headers: Vec<Param>,
+    body: String,
+}
+
+impl ScriptRequest {
+    fn header(&mut self, name: &str) -> Dynamic {
+        find_param(&self.headers, name)
+    }
+
+    fn set_header(&mut self, name: &str, value: Dynamic) {
+        set_param(&mut self.headers, name, value.to_string());
+    }
+
+    fn remove_header(&mut self, name: &str) {
+        self.headers.retain(|h| !h.key.eq_ignore_ascii_case(name));
+    }
+
+    fn query(&mut self, name: &str) -> Dynamic {
+        find_param(&self.query, name)
+    }
+
+    fn set_query(&mut self, name: &str, value: Dynamic) {
+        set_param(&mut self.query, name, value.to_string());
+    }
+
+    fn remove_query(&mut self, name: &str) {
+        self.query.retain(|q| !q.key.eq_ignore_ascii_case(name));
+    }
+}
+
+#[derive(Clone)]
+struct ScriptResponse {
+    status: i64,
+    status_text: String,
+    headers: Vec<(String, String)>,
+    body: String,
+    time: i64,
+}
+
+impl ScriptResponse {
+    fn header(&mut self, name: &str) -> Dynamic {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map_or(Dynamic::UNIT, |(_, value)| value.clone().into())
+    }
+
+    /// Body parsed as JSON, unit if it isn't JSON
+    fn json(&mut self) -> Dynamic {
+        serde_json::from_str(&self.body)
+            .map(json_to_dynamic)
+            .unwrap_or(Dynamic::UNIT)
+    }
+}
+
+#[derive(Clone)]
+struct ScriptVariables {
+    values: Variables,
+    updates: VariableUpdates,
+}
+
+impl ScriptVariables {
+    fn get(&mut self, name: &str) -> Dynamic {
+        self.values
+            .get(name)
+            .map_or(Dynamic::UNIT, |value| value.to_owned().into())
+    }
+
+    fn set(&mut self, name: &str, value: Dynamic) {
+        let value = value.to_string();
+        self.values.set(name, &value);
+        self.updates.push((name.to_owned(), value));
+    }
+
+    fn interpolate(&mut self, text: &str) -> String {
+        self.values.interpolate(text)
+    }
+}
+
+/// Runs the pre-request script of `state`. Returns the request to send with the script's
+/// changes, `variables` gets the ones it set so they apply to this request already
+pub fn run_pre_request(
+    state: &RequestState,
+    variables: &mut Variables,
+    console: &mut Vec<String>,
+) -> Result<(RequestState, VariableUpdates), String> {
+    let mut scope = Scope::new();
+    scope.push(
+        "request",
+        ScriptRequest {
+            method: state.method.to_string(),
+            url: state.url.clone(),
+            query: state.query.clone(),
+            headers: state.headers.clone(),
+            body: state.body.clone(),
+        },
+    );
+    scope.push(
+        "vars",
+        ScriptVariables {
+            values: variables.clone(),
+            updates: vec![],
+        },
+    );
+
+    run(&state.scripts.pre_request, &mut scope, console)?;
+
+    let request = scope
+        .get_value::<ScriptRequest>("request")
+        .ok_or("`request` was replaced by the script")?;
+    let vars = scope
+        .get_value::<ScriptVariables>("vars")
+        .ok_or("`vars` was replaced by the script")?;
+    let method = HttpMethod::from_name(&request.method)
+        .ok_or_else(|| format!("unknown method '{}'", request.method))?;
+
+    *variables = vars.values;
+    // what the script can't touch stays as it is
+    let mut state = state.clone();
+    state.method = method;
+    state.url = request.url;
+    state.query = request.query;
+    state.headers = request.headers;
+    state.body = request.body;
+    Ok((state, vars.updates))
+}
+
+/// Runs a post-response script, returns the variables it set
+pub fn run_post_response(
+    script: &str,
+    response: &HttpResponse,
+    variables: Variables,
+    console: &mut Vec<String>,
+) -> Result<VariableUpdates, String> {
+    let mut scope = Scope::new();
+    scope.push(
+        "response",
+        ScriptResponse {
+            status: response.status.into(),
+            status_text: response.status_text.clone(),
+            headers: response.headers.clone(),
+            body: response.body_raw.clone(),
+            time: response.duration.as_millis() as i64,
+        },
+    );
+    scope.push(
+        "vars",
+        ScriptVariables {
+            values: variables,
+            updates: vec![],
+        },
+    );
+
+    run(script, &mut scope, console)?;
+
+    Ok(scope
+        .get_value::<ScriptVariables>("vars")
+        .map(|vars| vars.updates)
+        .unwrap_or_default())
+}
+
+fn run(script: &str, scope: &mut Scope<'_>, console: &mut Vec<String>) -> Result<(), String> {
+    let output = Rc::new(RefCell::new(vec![]));
+    let engine = engine(output.clone());
+    let result = engine.run_with_scope(scope, script);
+    console.append(&mut output.borrow_mut());
+    result.map_err(|err| {
+        let message = format!("error: {err}");
+        console.push(message.clone());
+        message
+    })
+}
+
+/// Engine with the request API and nothing that reaches outside of it, no file access,
+/// no module imports, and bounded in how long it runs and how much it allocates
+fn engine(console: Rc<RefCell<Vec<String>>>) -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new())
+        .set_max_operations(1_000_000)
+        .set_max_call_levels(64)
+        .set_max_expr_depths(64, 32)
+        .set_max_string_size(10 * 1024 * 1024)
+        .set_max_array_size(100_000)
+        .set_max_map_size(100_000);
+    engine.disable_symbol("eval");
+
+    let print_console = console.clone();
+    engine.on_print(move |text| print_console.borrow_mut().push(text.to_owned()));
+    engine.on_debug(move |text, _, position| {
+        console
+            .borrow_mut()
+            .push(format!("[debug {position}] {text}"));
+    });
+
+    engine
+        .register_type_with_name::<ScriptRequest>("Request")
+        .register_get_set(
+            "method",
+            |r: &mut ScriptRequest| r.method.clone(),
+            |r: &mut ScriptRequest, method: String| r.method = method.to_uppercase(),
+        )
+        .register_get_set(
+            "url",
+            |r: &mut ScriptRequest| r.url.clone(),
+            |r: &mut ScriptRequest, url: String| r.url = url,
+        )
+        .register_get_set(
+            "body",
+            |r: &mut ScriptRequest| r.body.clone(),
+            |r: &mut ScriptRequest, body: String| r.body = body,
+        )
+        .register_fn("header", ScriptRequest::header)
+        .register_fn("set_header", ScriptRequest::set_header)
+        .register_fn("remove_header", ScriptRequest::remove_header)
+        .register_fn("query", ScriptRequest::query)
+        .register_fn("set_query", ScriptRequest::set_query)
+        .register_fn("remove_query", ScriptRequest::remove_query);
+
+    engine
+        .register_type_with_name::<ScriptResponse>("Response")
+        .register_get("status", |r: &mut ScriptResponse| r.status)
+        .register_get("status_text", |r: &mut ScriptResponse| {
+            r.status_text.clone()
+        })
+        .register_get("body", |r: &mut ScriptResponse| r.body.clone())
+        .register_get("time", |r: &mut ScriptResponse| r.time)
+        .register_fn("header", ScriptResponse::header)
+        .register_fn("json", ScriptResponse::json);
+
+    engine
+        .register_type_with_name::<ScriptVariables>("Variables")
+        .register_fn("get", ScriptVariables::get)
+        .register_fn("set", ScriptVariables::set)
+        .register_fn("interpolate", ScriptVariables::interpolate);
+
+    engine
+        .register_fn("unix_time", || (now_millis() / 1000) as i64)
+        .register_fn("unix_time_ms", || now_millis() as i64)
+        .register_fn("uuid", uuid)
+        .register_fn("random_hex", |bytes: i64| {
+            Ok::<_, Box<EvalAltResult>>(util::hex(
+                &random_bytes(bytes.clamp(0, 1024) as usize)?,
+                "",
+            ))
+        })
+        .register_fn("sha256", |text: &str| util::hex(&Sha256::digest(text), ""))
+        .register_fn("hmac_sha256", |key: &str, text: &str| {
+            util::hex(&hmac_sha256(key, text), "")
+        })
+        .register_fn("hmac_sha256_base64", |key: &str, text: &str| {
+            base64::engine::general_purpose::STANDARD.encode(hmac_sha256(key, text))
+        })
+        .register_fn("base64_encode", |text: &str| {
+            base64::engine::general_purpose::STANDARD.encode(text)
+        })
+        .register_fn("base64_decode", |text: &str| {
+            base64::engine::general_purpose::STANDARD
+                .decode(text.trim())
+                .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+                .map_err(|err| Box::<EvalAltResult>::from(err.to_string()))
+        });
+
+    engine
+}
+
+fn find_param(params: &[Param], name: &str) -> Dynamic {
+    params
+        .iter()
+        .find(|p| p.enabled && p.key.eq_ignore_ascii_case(name))
+        .map_or(Dynamic::UNIT, |p| p.value.clone().into())
+}
+
+fn set_param(params: &mut Vec<Param>, name: &str, value: String) {
+    match params.iter_mut().find(|p| p.key.eq_ignore_ascii_case(name)) {
+        Some(param) => {
+            param.value = value;
+            param.enabled = true;
+        }
+        None => params.push(Param::enabled(name.to_owned(), value)),
+    }
+}
+
+fn json_to_dynamic(value: serde_json::Value) -> Dynamic {
+    use serde_json::Value;
+
+    match value {
+        Value::Null => Dynamic::UNIT,
+        Value::Bool(value) => value.into(),
+        Value::Number(number) => match number.as_i64() {
+            Some(number) => number.into(),
+            None => number.as_f64().unwrap_or_default().into(),
+        },
+        Value::String(value) => value.into(),
+        Value::Array(values) => values
+            .into_iter()
+            .map(json_to_dynamic)
+            .collect::<Vec<_>>()
+            .into(),
+        Value::Object(entries) => Dynamic::from_map(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key.into(), json_to_dynamic(value)))
+                .collect(),
+        ),
+    }
+}
+
+fn random_bytes(len: usize) -> Result<Vec<u8>, Box<EvalAltResult>> {
+    let mut bytes = vec![0; len];
+    getrandom::getrandom(&mut bytes).map_err(|err| err.to_string())?;
+    Ok(bytes)
+}
+
+/// Random version 4 UUID
+fn uuid() -> Result<String, Box<EvalAltResult>> {
+    let mut bytes = random_bytes(16)?;
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = util::hex(&bytes, "");
+    Ok(format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    ))
+}
+
+fn hmac_sha256(key: &str, text: &str) -> Vec<u8> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(key.as_bytes()).expect("hmac takes keys of any size");
+    mac.update(text.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::RequestKind;
+
+    #[test]
+    fn pre_request_keeps_request() {
+        let mut state = RequestState {
+            kind: RequestKind::Grpc,
+            body: r#"{"name": "a"}"#.to_owned(),
+            ..Default::default()
+        };
+        state.grpc.service = "helloworld.Greeter".to_owned();
+        state.grpc.method = "SayHello".to_owned();
+        state.scripts.pre_request = r#"request.url = "http://localhost:50051";"#.to_owned();
+
+        let mut variables = Variables::default();
+        let (scripted, _) = run_pre_request(&state, &mut variables, &mut vec![]).unwrap();
+        assert!(scripted.kind == RequestKind::Grpc);
+        assert!(scripted.grpc == state.grpc);
+        assert_eq!(scripted.url, "http://localhost:50051");
+        assert_eq!(scripted.body, state.body);
+        // fields the script doesn't see are kept too
+        assert!(scripted.scripts == state.scripts);
+    }
+}
@@ -0,0 +1,353 @@
+use std::{
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use futures_channel::oneshot;
+use web_time::Instant;
+
+/// One event of a `text/event-stream` body
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ServerEvent {
+    /// The last id seen, it carries over to the events after it
+    pub id: Option<String>,
+    /// `message` unless the server names it
+    pub event: String,
+    pub data: String,
+    /// How long to wait before reconnecting, in milliseconds
+    pub retry: Option<u64>,
+    /// Since the response headers arrived
+    pub at: Duration,
+}
+
+/// Splits a `text/event-stream` body into events as its chunks arrive, see
+/// <https://html.spec.whatwg.org/multipage/server-sent-events.html#event-stream-interpretation>
+#[derive(Default)]
+pub struct EventParser {
+    /// Bytes of a line that hasn't ended yet
+    partial: Vec<u8>,
+    started: bool,
+    last_id: Option<String>,
+    event: String,
+    data: Vec<String>,
+    retry: Option<u64>,
+}
+
+impl EventParser {
+    /// The events completed by the chunk
+    pub fn feed(&mut self, chunk: &[u8], at: Duration) -> Vec<ServerEvent> {
+        self.partial.extend_from_slice(chunk);
+        let mut events = vec![];
+        let mut start = 0;
+        while let Some(end) = self.partial[start..]
+            .iter()
+            .position(|byte| matches!(byte, b'\r' | b'\n'))
+            .map(|end| start + end)
+        {
+            // a trailing \r could still be followed by the \n of a \r\n
+            let crlf = self.partial[end] == b'\r';
+            if crlf && end + 1 == self.partial.len() {
+                break;
+            }
+            let line = String::from_utf8_lossy(&self.partial[start..end]).into_owned();
+            start = end + 1;
+            if crlf && self.partial[start] == b'\n' {
+                start += 1;
+            }
+            events.extend(self.line(&line, at));
+        }
+        self.partial.drain(..start);
+        events
+    }
+
+    /// Whatever the body ended with, the spec drops an event missing its blank line
+    pub fn finish(&mut self, at: Duration) -> Vec<ServerEvent> {
+        let line = String::from_utf8_lossy(&std::mem::take(&mut self.partial)).into_owned();
+        if line.is_empty() {
+            return vec![];
+        }
+        // a \r held back in case a \n followed still ends its line, blank or not
+        let line = line.strip_suffix('\r').unwrap_or(&line);
+        self.line(line, at).into_iter().collect()
+    }
+
+    fn line(&mut self, line: &str, at: Duration) -> Option<ServerEvent> {
+        let line = match self.started {
+            true => line,
+            false => {
+                self.started = true;
+                line.strip_prefix('\u{feff}').unwrap_or(line)
+            }
+        };
+        if line.is_empty() {
+            return self.dispatch(at);
+        }
+        let (field, value) = match line.split_once(':') {
+            // a comment, servers send them to keep the connection up
+            Some(("", _)) => return None,
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "event" => self.event = value.to_owned(),
+            "data" => self.data.push(value.to_owned()),
+            "id" if !value.contains('\0') => self.last_id = Some(value.to_owned()),
+            "retry" if !value.is_empty() && value.bytes().all(|byte| byte.is_ascii_digit()) => {
+                self.retry = value.parse().ok()
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// Events without data are dropped, unless they change the retry so it shows up
+    fn dispatch(&mut self, at: Duration) -> Option<ServerEvent> {
+        let event = std::mem::take(&mut self.event);
+        let data = std::mem::take(&mut self.data);
+        let retry = self.retry.take();
+        if data.is_empty() && retry.is_none() {
+            return None;
+        }
+        Some(ServerEvent {
+            id: self.last_id.clone(),
+            event: match event.is_empty() {
+                true => "message".to_owned(),
+                false => event,
+            },
+            data: data.join("\n"),
+            retry,
+            at,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum StreamStatus {
+    #[default]
+    Idle,
+    /// Sent, the response headers haven't arrived yet
+    Waiting,
+    /// The body is arriving
+    Streaming,
+    Done,
+    /// Cancelled while waiting or streaming, the body is what arrived until then
+    Cancelled,
+}
+
+#[derive(Default)]
+pub struct StreamState {
+    pub status: StreamStatus,
+    /// The body so far
+    pub body: Vec<u8>,
+    /// Set when the response is `text/event-stream`
+    pub event_stream: bool,
+    pub events: Vec<ServerEvent>,
+    parser: EventParser,
+    started: Option<Instant>,
+    /// Bumped for every request, see `StreamFeed`
+    generation: u64,
+    /// Sending or dropping it cancels the request
+    cancel: Option<oneshot::Sender<()>>,
+}
+
+impl StreamState {
+    fn is_active(&self) -> bool {
+        matches!(self.status, StreamStatus::Waiting | StreamStatus::Streaming)
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.started
+            .map(|started| started.elapsed())
+            .unwrap_or_default()
+    }
+}
+
+/// The body of a request's response as it arrives, clones share it
+#[derive(Clone, Default)]
+pub struct ResponseStream(Arc<Mutex<StreamState>>);
+
+impl ResponseStream {
+    pub fn lock(&self) -> MutexGuard<'_, StreamState> {
+        self.0.lock().unwrap()
+    }
+
+    /// Whether the request is still being sent or its body read
+    pub fn is_active(&self) -> bool {
+        self.lock().is_active()
+    }
+
+    /// Starts over for a new request, returns where its response goes and what resolves
+    /// once it's cancelled. A request still streaming is cancelled, its feed is ignored from
+    /// then on
+    pub fn start(&self) -> (StreamFeed, oneshot::Receiver<()>) {
+        let (cancel, cancelled) = oneshot::channel();
+        let mut state = self.lock();
+        *state = StreamState {
+            status: StreamStatus::Waiting,
+            generation: state.generation + 1,
+            cancel: Some(cancel),
+            ..Default::default()
+        };
+        let feed = StreamFeed {
+            stream: self.clone(),
+            generation: state.generation,
+        };
+        (feed, cancelled)
+    }
+
+    pub fn cancel(&self) {
+        if let Some(cancel) = self.lock().cancel.take() {
+            let _ = cancel.send(());
+        }
+    }
+}
+
+/// Writes the response of one request to its stream
+#[derive(Clone)]
+pub struct StreamFeed {
+    stream: ResponseStream,
+    generation: u64,
+}
+
+impl StreamFeed {
+    /// Runs `update` unless a newer request took over the stream
+    fn update(&self, update: impl FnOnce(&mut StreamState)) {
+        let mut state = self.stream.lock();
+        if state.generation == self.generation {
+            update(&mut state);
+        }
+    }
+
+    /// The response headers arrived
+    pub fn headers(&self, event_stream: bool) {
+        self.update(|state| {
+            state.status = StreamStatus::Streaming;
+            state.event_stream = event_stream;
+            state.started = Some(Instant::now());
+        });
+    }
+
+    pub fn append(&self, chunk: &[u8]) {
+        self.update(|state| {
+            state.body.extend_from_slice(chunk);
+            if state.event_stream {
+                let at = state.elapsed();
+                let events = state.parser.feed(chunk, at);
+                state.events.extend(events);
+            }
+        });
+    }
+
+    pub fn finish(&self, cancelled: bool) {
+        self.update(|state| {
+            if state.event_stream {
+                let at = state.elapsed();
+                let events = state.parser.finish(at);
+                state.events.extend(events);
+            }
+            state.status = match cancelled {
+                true => StreamStatus::Cancelled,
+                false => StreamStatus::Done,
+            };
+            state.cancel = None;
+        });
+    }
+
+    /// Gives up on a request that didn't get through its body, like when sending failed
+    pub fn stop(&self) {
+        self.update(|state| {
+            if state.is_active() {
+                state.status = StreamStatus::Idle;
+            }
+            state.cancel = None;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(chunks: &[&str]) -> Vec<ServerEvent> {
+        let mut parser = EventParser::default();
+        let mut events = vec![];
+        for chunk in chunks {
+            events.extend(parser.feed(chunk.as_bytes(), Duration::ZERO));
+        }
+        events.extend(parser.finish(Duration::ZERO));
+        events
+    }
+
+    fn message(data: &str) -> ServerEvent {
+        ServerEvent {
+            event: "message".to_owned(),
+            data: data.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn line_endings() {
+        let lf = parse(&["data: a\n\ndata: b\n\n"]);
+        assert_eq!(lf, [message("a"), message("b")]);
+        assert_eq!(parse(&["data: a\r\n\r\ndata: b\r\n\r\n"]), lf);
+        assert_eq!(parse(&["data: a\r\rdata: b\r\r"]), lf);
+    }
+
+    #[test]
+    fn multi_line_data() {
+        let events = parse(&["event: update\ndata: first\ndata:second\ndata\n\n"]);
+        assert_eq!(
+            events,
+            [ServerEvent {
+                event: "update".to_owned(),
+                ..message("first\nsecond\n")
+            }]
+        );
+    }
+
+    #[test]
+    fn comments_are_skipped() {
+        assert_eq!(parse(&[": ping\n\n:\ndata: a\n\n"]), [message("a")]);
+    }
+
+    #[test]
+    fn retry_and_id() {
+        let events =
+            parse(&["id: 1\nretry: 3000\ndata: a\n\nretry: soon\ndata: b\n\nretry: 10\n\n"]);
+        assert_eq!(
+            events,
+            [
+                ServerEvent {
+                    id: Some("1".to_owned()),
+                    retry: Some(3000),
+                    ..message("a")
+                },
+                // the id carries over, a retry that isn't a number is ignored
+                ServerEvent {
+                    id: Some("1".to_owned()),
+                    ..message("b")
+                },
+                ServerEvent {
+                    id: Some("1".to_owned()),
+                    retry: Some(10),
+                    ..message("")
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn split_across_chunks() {
+        let whole = parse(&["event: tick\r\ndata: {\"n\": 1}\r\n\r\n"]);
+        let split = parse(&["ev", "ent: tick\r", "\ndata: {\"n\"", ": 1}\r\n\r", "\n"]);
+        assert_eq!(split, whole);
+        assert_eq!(whole.len(), 1);
+        assert_eq!(whole[0].data, r#"{"n": 1}"#);
+    }
+
+    #[test]
+    fn unfinished_event_is_dropped() {
+        assert_eq!(parse(&["data: a\n\ndata: b"]), [message("a")]);
+    }
+}
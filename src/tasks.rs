@@ -1,75 +1,676 @@
-use std::str::FromStr;
+use std::{
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
 
+use futures_channel::oneshot;
 use http::HeaderValue;
 
-use crate::{async_runtime::AsyncRuntimeHandle, core::Param, http::HttpResponse};
+use crate::{
+    assertions::{self, Assertion, AssertionResult},
+    async_runtime::AsyncRuntimeHandle,
+    auth::{
+        OAuth2Params, RequestAuth, digest,
+        oauth2::{self, TokenCache},
+    },
+    body::{self, BodyKind, BodyOptions, BodySource},
+    client::{ClientSettings, Clients},
+    collections::RequestDefaults,
+    cookies::BasicCookieStore,
+    core::{Param, RequestKind, RequestState},
+    environment::Variables,
+    graphql::{self, SchemaCache, SchemaStatus},
+    grpc::{self, DescriptorCache, DescriptorSource, DescriptorStatus},
+    history::{History, HistoryEntry, SentRequest},
+    http::{HttpError, HttpMethod, HttpResponse, HttpResult},
+    script::{self, ScriptOutput, VariableUpdates},
+    stream::StreamFeed,
+    websocket,
+};
 
-pub fn execute(state: &mut crate::core::RequestState, runtime_handle: &AsyncRuntimeHandle) {
-    let uri_without_query = &state.url;
-    let query = serde_urlencoded::to_string(filter_params(&state.query)).unwrap_or_default();
-    let full_url = format!("{}?{}", uri_without_query, query);
+/// Shared by every request sent, whether from the editor or a run
+#[derive(Clone)]
+pub struct GlobalContext {
+    pub cookie_jar: Arc<BasicCookieStore>,
+    pub clients: Clients,
+    pub async_runtime: AsyncRuntimeHandle,
+    pub history: Arc<Mutex<History>>,
+    /// Set by scripts while sending, saved into the active environment on the next frame
+    pub script_variables: Arc<Mutex<VariableUpdates>>,
+    pub oauth_tokens: TokenCache,
+    pub graphql_schemas: SchemaCache,
+    pub grpc_descriptors: DescriptorCache,
+}
+
+/// Everything outside of the request itself that affects what gets sent
+#[derive(Clone, Default)]
+pub struct RequestContext {
+    pub variables: Variables,
+    pub defaults: RequestDefaults,
+    /// Global client settings
+    pub settings: ClientSettings,
+}
+
+impl RequestContext {
+    /// Auth of the request, falling back to the one inherited from its folders
+    pub fn resolve_auth(&self, auth: &RequestAuth) -> RequestAuth {
+        let auth = match auth {
+            RequestAuth::Inherit => &self.defaults.auth,
+            auth => auth,
+        };
+        auth.interpolate(&self.variables)
+    }
+
+    /// The request's own client settings, if it overrides the global ones
+    pub fn resolve_settings<'a>(
+        &'a self,
+        settings: &'a Option<ClientSettings>,
+    ) -> &'a ClientSettings {
+        settings.as_ref().unwrap_or(&self.settings)
+    }
+
+    /// Joins relative urls onto the inherited base url
+    pub fn resolve_url(&self, url: &str) -> String {
+        let url = self.variables.interpolate(url);
+        let base_url = self.variables.interpolate(&self.defaults.base_url);
+        if base_url.is_empty() || url.contains("://") {
+            return url;
+        }
+        if url.is_empty() {
+            return base_url;
+        }
+        format!(
+            "{}/{}",
+            base_url.trim_end_matches('/'),
+            url.trim_start_matches('/')
+        )
+    }
 
-    let mut request_builder = http::Request::builder()
+    /// Inherited headers first, the request's own headers win on name clashes
+    fn resolve_headers(&self, headers: &[Param]) -> Vec<Param> {
+        let mut resolved = self
+            .defaults
+            .headers
+            .iter()
+            .filter(|default| {
+                !headers
+                    .iter()
+                    .any(|h| h.enabled && h.key.eq_ignore_ascii_case(&default.key))
+            })
+            .cloned()
+            .collect::<Vec<Param>>();
+        resolved.extend(headers.iter().cloned());
+        self.variables.interpolate_params(&resolved)
+    }
+}
+
+/// Builds the request as it will be sent, with folder defaults and `{{name}}` variables applied.
+/// Auth isn't applied here, it's done right before sending
+pub fn build_request(
+    state: &RequestState,
+    context: &RequestContext,
+) -> Result<http::Request<Vec<u8>>, HttpError> {
+    let uri_without_query = context.resolve_url(&state.url);
+    let query_params = context.variables.interpolate_params(&state.query);
+    let query = serde_urlencoded::to_string(filter_params(&query_params)).unwrap_or_default();
+    let full_url = match (query.is_empty(), uri_without_query.contains('?')) {
+        (true, _) => uri_without_query,
+        (false, false) => format!("{}?{}", uri_without_query, query),
+        (false, true) => format!("{}&{}", uri_without_query, query),
+    };
+
+    let invalid_url = |reason: &str| HttpError::InvalidUrl {
+        url: full_url.clone(),
+        reason: reason.to_owned(),
+    };
+    let uri = http::Uri::from_str(&full_url).map_err(|err| invalid_url(&err.to_string()))?;
+    if uri.scheme().is_none() || uri.host().is_none() {
+        return Err(invalid_url(
+            "expected an absolute url like https://example.com",
+        ));
+    }
+
+    // a gRPC message is encoded once its method is known, see `grpc::prepare`
+    let body = match state.kind {
+        RequestKind::Grpc => body::EncodedBody {
+            bytes: vec![],
+            content_type: None,
+        },
+        _ => body::encode(&state.body, &state.body_options, &context.variables)?,
+    };
+    let mut request = http::Request::builder()
         .method(http::Method::from_str(&state.method.to_string()).unwrap_or_default())
-        .uri(http::Uri::from_str(&full_url).unwrap_or_default())
-        // TODO move to conditional auto-generated header, keeping for now
-        .header(http::header::ACCEPT, HeaderValue::from_static("*/*"));
-
-    for (header_name, header_value) in filter_params(&state.headers) {
-        request_builder = request_builder.header(header_name, header_value);
-    }
-    let request = request_builder
-        .body(state.body.clone().into_bytes())
-        .unwrap();
-
-    // Clone request so easier to pass it to another thread
-    let response_store = state.response.clone();
-    let auth = state.auth.clone();
-    let mut request = request.clone();
-    //
-    // let runner = move || {
-    //     auth.apply(&mut request);
-    //     log::info!("{:?}", request);
-    //     // let response_store = response.clone();
-    //     crate::http::execute(request, move |result| match result {
-    //         Ok(resp) => {
-    //             *response_store.lock().unwrap() = Some(resp);
-    //         }
-    //         Err(resp) => match resp {
-    //             HttpError::Unknown(err) => {
-    //                 *response_store.lock().unwrap() = Some(HttpResponse {
-    //                     body_raw: err,
-    //                     ..Default::default()
-    //                 })
-    //             }
-    //         },
-    //     });
-    // };
-    //
-    let response_store = state.response.clone();
-    runtime_handle.spawn_future(async move {
-        auth.apply(&mut request);
+        .uri(uri)
+        .body(body.bytes)
+        .map_err(|err| HttpError::Unknown(err.to_string()))?;
+    // TODO move to conditional auto-generated header, keeping for now
+    request
+        .headers_mut()
+        .insert(http::header::ACCEPT, HeaderValue::from_static("*/*"));
+
+    for (header_name, header_value) in filter_params(&context.resolve_headers(&state.headers)) {
+        let invalid = |reason: String| HttpError::InvalidHeader {
+            name: header_name.clone(),
+            reason,
+        };
+        let name = http::HeaderName::from_str(&header_name).map_err(|e| invalid(e.to_string()))?;
+        let value = HeaderValue::from_str(&header_value).map_err(|e| invalid(e.to_string()))?;
+        request.headers_mut().append(name, value);
+    }
+
+    // a content type set in the headers wins, except for multipart as it has to name the boundary
+    if let Some(content_type) = body.content_type {
+        let multipart = state.body_options.kind == BodyKind::Multipart;
+        if multipart || !request.headers().contains_key(http::header::CONTENT_TYPE) {
+            let value =
+                HeaderValue::from_str(&content_type).map_err(|err| HttpError::InvalidHeader {
+                    name: http::header::CONTENT_TYPE.to_string(),
+                    reason: err.to_string(),
+                })?;
+            request
+                .headers_mut()
+                .insert(http::header::CONTENT_TYPE, value);
+        }
+    }
+    Ok(request)
+}
+
+/// The exact request `execute` sends, auth included. OAuth 2.0 uses the cached token,
+/// none is fetched here
+pub fn prepare_request(
+    state: &RequestState,
+    context: &RequestContext,
+    oauth_tokens: &TokenCache,
+) -> Result<http::Request<Vec<u8>>, HttpError> {
+    let mut request = build_request(state, context)?;
+    match context.resolve_auth(&state.auth) {
+        RequestAuth::OAuth2(params) => {
+            if let Some(token) = oauth_tokens.valid_token(&params) {
+                bearer(token.access_token).apply(&mut request)?;
+            }
+        }
+        auth => auth.apply(&mut request)?,
+    }
+    Ok(request)
+}
+
+/// The request as `prepare_request` builds it, with its body as curl and generated code
+/// write it out
+pub fn prepare_snippet(
+    state: &RequestState,
+    context: &RequestContext,
+    oauth_tokens: &TokenCache,
+) -> Result<(http::Request<Vec<u8>>, BodySource), HttpError> {
+    let request = prepare_request(state, context, oauth_tokens)?;
+    let body = BodySource::new(request.body(), &state.body_options, &context.variables)?;
+    Ok((request, body))
+}
+
+fn bearer(token: String) -> RequestAuth {
+    RequestAuth::Bearer { token }
+}
+
+/// Fetches a new token for the auth in the background, ignoring the cached one
+pub fn fetch_oauth2_token(
+    params: OAuth2Params,
+    settings: &ClientSettings,
+    global_context: &GlobalContext,
+) {
+    let tokens = global_context.oauth_tokens.clone();
+    match global_context.clients.get(settings) {
+        Ok(client) => global_context.async_runtime.spawn_future(async move {
+            // the outcome is shown from the cache
+            let _ = oauth2::fetch(&params, &client, &tokens, None).await;
+        }),
+        Err(err) => tokens.fail(&params, err.to_string()),
+    }
+}
+
+/// A request ready to be sent, with where its outcome goes
+pub struct Execution {
+    prepared: Prepared,
+    oauth_tokens: TokenCache,
+    entry: HistoryEntry,
+    variables: Variables,
+    console: Vec<String>,
+    post_response: String,
+    assertions: Vec<Assertion>,
+    response_store: Arc<Mutex<Option<HttpResult>>>,
+    stream: StreamFeed,
+    script_output: Arc<Mutex<ScriptOutput>>,
+    test_results: Arc<Mutex<Vec<AssertionResult>>>,
+    script_variables: Arc<Mutex<VariableUpdates>>,
+    history: Arc<Mutex<History>>,
+}
+
+impl Execution {
+    /// Sends the request, then stores the response on the request it came from, runs the
+    /// post-response script and evaluates the assertions.
+    /// Also returns the variables with whatever the scripts set
+    pub async fn send(mut self) -> (Result<Vec<AssertionResult>, HttpError>, Variables) {
         log::info!("sending request");
 
-        // thread::spawn doesn't work on web, so we just run the auth
-        // signing on main thread which isn't slow in any means, its just
-        // I didn't wanna do it
-        let result = crate::http::execute_new(request).await;
-
-        let response = match result {
-            Ok(response) => response,
-            // build empty response for now
-            // TODO: map status codes and errors
-            Err(err) => HttpResponse {
-                body_raw: err.to_string(),
-                ..Default::default()
-            },
+        let result = self.send_request().await;
+        self.stream.stop();
+
+        if let Ok(response) = &result {
+            if !self.post_response.trim().is_empty() {
+                let variables = self.variables.clone();
+                if let Ok(updates) = script::run_post_response(
+                    &self.post_response,
+                    response,
+                    variables,
+                    &mut self.console,
+                ) {
+                    for (key, value) in &updates {
+                        self.variables.set(key, value);
+                    }
+                    self.script_variables.lock().unwrap().extend(updates);
+                }
+            }
+        }
+        self.script_output.lock().unwrap().console = self.console;
+
+        match &result {
+            Ok(response) => self.entry.response = response.clone(),
+            Err(err) => self.entry.error = Some(err.clone()),
+        }
+        self.history.lock().unwrap().push(self.entry);
+        *self.response_store.lock().unwrap() = Some(result.clone());
+
+        let results = match &result {
+            Ok(response) => assertions::evaluate(&self.assertions, response, &self.variables),
+            Err(_) => vec![],
         };
+        *self.test_results.lock().unwrap() = results.clone();
+        (result.map(|_| results), self.variables)
+    }
 
-        *response_store.lock().unwrap() = Some(response);
+    async fn send_request(&mut self) -> HttpResult {
+        if self.prepared.deferred_auth.is_some() {
+            if let Some(unauthorized) = self.prepared.authenticate(&self.oauth_tokens).await? {
+                return Ok(unauthorized);
+            }
+            self.entry.sent = self.prepared.sent();
+        }
+        self.prepared.send().await
+    }
+}
+
+/// Runs the pre-request script and builds the request. Errors are also stored as the
+/// request's response, since nothing will be sent
+pub fn prepare_execution(
+    state: &RequestState,
+    context: &RequestContext,
+    global_context: &GlobalContext,
+) -> Result<Execution, HttpError> {
+    let mut console = vec![];
+    let mut context = context.clone();
+    let prepared = prepare_scripted(state, &mut context, global_context, &mut console);
+    let mut prepared = match prepared {
+        Ok(prepared) => prepared,
+        Err(err) => {
+            // never sent, so there's nothing to keep in the history
+            state.script_output.lock().unwrap().console = console;
+            state.test_results.lock().unwrap().clear();
+            *state.response.lock().unwrap() = Some(Err(err.clone()));
+            return Err(err);
+        }
+    };
+
+    let (stream, cancelled) = state.stream.start();
+    prepared.stream = Some((stream.clone(), cancelled));
+
+    Ok(Execution {
+        entry: HistoryEntry::new(state, prepared.sent()),
+        prepared,
+        oauth_tokens: global_context.oauth_tokens.clone(),
+        variables: context.variables,
+        console,
+        post_response: state.scripts.post_response.clone(),
+        assertions: state.assertions.clone(),
+        response_store: state.response.clone(),
+        stream,
+        script_output: state.script_output.clone(),
+        test_results: state.test_results.clone(),
+        script_variables: global_context.script_variables.clone(),
+        history: global_context.history.clone(),
+    })
+}
+
+struct Prepared {
+    client: reqwest::Client,
+    request: http::Request<Vec<u8>>,
+    /// Auth needing a round trip first, applied while sending
+    deferred_auth: Option<RequestAuth>,
+    /// Where the body goes as it arrives, with what cancels the request
+    stream: Option<(StreamFeed, oneshot::Receiver<()>)>,
+    /// The method of a gRPC call, its answers are decoded with it
+    method: Option<prost_reflect::MethodDescriptor>,
+    /// The message of a gRPC call as JSON, the history keeps it instead of its encoding
+    message: Option<String>,
+}
+
+impl Prepared {
+    fn sent(&self) -> SentRequest {
+        let mut sent = SentRequest::from_request(&self.request);
+        if let Some(message) = &self.message {
+            sent.body = message.clone();
+        }
+        sent
+    }
+
+    /// Applies the deferred auth. When a Digest server sends no challenge, its response is
+    /// returned as is
+    async fn authenticate(
+        &mut self,
+        oauth_tokens: &TokenCache,
+    ) -> Result<Option<HttpResponse>, HttpError> {
+        match self.deferred_auth.take() {
+            Some(RequestAuth::OAuth2(params)) => {
+                let token = oauth2::access_token(&params, &self.client, oauth_tokens)
+                    .await
+                    .map_err(HttpError::Auth)?;
+                bearer(token.access_token).apply(&mut self.request)?;
+            }
+            Some(RequestAuth::Digest { username, password }) => {
+                // the challenge comes with the 401 of the request sent without auth
+                let unauthorized =
+                    crate::http::execute_new(self.client.clone(), clone_request(&self.request))
+                        .await?;
+                let challenge = digest::find_challenge(&unauthorized.headers);
+                match challenge.filter(|_| unauthorized.status == 401) {
+                    Some(challenge) => {
+                        digest::apply(&mut self.request, &username, &password, &challenge)?
+                    }
+                    None => return Ok(Some(unauthorized)),
+                }
+            }
+            Some(auth) => auth.apply(&mut self.request)?,
+            None => {}
+        }
+        Ok(None)
+    }
+
+    async fn send(&mut self) -> HttpResult {
+        let request = std::mem::take(&mut self.request);
+        let (stream, method) = (self.stream.take(), self.method.clone());
+        crate::http::execute_streamed(self.client.clone(), request, stream, method).await
+    }
+}
+
+fn prepare_scripted(
+    state: &RequestState,
+    context: &mut RequestContext,
+    global_context: &GlobalContext,
+    console: &mut Vec<String>,
+) -> Result<Prepared, HttpError> {
+    if state.kind == RequestKind::WebSocket {
+        return Err(HttpError::WebSocket(
+            "the request is a WebSocket connection, runs skip it".to_owned(),
+        ));
+    }
+    let scripted = if state.scripts.pre_request.trim().is_empty() {
+        None
+    } else {
+        let (scripted, updates) = script::run_pre_request(state, &mut context.variables, console)
+            .map_err(HttpError::Script)?;
+        global_context
+            .script_variables
+            .lock()
+            .unwrap()
+            .extend(updates);
+        Some(scripted)
+    };
+    prepare(scripted.as_ref().unwrap_or(state), context, global_context)
+}
+
+fn prepare(
+    to_send: &RequestState,
+    context: &RequestContext,
+    global_context: &GlobalContext,
+) -> Result<Prepared, HttpError> {
+    // auth signing runs on the main thread since thread::spawn doesn't work on web,
+    // it isn't slow in any means
+    let mut request = build_request(to_send, context)?;
+    let (method, message) = match to_send.kind {
+        RequestKind::Grpc => {
+            let method = grpc_method(to_send, context, global_context)?;
+            let message = context.variables.interpolate(&to_send.body);
+            grpc::prepare(&mut request, &method, &message)?;
+            (Some(method), Some(message))
+        }
+        _ => (None, None),
+    };
+    let auth = context.resolve_auth(&to_send.auth);
+    let deferred_auth = if auth.needs_round_trip() {
+        Some(auth)
+    } else {
+        auth.apply(&mut request)?;
+        None
+    };
+    let settings = context.resolve_settings(&to_send.settings);
+    let client = match method {
+        Some(_) => global_context.clients.get_http2(settings)?,
+        None => global_context.clients.get(settings)?,
+    };
+    Ok(Prepared {
+        client,
+        request,
+        deferred_auth,
+        stream: None,
+        method,
+        message,
+    })
+}
+
+/// The method a gRPC request calls, from descriptors already loaded by
+/// `load_grpc_descriptors`. Errors when they aren't, the Body tab and folder runs load them
+#[cfg(not(target_arch = "wasm32"))]
+fn grpc_method(
+    state: &RequestState,
+    context: &RequestContext,
+    global_context: &GlobalContext,
+) -> Result<prost_reflect::MethodDescriptor, HttpError> {
+    let options = &state.grpc;
+    let key = options.cache_key(&context.resolve_url(&state.url));
+    let descriptors = &global_context.grpc_descriptors;
+    // parsing here would block the frame
+    let pool = match (descriptors.get(&key).pool, options.source) {
+        (Some(pool), _) => pool,
+        (None, DescriptorSource::ProtoFiles) => {
+            return Err(HttpError::Grpc(
+                "the descriptors aren't loaded, load the protos in the Body tab".to_owned(),
+            ));
+        }
+        (None, DescriptorSource::Reflection) => {
+            return Err(HttpError::Grpc(
+                "the server wasn't asked for its services yet, reflect it in the Body tab"
+                    .to_owned(),
+            ));
+        }
+    };
+    grpc::find_method(&pool, &options.service, &options.method).map_err(HttpError::Grpc)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn grpc_method(
+    _state: &RequestState,
+    _context: &RequestContext,
+    _global_context: &GlobalContext,
+) -> Result<prost_reflect::MethodDescriptor, HttpError> {
+    Err(HttpError::Grpc(
+        "browsers can't make gRPC calls, they don't expose HTTP/2 trailers".to_owned(),
+    ))
+}
+
+pub fn execute(state: &mut RequestState, context: &RequestContext, global_context: &GlobalContext) {
+    if let Ok(execution) = prepare_execution(state, context, global_context) {
+        global_context.async_runtime.spawn_future(async move {
+            // the outcome is already stored on the request
+            let _ = execution.send().await;
+        });
+    }
+}
+
+/// Runs the introspection query against the request's url in the background, with its
+/// headers and auth. The schema is cached by the url, nothing goes to the history
+pub fn introspect_graphql(
+    state: &RequestState,
+    context: &RequestContext,
+    global_context: &GlobalContext,
+) {
+    let schemas = global_context.graphql_schemas.clone();
+    let url = context.resolve_url(&state.url);
+    let introspection = RequestState {
+        method: HttpMethod::Post,
+        body: graphql::INTROSPECTION_QUERY.to_owned(),
+        body_options: BodyOptions {
+            kind: BodyKind::GraphQl,
+            ..Default::default()
+        },
+        ..state.clone()
+    };
+    let mut prepared = match prepare(&introspection, context, global_context) {
+        Ok(prepared) => prepared,
+        Err(err) => return schemas.set_status(&url, SchemaStatus::Failed(err.to_string())),
+    };
+    schemas.set_status(&url, SchemaStatus::Fetching);
+    let oauth_tokens = global_context.oauth_tokens.clone();
+    global_context.async_runtime.spawn_future(async move {
+        let response = match prepared.authenticate(&oauth_tokens).await {
+            Ok(Some(unauthorized)) => Ok(unauthorized),
+            Ok(None) => prepared.send().await,
+            Err(err) => Err(err),
+        };
+        let schema = response
+            .map_err(|err| err.to_string())
+            .and_then(|response| match graphql::parse_schema(&response.body_raw) {
+                Err(_) if !response.ok => Err(format!(
+                    "the server answered {} {}",
+                    response.status, response.status_text
+                )),
+                schema => schema,
+            });
+        schemas.store(&url, schema);
     });
 }
 
+/// Loads the descriptors of a gRPC request in the background, from its proto files or by
+/// asking the server with reflection. Reflection carries the request's metadata and auth
+pub fn load_grpc_descriptors(
+    state: &RequestState,
+    context: &RequestContext,
+    global_context: &GlobalContext,
+) {
+    let descriptors = global_context.grpc_descriptors.clone();
+    let key = state.grpc.cache_key(&context.resolve_url(&state.url));
+    match state.grpc.source {
+        #[cfg(not(target_arch = "wasm32"))]
+        DescriptorSource::ProtoFiles => {
+            descriptors.set_status(&key, DescriptorStatus::Loading);
+            let options = state.grpc.clone();
+            global_context.async_runtime.spawn_future(async move {
+                let parsed = grpc::parse_proto_files(&options.proto_files, &options.import_paths);
+                descriptors.store(&key, parsed);
+            });
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        DescriptorSource::Reflection => {
+            let base = RequestState {
+                kind: RequestKind::Http,
+                method: HttpMethod::Post,
+                body: String::new(),
+                body_options: Default::default(),
+                ..state.clone()
+            };
+            let settings = context.resolve_settings(&state.settings);
+            let prepared = prepare(&base, context, global_context).and_then(|prepared| {
+                let client = global_context.clients.get_http2(settings)?;
+                Ok(Prepared { client, ..prepared })
+            });
+            let mut prepared = match prepared {
+                Ok(prepared) => prepared,
+                Err(err) => return descriptors.store(&key, Err(err.to_string())),
+            };
+            descriptors.set_status(&key, DescriptorStatus::Loading);
+            let oauth_tokens = global_context.oauth_tokens.clone();
+            global_context.async_runtime.spawn_future(async move {
+                let reflected = match prepared.authenticate(&oauth_tokens).await {
+                    Ok(_) => {
+                        let request = std::mem::take(&mut prepared.request);
+                        grpc::reflect(prepared.client.clone(), request).await
+                    }
+                    Err(err) => Err(err.to_string()),
+                };
+                descriptors.store(&key, reflected);
+            });
+        }
+        #[cfg(target_arch = "wasm32")]
+        _ => descriptors.store(
+            &key,
+            Err("browsers can't make gRPC calls, they don't expose HTTP/2 trailers".to_owned()),
+        ),
+    }
+}
+
+/// Opens the connection of a WebSocket request in the background, the upgrade carries the
+/// request's headers, cookies and auth. Nothing goes to the history
+pub fn connect_websocket(
+    state: &RequestState,
+    context: &RequestContext,
+    global_context: &GlobalContext,
+) {
+    let session = state.session.clone();
+    let upgrade = RequestState {
+        method: HttpMethod::Get,
+        body: String::new(),
+        body_options: Default::default(),
+        ..state.clone()
+    };
+    let mut prepared = match prepare(&upgrade, context, global_context) {
+        Ok(prepared) => prepared,
+        Err(err) => return session.fail(err.to_string()),
+    };
+    if let Some(RequestAuth::Digest { .. }) = prepared.deferred_auth {
+        return session.fail(
+            "Digest auth answers a challenge of an HTTP response, WebSocket upgrades can't use it"
+                .to_owned(),
+        );
+    }
+    let (connection, outgoing, cancelled) = session.start();
+    let settings = context.resolve_settings(&state.settings).clone();
+    let oauth_tokens = global_context.oauth_tokens.clone();
+    let cookie_jar = global_context.cookie_jar.clone();
+    global_context.async_runtime.spawn_future(async move {
+        if let Err(err) = prepared.authenticate(&oauth_tokens).await {
+            return connection.fail(err.to_string());
+        }
+        let mut request = std::mem::take(&mut prepared.request);
+        // the HTTP client adds the cookies itself, here they're added by hand
+        if !request.headers().contains_key(http::header::COOKIE) {
+            let url = url::Url::parse(&request.uri().to_string());
+            let cookies = url
+                .ok()
+                .and_then(|url| reqwest::cookie::CookieStore::cookies(&*cookie_jar, &url));
+            if let Some(cookies) = cookies {
+                request.headers_mut().insert(http::header::COOKIE, cookies);
+            }
+        }
+        websocket::run(connection, request, settings, outgoing, cancelled).await;
+    });
+}
+
+/// `http::Request` isn't Clone
+fn clone_request(request: &http::Request<Vec<u8>>) -> http::Request<Vec<u8>> {
+    let mut clone = http::Request::new(request.body().clone());
+    *clone.method_mut() = request.method().clone();
+    *clone.uri_mut() = request.uri().clone();
+    *clone.version_mut() = request.version();
+    *clone.headers_mut() = request.headers().clone();
+    clone
+}
+
 fn filter_params(params: &[Param]) -> Vec<(String, String)> {
     params
         .iter()
@@ -5,15 +5,18 @@ use egui::{Color32, Id, Margin, Rect, Sense};
 use egui_tiles::{SimplificationOptions, Tile, TileId, Tiles};
 
 use crate::{
-    app::GlobalContext,
+    auth::RequestAuth,
     components::{
-        auth_editor_view, body_editor_view,
-        body_reader_view::{self, BodyReaderView},
-        params_editor_view::ParamsEditorView,
-        params_reader_view, response_stats_view,
+        auth_editor_view, body_editor_view, body_reader_view::BodyReaderView, client_settings_view,
+        codegen_view::CodegenView, graphql_errors_view, grpc_editor_view, http_error_view,
+        jwt_view::JwtView, oauth2_token_view, params_editor_view::ParamsEditorView,
+        params_reader_view, response_stats_view, schema_view::SchemaView, script_view::ScriptView,
+        stream_view::StreamView, tests_view, websocket_view::WebSocketView,
     },
-    core::RequestState,
-    http::HttpResponse,
+    core::{RequestId, RequestKind, RequestState},
+    graphql, grpc,
+    http::{HttpError, HttpResponse},
+    tasks::{self, GlobalContext, RequestContext},
 };
 
 #[derive(serde::Deserialize, serde::Serialize)]
@@ -35,11 +38,16 @@ pub enum PaneKind {
     Body,
     Auth,
     Script,
+    Code,
+    Settings,
+    Jwt,
+    GraphQlSchema,
 
     // TODO: (temp) move to its own parent enum
     ResponseBody,
     ResponseHeaders,
     ResponseStats,
+    Tests,
 }
 
 impl fmt::Display for PaneKind {
@@ -50,9 +58,14 @@ impl fmt::Display for PaneKind {
             PaneKind::Body => write!(f, "Body"),
             PaneKind::Auth => write!(f, "Auth"),
             PaneKind::Script => write!(f, "Script"),
+            PaneKind::Code => write!(f, "Code"),
+            PaneKind::Settings => write!(f, "Settings"),
+            PaneKind::Jwt => write!(f, "JWT"),
+            PaneKind::GraphQlSchema => write!(f, "GraphQL Schema"),
             PaneKind::ResponseBody => write!(f, "Response Body"),
             PaneKind::ResponseHeaders => write!(f, "Response Headers"),
             PaneKind::ResponseStats => write!(f, "Response Stats"),
+            PaneKind::Tests => write!(f, "Tests"),
         }
     }
 }
@@ -64,12 +77,29 @@ impl Pane {
 
     pub fn pane_ui(
         &mut self,
-        state: &mut RequestState,
-        _: &mut GlobalContext,
-        params_view: &mut ParamsEditorView,
-        body_reader_view: &mut BodyReaderView,
+        behavior: &mut TreeBehavior<'_>,
         ui: &mut egui::Ui,
     ) -> egui_tiles::UiResponse {
+        let TreeBehavior {
+            state,
+            context,
+            global_context,
+            views:
+                PaneViews {
+                    params_view,
+                    form_view,
+                    body_reader_view,
+                    codegen_view,
+                    script_view,
+                    jwt_view,
+                    schema_view,
+                    stream_view,
+                    websocket_view,
+                    presigned_url,
+                    ..
+                },
+            ..
+        } = behavior;
         let color = egui::epaint::Hsva::new(0.103 * self.nr as f32, 0.5, 0.5, 1.0);
 
         let dragged = ui
@@ -87,44 +117,148 @@ impl Pane {
                     PaneKind::Headers => {
                         params_view.show(ui, &mut state.headers);
                     }
+                    // a WebSocket request's messages go where an HTTP body and response would
+                    PaneKind::Body if state.kind == RequestKind::WebSocket => {
+                        websocket_view.show_composer(
+                            ui,
+                            &mut state.websocket,
+                            &state.session,
+                            &context.variables,
+                        );
+                    }
+                    PaneKind::ResponseBody if state.kind == RequestKind::WebSocket => {
+                        websocket_view.show_log(ui, &state.session);
+                    }
+                    PaneKind::Body if state.kind == RequestKind::Grpc => {
+                        let key = state.grpc.cache_key(&context.resolve_url(&state.url));
+                        let cached = global_context.grpc_descriptors.get(&key);
+                        if grpc_editor_view::show(ui, &mut state.grpc, &mut state.body, &cached) {
+                            tasks::load_grpc_descriptors(state, context, global_context);
+                        }
+                    }
                     PaneKind::Body => {
-                        body_editor_view::show(ui, &mut state.body);
+                        let schema = global_context
+                            .graphql_schemas
+                            .get(&context.resolve_url(&state.url));
+                        if body_editor_view::show(
+                            ui,
+                            &mut state.body,
+                            &mut state.body_options,
+                            form_view,
+                            &schema,
+                        ) {
+                            tasks::introspect_graphql(state, context, global_context);
+                        }
                     }
-                    PaneKind::ResponseBody => {
-                        let guard = state.response.lock().unwrap();
-                        let response = guard.deref();
-                        if let Some(response) = response {
+                    PaneKind::ResponseBody if state.stream.is_active() => {
+                        stream_view.show_live(ui, &state.stream);
+                    }
+                    PaneKind::ResponseBody => show_response(ui, state, |ui, response| {
+                        if let Some(status) = grpc::status(response) {
+                            grpc_editor_view::show_status(ui, &status);
+                        }
+                        if let Some(errors) = graphql::response_errors(&response.body_raw) {
+                            graphql_errors_view::show(ui, &errors);
+                        }
+                        if !stream_view.show_done(ui, &state.stream) {
                             body_reader_view.show(ui, &response.body_raw, &response.body_pretty);
-                        } else {
-                            ui.label("No response yet");
                         }
-                    }
-                    PaneKind::ResponseHeaders => {
-                        let guard = state.response.lock().unwrap();
-                        let response = guard.deref();
-                        if let Some(response) = response {
+                    }),
+                    PaneKind::ResponseHeaders => show_response(ui, state, |ui, response| {
+                        params_reader_view::show(
+                            Id::new("response_headers"),
+                            ui,
+                            &response.headers,
+                        );
+                        if !response.trailers.is_empty() {
+                            ui.add_space(8.0);
+                            ui.strong("Trailers");
                             params_reader_view::show(
-                                Id::new("response_headers"),
+                                Id::new("response_trailers"),
                                 ui,
-                                &response.headers,
+                                &response.trailers,
                             );
-                        } else {
-                            ui.label("No response yet");
                         }
-                    }
+                    }),
                     PaneKind::Auth => {
                         auth_editor_view::show(ui, &mut state.auth);
+                        match context.resolve_auth(&state.auth) {
+                            // also for an inherited one, the token is shared with the folder's requests
+                            RequestAuth::OAuth2(params) => {
+                                ui.separator();
+                                if oauth2_token_view::show(
+                                    ui,
+                                    &params,
+                                    &global_context.oauth_tokens,
+                                ) {
+                                    let settings = context.resolve_settings(&state.settings);
+                                    tasks::fetch_oauth2_token(params, settings, global_context);
+                                }
+                            }
+                            RequestAuth::AwsSigV4(params) if params.presign => {
+                                ui.separator();
+                                show_presigned_url(
+                                    ui,
+                                    presigned_url,
+                                    state,
+                                    context,
+                                    global_context,
+                                );
+                            }
+                            _ => {}
+                        }
+                    }
+                    PaneKind::Script => {
+                        script_view.show(ui, &mut state.scripts, &state.script_output);
                     }
-                    PaneKind::ResponseStats => {
-                        let guard = state.response.lock().unwrap();
-                        let response = guard.deref();
-                        if let Some(response) = response {
-                            response_stats_view::show(Id::new("response_stats"), ui, &response)
-                        } else {
-                            ui.label("No response yet");
+                    PaneKind::Code => {
+                        codegen_view.show(ui, || {
+                            tasks::prepare_snippet(state, context, &global_context.oauth_tokens)
+                        });
+                    }
+                    PaneKind::Jwt => {
+                        let response = state.response.lock().unwrap();
+                        let response = match response.deref() {
+                            Some(Ok(response)) => Some(response),
+                            _ => None,
+                        };
+                        // the request as sent, with a signed or fetched token in its auth
+                        let prepare =
+                            || tasks::prepare_request(state, context, &global_context.oauth_tokens);
+                        jwt_view.show(ui, prepare, response);
+                    }
+                    PaneKind::GraphQlSchema => {
+                        let schema = global_context
+                            .graphql_schemas
+                            .get(&context.resolve_url(&state.url));
+                        if schema_view.show(ui, &schema) {
+                            tasks::introspect_graphql(state, context, global_context);
+                        }
+                    }
+                    PaneKind::Settings => {
+                        let mut overridden = state.settings.is_some();
+                        if ui
+                            .checkbox(&mut overridden, "Override the global settings")
+                            .changed()
+                        {
+                            state.settings = overridden.then(|| context.settings.clone());
+                        }
+                        ui.add_space(8.0);
+                        match &mut state.settings {
+                            Some(settings) => client_settings_view::show(ui, settings),
+                            None => {
+                                ui.add_enabled_ui(false, |ui| {
+                                    client_settings_view::show(ui, &mut context.settings.clone())
+                                });
+                            }
                         }
                     }
-                    _ => {}
+                    PaneKind::ResponseStats => show_response(ui, state, |ui, response| {
+                        response_stats_view::show(Id::new("response_stats"), ui, response);
+                    }),
+                    PaneKind::Tests => {
+                        tests_view::show(ui, &mut state.assertions, &state.test_results);
+                    }
                 }
                 ui.allocate_rect(ui.max_rect(), Sense::empty());
             });
@@ -138,24 +272,106 @@ impl Pane {
     }
 }
 
+/// The url signed as it would be sent, to share it. Signed when asked for, since every
+/// signature differs and signing can read the AWS profiles
+fn show_presigned_url(
+    ui: &mut egui::Ui,
+    presigned: &mut Option<Result<String, HttpError>>,
+    state: &RequestState,
+    context: &RequestContext,
+    global_context: &GlobalContext,
+) {
+    ui.horizontal(|ui| {
+        ui.strong("Presigned URL");
+        if ui
+            .small_button("Presign")
+            .on_hover_text("Signs the request as it is now")
+            .clicked()
+        {
+            let request = tasks::prepare_request(state, context, &global_context.oauth_tokens);
+            *presigned = Some(request.map(|request| request.uri().to_string()));
+        }
+        if let Some(Ok(url)) = presigned {
+            if ui.small_button("\u{e14d}").on_hover_text("Copy").clicked() {
+                ui.ctx().copy_text(url.clone());
+            }
+        }
+    });
+    match presigned {
+        Some(Ok(url)) => {
+            ui.add(egui::Label::new(egui::RichText::new(url.as_str()).monospace()).truncate());
+        }
+        Some(Err(err)) => http_error_view::show(ui, err),
+        None => {
+            ui.weak("Not signed yet");
+        }
+    }
+}
+
+/// Shows the last response of the request, or why there isn't one
+fn show_response(
+    ui: &mut egui::Ui,
+    state: &RequestState,
+    add_contents: impl FnOnce(&mut egui::Ui, &HttpResponse),
+) {
+    match state.response.lock().unwrap().deref() {
+        Some(Ok(response)) => add_contents(ui, response),
+        Some(Err(err)) => http_error_view::show(ui, err),
+        None => {
+            ui.label("No response yet");
+        }
+    }
+}
+
 pub struct TreeBehavior<'a> {
     pub simplification_options: egui_tiles::SimplificationOptions,
     pub tab_bar_height: f32,
     pub gap_width: f32,
     pub add_child_to: Option<(egui_tiles::TileId, PaneKind)>,
     pub state: &'a mut RequestState,
-    pub global_context: &'a mut GlobalContext,
-    // TODO: move these to request view and move req state under it
-    pub params_view: &'a mut ParamsEditorView,
-    pub body_reader_view: &'a mut BodyReaderView,
+    pub context: &'a RequestContext,
+    pub global_context: &'a GlobalContext,
+    pub views: &'a mut PaneViews,
+}
+
+/// State the panes keep between frames
+// TODO: move these to request view and move req state under it
+#[derive(Default)]
+pub struct PaneViews {
+    pub params_view: ParamsEditorView,
+    /// Fields of form bodies, apart from the query and headers
+    pub form_view: ParamsEditorView,
+    pub body_reader_view: BodyReaderView,
+    pub codegen_view: CodegenView,
+    pub script_view: ScriptView,
+    pub jwt_view: JwtView,
+    pub schema_view: SchemaView,
+    pub stream_view: StreamView,
+    pub websocket_view: WebSocketView,
+    /// Signed for the Auth pane when asked for
+    pub presigned_url: Option<Result<String, HttpError>>,
+    /// The request the views were last shown for
+    request_id: Option<RequestId>,
+}
+
+impl PaneViews {
+    /// Drops what was built from another request, the views are shared between them
+    pub fn show_request(&mut self, request_id: RequestId) {
+        if self.request_id != Some(request_id) {
+            self.request_id = Some(request_id);
+            self.codegen_view.forget();
+            self.presigned_url = None;
+            self.jwt_view.forget();
+        }
+    }
 }
 
 impl<'a> TreeBehavior<'a> {
     pub fn default_with_state(
         state: &'a mut RequestState,
-        global_context: &'a mut GlobalContext,
-        params_view: &'a mut ParamsEditorView,
-        body_reader_view: &'a mut BodyReaderView,
+        context: &'a RequestContext,
+        global_context: &'a GlobalContext,
+        views: &'a mut PaneViews,
     ) -> Self {
         Self {
             simplification_options: SimplificationOptions {
@@ -166,9 +382,9 @@ impl<'a> TreeBehavior<'a> {
             gap_width: 2.0,
             add_child_to: None,
             state,
+            context,
             global_context,
-            params_view,
-            body_reader_view,
+            views,
         }
     }
     pub fn ui(&mut self, ui: &mut egui::Ui) {
@@ -215,13 +431,7 @@ impl<'a> egui_tiles::Behavior<Pane> for TreeBehavior<'a> {
         _tile_id: egui_tiles::TileId,
         view: &mut Pane,
     ) -> egui_tiles::UiResponse {
-        view.pane_ui(
-            &mut self.state,
-            &mut self.global_context,
-            &mut self.params_view,
-            &mut self.body_reader_view,
-            ui,
-        )
+        view.pane_ui(self, ui)
     }
 
     fn tab_title_for_pane(&mut self, view: &Pane) -> egui::WidgetText {
@@ -245,8 +455,13 @@ impl<'a> egui_tiles::Behavior<Pane> for TreeBehavior<'a> {
                     PaneKind::Body,
                     PaneKind::Auth,
                     PaneKind::Script,
+                    PaneKind::Code,
+                    PaneKind::Settings,
                     PaneKind::ResponseBody,
                     PaneKind::ResponseHeaders,
+                    PaneKind::Tests,
+                    PaneKind::Jwt,
+                    PaneKind::GraphQlSchema,
                 ] {
                     if ui.selectable_label(false, kind.to_string()).clicked() {
                         self.add_child_to = Some((tile_id, kind));
@@ -0,0 +1,185 @@
+use std::time::Duration;
+
+/// How long each phase of a request took. Phases that didn't happen or can't be observed,
+/// like DNS and connect on wasm, are left empty
+#[derive(Default, Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct Timing {
+    pub dns: Option<Duration>,
+    /// TCP connect and, when `secure`, the TLS handshake. reqwest does both in one step so
+    /// they can't be told apart, see `connect_label`
+    pub connect: Option<Duration>,
+    /// Request sent until the response headers arrived
+    pub waiting: Duration,
+    pub download: Duration,
+    pub total: Duration,
+    /// Whether the connection timed was over TLS
+    pub secure: bool,
+}
+
+impl Timing {
+    /// `dns` and `connect` are measured separately but `connect` starts with resolving,
+    /// so it's made relative here
+    pub fn new(
+        dns: Option<Duration>,
+        connect: Option<Duration>,
+        headers_received: Duration,
+        total: Duration,
+        secure: bool,
+    ) -> Self {
+        let connect = connect.map(|connect| connect.saturating_sub(dns.unwrap_or_default()));
+        let before_send = dns.unwrap_or_default() + connect.unwrap_or_default();
+        Self {
+            dns,
+            connect,
+            waiting: headers_received.saturating_sub(before_send),
+            download: total.saturating_sub(headers_received),
+            total,
+            secure,
+        }
+    }
+
+    pub fn time_to_first_byte(&self) -> Duration {
+        self.total.saturating_sub(self.download)
+    }
+
+    /// Names the connect phase for what it measured, so the handshake isn't taken for part
+    /// of plain TCP connect
+    pub fn connect_label(&self) -> &'static str {
+        match self.secure {
+            true => "Connect + TLS",
+            false => "Connect",
+        }
+    }
+
+    /// Phases in order with their offset from the start, for drawing a waterfall
+    pub fn phases(&self) -> Vec<(&'static str, Duration, Duration)> {
+        let phases = [
+            ("DNS", self.dns),
+            (self.connect_label(), self.connect),
+            ("Waiting", Some(self.waiting)),
+            ("Download", Some(self.download)),
+        ];
+
+        let mut offset = Duration::ZERO;
+        let mut result = vec![];
+        for (label, duration) in phases {
+            if let Some(duration) = duration {
+                result.push((label, offset, duration));
+                offset += duration;
+            }
+        }
+        result
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use native::{PhaseRecorder, TimingLayer, TimingResolver, current, record};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use std::{
+        future::Future,
+        pin::Pin,
+        sync::{Arc, Mutex},
+        task::{Context, Poll},
+        time::Duration,
+    };
+
+    use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+    use web_time::Instant;
+
+    tokio::task_local! {
+        static RECORDER: Arc<PhaseRecorder>;
+    }
+
+    /// Runs a request with `recorder` receiving its phases. Clients are shared between requests,
+    /// so the resolver and connector find the recorder of the request they work for through here
+    pub async fn record<F: Future>(recorder: Arc<PhaseRecorder>, request: F) -> F::Output {
+        RECORDER.scope(recorder, request).await
+    }
+
+    /// Recorder of the request being sent on this task
+    pub fn current() -> Option<Arc<PhaseRecorder>> {
+        RECORDER.try_with(|recorder| recorder.clone()).ok()
+    }
+
+    /// Filled in by the resolver and connector of a client while a request connects
+    #[derive(Default)]
+    pub struct PhaseRecorder {
+        dns: Mutex<Option<Duration>>,
+        connect: Mutex<Option<Duration>>,
+    }
+
+    impl PhaseRecorder {
+        pub fn dns(&self) -> Option<Duration> {
+            *self.dns.lock().unwrap()
+        }
+
+        pub fn connect(&self) -> Option<Duration> {
+            *self.connect.lock().unwrap()
+        }
+    }
+
+    /// System resolver that records how long lookups take
+    pub struct TimingResolver;
+
+    impl Resolve for TimingResolver {
+        fn resolve(&self, name: Name) -> Resolving {
+            let recorder = current();
+            Box::pin(async move {
+                let start = Instant::now();
+                let addrs = tokio::net::lookup_host((name.as_str(), 0)).await?;
+                if let Some(recorder) = recorder {
+                    *recorder.dns.lock().unwrap() = Some(start.elapsed());
+                }
+                Ok(Box::new(addrs.collect::<Vec<_>>().into_iter()) as Addrs)
+            })
+        }
+    }
+
+    /// Wraps reqwest's connector to time establishing connections, pooled ones never get here
+    #[derive(Clone)]
+    pub struct TimingLayer;
+
+    impl<S> tower_layer::Layer<S> for TimingLayer {
+        type Service = TimingConnector<S>;
+
+        fn layer(&self, inner: S) -> Self::Service {
+            TimingConnector { inner }
+        }
+    }
+
+    #[derive(Clone)]
+    pub struct TimingConnector<S> {
+        inner: S,
+    }
+
+    impl<S, R> tower_service::Service<R> for TimingConnector<S>
+    where
+        S: tower_service::Service<R>,
+        S::Future: Send + 'static,
+        S::Response: Send + 'static,
+        S::Error: Send + 'static,
+    {
+        type Response = S::Response;
+        type Error = S::Error;
+        type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+        fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            self.inner.poll_ready(cx)
+        }
+
+        fn call(&mut self, request: R) -> Self::Future {
+            let start = Instant::now();
+            let recorder = current();
+            let connecting = self.inner.call(request);
+            Box::pin(async move {
+                let result = connecting.await;
+                if let Some(recorder) = recorder {
+                    *recorder.connect.lock().unwrap() = Some(start.elapsed());
+                }
+                result
+            })
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use std::fmt::Write as _;
+
+/// Lowercase hex digits of the bytes, with `separator` between two bytes
+pub fn hex(bytes: &[u8], separator: &str) -> String {
+    bytes.iter().fold(String::new(), |mut hex, byte| {
+        if !hex.is_empty() {
+            hex.push_str(separator);
+        }
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    })
+}
+
+/// The whole file, the error names the path
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read_file(path: &str) -> Result<Vec<u8>, String> {
+    let path = path.trim();
+    std::fs::read(path).map_err(|err| format!("failed to read '{path}': {err}"))
+}
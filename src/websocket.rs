@@ -0,0 +1,598 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use futures_channel::{
+    mpsc::{self, UnboundedReceiver, UnboundedSender},
+    oneshot,
+};
+use web_time::SystemTime;
+
+use crate::{environment::Variables, util};
+
+/// Oldest entries are dropped past this, a chatty server would grow the log forever
+const MAX_LOG_ENTRIES: usize = 5000;
+
+/// How the text of a message is sent
+#[derive(Clone, Copy, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum MessageKind {
+    #[default]
+    Text,
+    /// A text frame, checked to be JSON first
+    Json,
+    /// The text is hex bytes like `48 65 6c 6c 6f`
+    Binary,
+}
+
+impl MessageKind {
+    pub fn values() -> [Self; 3] {
+        [Self::Text, Self::Json, Self::Binary]
+    }
+}
+
+impl std::fmt::Display for MessageKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Text => write!(f, "Text"),
+            Self::Json => write!(f, "JSON"),
+            Self::Binary => write!(f, "Binary"),
+        }
+    }
+}
+
+#[derive(Clone, Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct MessageTemplate {
+    pub name: String,
+    pub kind: MessageKind,
+    pub text: String,
+}
+
+/// What a WebSocket request sends once connected, the upgrade itself is made from the
+/// request's url, headers and auth
+#[derive(Clone, Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct WebSocketOptions {
+    pub kind: MessageKind,
+    /// The message being written
+    pub message: String,
+    pub templates: Vec<MessageTemplate>,
+}
+
+#[derive(Clone)]
+pub enum Frame {
+    Text(String),
+    Binary(Vec<u8>),
+    Ping(Vec<u8>),
+    Pong(Vec<u8>),
+    /// Code and reason are left out of a close frame without a status
+    Close {
+        code: Option<u16>,
+        reason: String,
+    },
+}
+
+impl Frame {
+    /// The message as it's sent, with `{{name}}` variables substituted
+    pub fn encode(kind: MessageKind, text: &str, variables: &Variables) -> Result<Self, String> {
+        let text = variables.interpolate(text);
+        match kind {
+            MessageKind::Text => Ok(Self::Text(text)),
+            MessageKind::Json => serde_json::from_str::<serde::de::IgnoredAny>(&text)
+                .map(|_| Self::Text(text.clone()))
+                .map_err(|err| format!("the message isn't valid JSON: {err}")),
+            MessageKind::Binary => parse_hex(&text).map(Self::Binary),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Text(_) => "Text",
+            Self::Binary(_) => "Binary",
+            Self::Ping(_) => "Ping",
+            Self::Pong(_) => "Pong",
+            Self::Close { .. } => "Close",
+        }
+    }
+
+    /// What's shown in the log, binary payloads as hex
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Text(text) => text.clone(),
+            Self::Binary(bytes) | Self::Ping(bytes) | Self::Pong(bytes) => util::hex(bytes, " "),
+            Self::Close { code: None, .. } => "No status".to_owned(),
+            Self::Close {
+                code: Some(code),
+                reason,
+            } => match reason.is_empty() {
+                true => format!("{code} {}", close_code_name(*code)),
+                false => format!("{code} {}, {reason}", close_code_name(*code)),
+            },
+        }
+    }
+}
+
+/// Whitespace between the bytes is optional
+fn parse_hex(text: &str) -> Result<Vec<u8>, String> {
+    let digits = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<Vec<_>>();
+    if digits.len() % 2 != 0 {
+        return Err("binary messages are hex bytes, one digit is missing".to_owned());
+    }
+    digits
+        .chunks(2)
+        .map(|pair| {
+            let pair = pair.iter().collect::<String>();
+            u8::from_str_radix(&pair, 16).map_err(|_| format!("'{pair}' isn't a hex byte"))
+        })
+        .collect()
+}
+
+/// Meaning of the close codes the spec defines
+pub fn close_code_name(code: u16) -> &'static str {
+    match code {
+        1000 => "Normal Closure",
+        1001 => "Going Away",
+        1002 => "Protocol Error",
+        1003 => "Unsupported Data",
+        1005 => "No Status Received",
+        1006 => "Abnormal Closure",
+        1007 => "Invalid Payload",
+        1008 => "Policy Violation",
+        1009 => "Message Too Big",
+        1010 => "Mandatory Extension",
+        1011 => "Internal Error",
+        1012 => "Service Restart",
+        1013 => "Try Again Later",
+        1014 => "Bad Gateway",
+        1015 => "TLS Handshake Failed",
+        3000..=3999 => "Registered",
+        4000..=4999 => "Application",
+        _ => "Unknown",
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Sent,
+    Received,
+}
+
+pub enum Event {
+    /// The server accepted the upgrade
+    Opened,
+    Frame(Direction, Frame),
+    Error(String),
+}
+
+pub struct LogEntry {
+    pub at: SystemTime,
+    pub event: Event,
+}
+
+#[derive(Clone, Default)]
+pub enum Status {
+    #[default]
+    Idle,
+    Connecting,
+    Open,
+    /// Our close frame went out, waiting for the server's
+    Closing,
+    Closed {
+        code: Option<u16>,
+        reason: String,
+    },
+    Failed(String),
+}
+
+impl Status {
+    /// Whether there's a connection, or one is being made
+    pub fn is_active(&self) -> bool {
+        matches!(self, Self::Connecting | Self::Open | Self::Closing)
+    }
+}
+
+#[derive(Default)]
+pub struct SessionState {
+    pub status: Status,
+    pub log: Vec<LogEntry>,
+    /// Frames for the connection's task to send, dropping it ends the task
+    outgoing: Option<UnboundedSender<Frame>>,
+    /// Bumped for every connection, see `Connection`
+    generation: u64,
+    /// Dropping it stops a connection that isn't open yet
+    cancel: Option<oneshot::Sender<()>>,
+}
+
+impl SessionState {
+    fn push(&mut self, event: Event) {
+        if self.log.len() >= MAX_LOG_ENTRIES {
+            self.log.remove(0);
+        }
+        self.log.push(LogEntry {
+            at: SystemTime::now(),
+            event,
+        });
+    }
+
+    fn fail(&mut self, reason: String) {
+        self.push(Event::Error(reason.clone()));
+        self.status = Status::Failed(reason);
+        self.outgoing = None;
+        self.cancel = None;
+    }
+}
+
+/// The connection of a WebSocket request with what went over it, clones share it
+#[derive(Clone, Default)]
+pub struct Session(Arc<Mutex<SessionState>>);
+
+impl Session {
+    pub fn lock(&self) -> MutexGuard<'_, SessionState> {
+        self.0.lock().unwrap()
+    }
+
+    pub fn status(&self) -> Status {
+        self.lock().status.clone()
+    }
+
+    /// Queues the frame on the open connection, it's logged once it's written
+    pub fn send(&self, frame: Frame) -> Result<(), String> {
+        let mut state = self.lock();
+        if !matches!(state.status, Status::Open) {
+            return Err("the connection isn't open".to_owned());
+        }
+        if matches!(frame, Frame::Close { .. }) {
+            state.status = Status::Closing;
+        }
+        let outgoing = state.outgoing.as_ref().ok_or("the connection isn't open")?;
+        outgoing
+            .unbounded_send(frame)
+            .map_err(|_| "the connection is gone".to_owned())
+    }
+
+    /// Adds an error to the log without touching the connection
+    pub fn warn(&self, message: impl Into<String>) {
+        self.lock().push(Event::Error(message.into()));
+    }
+
+    /// Starts over for a new connection, returns what its task reports to, where the frames
+    /// to send arrive and what resolves once it's stopped. A connection still going is
+    /// stopped, what it reports is ignored from then on
+    pub fn start(&self) -> (Connection, UnboundedReceiver<Frame>, oneshot::Receiver<()>) {
+        let (outgoing, receiver) = mpsc::unbounded();
+        let (cancel, cancelled) = oneshot::channel();
+        let mut state = self.lock();
+        state.status = Status::Connecting;
+        state.outgoing = Some(outgoing);
+        state.cancel = Some(cancel);
+        state.generation += 1;
+        let connection = Connection {
+            session: self.clone(),
+            generation: state.generation,
+        };
+        (connection, receiver, cancelled)
+    }
+
+    /// Drops the connection without a close frame, the log is kept. One still connecting
+    /// is stopped, and nothing it reports shows up anymore
+    pub fn fail(&self, reason: String) {
+        let mut state = self.lock();
+        state.fail(reason);
+        state.generation += 1;
+    }
+}
+
+/// Where the task of one connection reports to, clones share it
+#[derive(Clone)]
+pub struct Connection {
+    session: Session,
+    generation: u64,
+}
+
+impl Connection {
+    /// Runs `update` unless the session moved on to a newer connection, or was failed
+    fn update(&self, update: impl FnOnce(&mut SessionState)) {
+        let mut state = self.session.lock();
+        if state.generation == self.generation {
+            update(&mut state);
+        }
+    }
+
+    fn warn(&self, message: impl Into<String>) {
+        let message = message.into();
+        self.update(|state| state.push(Event::Error(message)));
+    }
+
+    pub fn fail(&self, reason: String) {
+        self.update(|state| state.fail(reason));
+    }
+
+    fn opened(&self) {
+        self.update(|state| {
+            state.push(Event::Opened);
+            state.status = Status::Open;
+        });
+    }
+
+    fn record(&self, direction: Direction, frame: Frame) {
+        let closed = match &frame {
+            Frame::Close { code, reason } if direction == Direction::Received => {
+                Some(Status::Closed {
+                    code: *code,
+                    reason: reason.clone(),
+                })
+            }
+            _ => None,
+        };
+        self.update(|state| {
+            state.push(Event::Frame(direction, frame));
+            if let Some(closed) = closed {
+                state.status = closed;
+                state.outgoing = None;
+            }
+        });
+    }
+
+    /// The connection ended, without a close frame if it isn't closed already
+    fn ended(&self) {
+        self.update(|state| {
+            if state.status.is_active() {
+                state.status = Status::Closed {
+                    code: Some(1006),
+                    reason: "the connection dropped".to_owned(),
+                };
+            }
+            state.outgoing = None;
+        });
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use native::run;
+
+#[cfg(target_arch = "wasm32")]
+pub use web::run;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use std::{pin::pin, time::Duration};
+
+    use futures_channel::{mpsc::UnboundedReceiver, oneshot};
+    use futures_util::{
+        SinkExt, StreamExt,
+        future::{self, Either},
+    };
+    use tokio_tungstenite::tungstenite::{
+        self, Message,
+        client::IntoClientRequest,
+        protocol::{CloseFrame, frame::coding::CloseCode},
+    };
+
+    use super::{Connection, Direction, Frame};
+    use crate::client::{self, ClientSettings};
+
+    /// Connects with the request as the upgrade, then sends the queued frames and logs
+    /// everything until either side closes
+    pub async fn run(
+        connection: Connection,
+        request: http::Request<Vec<u8>>,
+        settings: ClientSettings,
+        mut outgoing: UnboundedReceiver<Frame>,
+        cancelled: oneshot::Receiver<()>,
+    ) {
+        let connecting = pin!(connect(&connection, request, &settings));
+        let mut stream = match future::select(cancelled, connecting).await {
+            // disconnected, or connected again, before it opened
+            Either::Left(_) => return,
+            Either::Right((Ok(stream), _)) => stream,
+            Either::Right((Err(err), _)) => return connection.fail(err),
+        };
+        connection.opened();
+
+        loop {
+            tokio::select! {
+                frame = outgoing.next() => {
+                    let Some(frame) = frame else {
+                        // closed by the server, or nothing can send on it anymore
+                        let _ = stream.close(None).await;
+                        break;
+                    };
+                    match stream.send(to_message(&frame)).await {
+                        Ok(()) => connection.record(Direction::Sent, frame),
+                        Err(err) => return connection.fail(err.to_string()),
+                    }
+                }
+                message = stream.next() => match message {
+                    Some(Ok(message)) => {
+                        if let Some(frame) = from_message(message) {
+                            connection.record(Direction::Received, frame);
+                        }
+                    }
+                    Some(Err(tungstenite::Error::ConnectionClosed)) | None => break,
+                    Some(Err(err)) => return connection.fail(err.to_string()),
+                },
+            }
+        }
+        connection.ended();
+    }
+
+    async fn connect(
+        connection: &Connection,
+        request: http::Request<Vec<u8>>,
+        settings: &ClientSettings,
+    ) -> Result<
+        tokio_tungstenite::WebSocketStream<
+            tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+        >,
+        String,
+    > {
+        // the generated request has the handshake headers, the request's own are added to it
+        let mut upgrade = request
+            .uri()
+            .to_string()
+            .into_client_request()
+            .map_err(|err| err.to_string())?;
+        for (name, value) in request.headers() {
+            let body_header = matches!(
+                *name,
+                http::header::CONTENT_TYPE | http::header::CONTENT_LENGTH
+            );
+            if !body_header && !upgrade.headers().contains_key(name) {
+                upgrade.headers_mut().append(name, value.clone());
+            }
+        }
+        if !settings.proxy.trim().is_empty() {
+            connection.warn("the proxy isn't used for WebSocket connections");
+        }
+
+        let connect = tokio_tungstenite::connect_async_tls_with_config(
+            upgrade,
+            None,
+            false,
+            Some(tokio_tungstenite::Connector::NativeTls(
+                client::tls_connector(settings, &["http/1.1"])?,
+            )),
+        );
+        let connected = match settings.connect_timeout {
+            0 => connect.await,
+            timeout => tokio::time::timeout(Duration::from_millis(timeout), connect)
+                .await
+                .map_err(|_| "the connection timed out".to_owned())?,
+        };
+        match connected {
+            Ok((stream, _)) => Ok(stream),
+            Err(tungstenite::Error::Http(response)) => Err(format!(
+                "the server answered {} instead of switching protocols",
+                response.status()
+            )),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    fn to_message(frame: &Frame) -> Message {
+        match frame {
+            Frame::Text(text) => Message::text(text.as_str()),
+            Frame::Binary(bytes) => Message::binary(bytes.clone()),
+            Frame::Ping(bytes) => Message::Ping(bytes.clone().into()),
+            Frame::Pong(bytes) => Message::Pong(bytes.clone().into()),
+            Frame::Close { code, reason } => Message::Close(code.map(|code| CloseFrame {
+                code: CloseCode::from(code),
+                reason: reason.as_str().into(),
+            })),
+        }
+    }
+
+    fn from_message(message: Message) -> Option<Frame> {
+        Some(match message {
+            Message::Text(text) => Frame::Text(text.to_string()),
+            Message::Binary(bytes) => Frame::Binary(bytes.to_vec()),
+            Message::Ping(bytes) => Frame::Ping(bytes.to_vec()),
+            Message::Pong(bytes) => Frame::Pong(bytes.to_vec()),
+            Message::Close(frame) => Frame::Close {
+                code: frame.as_ref().map(|frame| u16::from(frame.code)),
+                reason: frame
+                    .map(|frame| frame.reason.to_string())
+                    .unwrap_or_default(),
+            },
+            Message::Frame(_) => return None,
+        })
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use futures_channel::{mpsc::UnboundedReceiver, oneshot};
+    use futures_util::StreamExt;
+    use wasm_bindgen::{JsCast, closure::Closure};
+    use web_sys::{BinaryType, CloseEvent, Event, MessageEvent, WebSocket};
+
+    use super::{Connection, Direction, Frame};
+    use crate::client::ClientSettings;
+
+    /// Connects with the browser's WebSocket, which makes the upgrade itself. Only the url
+    /// carries over from the request, the browser doesn't let headers be set
+    pub async fn run(
+        connection: Connection,
+        request: http::Request<Vec<u8>>,
+        _settings: ClientSettings,
+        mut outgoing: UnboundedReceiver<Frame>,
+        // the socket opens by itself, stopping it drops `outgoing` which closes it
+        _cancelled: oneshot::Receiver<()>,
+    ) {
+        let socket = match WebSocket::new(&request.uri().to_string()) {
+            Ok(socket) => socket,
+            Err(err) => return connection.fail(format!("{err:?}")),
+        };
+        socket.set_binary_type(BinaryType::Arraybuffer);
+        if request.headers().keys().any(|name| {
+            !matches!(
+                *name,
+                http::header::ACCEPT | http::header::CONTENT_TYPE | http::header::CONTENT_LENGTH
+            )
+        }) {
+            connection.warn("the browser doesn't let headers be set on WebSocket connections");
+        }
+
+        let on_open = {
+            let connection = connection.clone();
+            Closure::<dyn FnMut(Event)>::new(move |_| connection.opened())
+        };
+        let on_message = {
+            let connection = connection.clone();
+            Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+                let data = event.data();
+                let frame = match data.as_string() {
+                    Some(text) => Frame::Text(text),
+                    None => Frame::Binary(js_sys::Uint8Array::new(&data).to_vec()),
+                };
+                connection.record(Direction::Received, frame);
+            })
+        };
+        let on_close = {
+            let connection = connection.clone();
+            Closure::<dyn FnMut(CloseEvent)>::new(move |event: CloseEvent| {
+                let frame = Frame::Close {
+                    code: Some(event.code()),
+                    reason: event.reason(),
+                };
+                connection.record(Direction::Received, frame);
+                connection.ended();
+            })
+        };
+        // the browser keeps the reason to itself
+        let on_error = {
+            let connection = connection.clone();
+            Closure::<dyn FnMut(Event)>::new(move |_| connection.warn("the connection failed"))
+        };
+        socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+        socket.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+
+        // ends once the session is closed, the callbacks live until then
+        while let Some(frame) = outgoing.next().await {
+            let sent = match &frame {
+                Frame::Text(text) => socket.send_with_str(text),
+                Frame::Binary(bytes) => socket.send_with_u8_array(bytes),
+                Frame::Close { code, reason } => match code {
+                    Some(code) => socket.close_with_code_and_reason(*code, reason),
+                    None => socket.close(),
+                },
+                Frame::Ping(_) | Frame::Pong(_) => {
+                    connection.warn("the browser doesn't let pages send ping and pong frames");
+                    continue;
+                }
+            };
+            match sent {
+                Ok(()) => connection.record(Direction::Sent, frame),
+                Err(err) => connection.warn(format!("{err:?}")),
+            }
+        }
+        let _ = socket.close();
+        socket.set_onopen(None);
+        socket.set_onmessage(None);
+        socket.set_onclose(None);
+        socket.set_onerror(None);
+    }
+}